@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, BTreeSet};
+use l_group_formulas::formula::LGroupFormula;
+use l_group_formulas::free_group_term::FreeGroupTerm;
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::literal::Literal;
+use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
+
+/// A clause of a `ThreeCNF`, i.e., a join of short free group terms.
+pub type Clause = BTreeSet<ShortFreeGroupTerm>;
+
+/// Evidence that an `LGroupFormula` holds in all l-groups, or in all
+/// representable l-groups if `representable` is set.
+///
+/// The formula holds if, and only if, `e <= meetand` holds for every meetand
+/// of splitting its inequations at meets, which is the case if, and only if,
+/// no clause of the `ThreeCNF` of a meetand extends to a right order, or to a
+/// bi-order if `representable` is set. That a clause does not extend is shown
+/// by its `Refutation`.
+///
+/// Certificates are written and read in a line-based text format:
+/// ```text
+/// formula e<=(x v X)
+/// order right
+/// meetand ((x v X) * e)
+/// clause v1, V1xv2, V1Xv2, V2
+/// refutation v1, V1xv2, V1Xv2, V2
+/// closure v1 * V1xv2 = xv2, xv2 * V2 = x, v1 * V1Xv2 = Xv2, Xv2 * V2 = X, x * X = e
+/// ```
+/// where the elements a `branch` line chooses are followed by the two
+/// refutations it needs, each indented by two more spaces.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use certificate_checker::certificate::{Certificate, Refutation};
+/// let text = "formula e <= x v X
+/// order right
+/// meetand (x v X) * e
+/// clause v1, V1xv2, V1Xv2, V2
+/// refutation v1, V1xv2, V1Xv2, V2
+/// closure v1 * V1xv2 = xv2, xv2 * V2 = x, v1 * V1Xv2 = Xv2, Xv2 * V2 = X, x * X = e";
+/// let certificate: Certificate = text.parse().unwrap();
+/// assert_eq!(1, certificate.meetands.len());
+/// assert!(matches!(certificate.refutations.values().next(), Some(Refutation::Closure(_))));
+/// assert_eq!(Ok(certificate.clone()), certificate.to_string().parse());
+/// let error = text.replace("closure", "clause").parse::<Certificate>().unwrap_err();
+/// assert_eq!(6, error.line);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate {
+    pub formula:       LGroupFormula,
+    pub representable: bool,
+    /// The meetands of the inequations of `formula`, in order.
+    pub meetands:      Vec<Meetand>,
+    pub refutations:   BTreeMap<Clause, Refutation>
+}
+
+/// A meetand of splitting an inequation at meets, with the clauses of its `ThreeCNF`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meetand {
+    pub term:    LGroupTerm,
+    pub clauses: Vec<Clause>
+}
+
+/// Why a set of elements, which are supposed to be below `e`, does not
+/// extend to an order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Refutation {
+    /// Closing the elements under the steps gives `e`.
+    Closure(Vec<Step>),
+    /// Every order has the element of the ball of radius 2 or its inverse
+    /// below `e`. Neither extends, by the first refutation, which may use the
+    /// element, and the second one, which may use its inverse.
+    Branch(ShortFreeGroupTerm, Box<Refutation>, Box<Refutation>)
+}
+
+/// A step of closing a set of elements below `e` of a partial order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// The product of the first two elements is the third.
+    Product(ShortFreeGroupTerm, ShortFreeGroupTerm, ShortFreeGroupTerm),
+    /// The conjugate `g⁻¹xg` of the first element `x` by the literal `g` is
+    /// the third, which holds in bi-orders only.
+    Conjugate(ShortFreeGroupTerm, Literal, ShortFreeGroupTerm)
+}
+
+impl std::fmt::Display for Certificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "formula {}", self.formula)?;
+        writeln!(f, "order {}", if self.representable { "bi" } else { "right" })?;
+        for meetand in &self.meetands {
+            writeln!(f, "meetand {}", meetand.term)?;
+            for clause in &meetand.clauses {
+                writeln!(f, "clause {}", join(clause))?;
+            }
+        }
+        for (clause, refutation) in &self.refutations {
+            writeln!(f, "refutation {}", join(clause))?;
+            write_refutation(f, refutation, 0)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_refutation(f: &mut std::fmt::Formatter, refutation: &Refutation, depth: usize) -> std::fmt::Result {
+    let indent = "  ".repeat(depth);
+    match refutation {
+        Refutation::Closure(steps) => {
+            let steps: Vec<String> = steps.iter().map(|step| step.to_string()).collect();
+            writeln!(f, "{}closure {}", indent, steps.join(", "))
+        },
+        Refutation::Branch(x, with, with_inverse) => {
+            writeln!(f, "{}branch {}", indent, x)?;
+            write_refutation(f, with, depth + 1)?;
+            write_refutation(f, with_inverse, depth + 1)
+        }
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Step::Product(x, y, product) => write!(f, "{} * {} = {}", x, y, product),
+            Step::Conjugate(x, g, conjugate) => write!(f, "{} ^ {} = {}", x, g, conjugate)
+        }
+    }
+}
+
+/// The elements of `clause`, separated by commas.
+pub fn join(clause: &Clause) -> String {
+    clause.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ")
+}
+
+/// Why a certificate could not be read, with the number of the line, starting at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateParsingError {
+    pub line:    usize,
+    pub message: String
+}
+
+impl std::fmt::Display for CertificateParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for CertificateParsingError {}
+
+impl std::str::FromStr for Certificate {
+    type Err = CertificateParsingError;
+
+    fn from_str(s: &str) -> Result<Certificate, CertificateParsingError> {
+        let mut lines = Lines {
+            lines: s.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).collect(),
+            next:  0
+        };
+        let formula = lines.expect("formula")?;
+        let formula = lines.parsed(formula.parse())?;
+        let representable = match lines.expect("order")? {
+            "right" => false,
+            "bi"    => true,
+            order   => return Err(lines.error(format!("unknown order {}, expected right or bi", order)))
+        };
+        let mut meetands = Vec::new();
+        while let Some(term) = lines.next_if("meetand") {
+            let term = lines.parsed(term.parse())?;
+            let mut clauses = Vec::new();
+            while let Some(clause) = lines.next_if("clause") {
+                clauses.push(lines.parsed(parse_clause(clause))?);
+            }
+            meetands.push(Meetand { term, clauses });
+        }
+        let mut refutations = BTreeMap::new();
+        while let Some(clause) = lines.next_if("refutation") {
+            let clause = lines.parsed(parse_clause(clause))?;
+            let refutation = parse_refutation(&mut lines)?;
+            refutations.insert(clause, refutation);
+        }
+        if let Some((keyword, _)) = lines.next() {
+            return Err(lines.error(format!("unexpected {}", keyword)));
+        }
+        Ok(Certificate { formula, representable, meetands, refutations })
+    }
+}
+
+/// The non-empty lines of a certificate with their indices, split into a
+/// keyword and the rest when read.
+struct Lines<'a> {
+    lines: Vec<(usize, &'a str)>,
+    next:  usize
+}
+
+impl<'a> Lines<'a> {
+    fn peek(&self) -> Option<(&'a str, &'a str)> {
+        let line = self.lines.get(self.next)?.1.trim();
+        Some(line.split_once(' ').unwrap_or((line, "")))
+    }
+
+    fn next(&mut self) -> Option<(&'a str, &'a str)> {
+        let line = self.peek()?;
+        self.next += 1;
+        Some(line)
+    }
+
+    /// Returns the rest of the next line if it starts with `keyword`.
+    fn next_if(&mut self, keyword: &str) -> Option<&'a str> {
+        match self.peek() {
+            Some((found, _)) if found == keyword => self.next().map(|(_, rest)| rest),
+            _ => None
+        }
+    }
+
+    fn expect(&mut self, keyword: &str) -> Result<&'a str, CertificateParsingError> {
+        match self.next() {
+            Some((found, rest)) if found == keyword => Ok(rest),
+            Some((found, _)) => Err(self.error(format!("expected {}, found {}", keyword, found))),
+            None => Err(self.error(format!("expected {}, found the end", keyword)))
+        }
+    }
+
+    fn parsed<T, E: std::fmt::Display>(&self, result: Result<T, E>) -> Result<T, CertificateParsingError> {
+        result.map_err(|e| self.error(e.to_string()))
+    }
+
+    /// Returns an error in the line read last.
+    fn error(&self, message: String) -> CertificateParsingError {
+        let line = self.next.checked_sub(1).and_then(|i| self.lines.get(i)).map_or(0, |(i, _)| *i);
+        CertificateParsingError { line: line + 1, message }
+    }
+}
+
+fn parse_refutation(lines: &mut Lines) -> Result<Refutation, CertificateParsingError> {
+    match lines.next() {
+        Some(("closure", steps)) => {
+            let steps = steps.split(", ").filter(|step| !step.is_empty()).map(parse_step).collect();
+            Ok(Refutation::Closure(lines.parsed(steps)?))
+        },
+        Some(("branch", x)) => {
+            let x = lines.parsed(parse_short_term(x))?;
+            let with = parse_refutation(lines)?;
+            let with_inverse = parse_refutation(lines)?;
+            Ok(Refutation::Branch(x, Box::new(with), Box::new(with_inverse)))
+        },
+        Some((found, _)) => Err(lines.error(format!("expected closure or branch, found {}", found))),
+        None => Err(lines.error(String::from("expected closure or branch, found the end")))
+    }
+}
+
+fn parse_clause(s: &str) -> Result<Clause, String> {
+    s.split(", ").filter(|x| !x.is_empty()).map(parse_short_term).collect()
+}
+
+fn parse_step(s: &str) -> Result<Step, String> {
+    let (operation, result) = s.split_once(" = ").ok_or(format!("expected = in {}", s))?;
+    let result = parse_short_term(result)?;
+    if let Some((x, y)) = operation.split_once(" * ") {
+        return Ok(Step::Product(parse_short_term(x)?, parse_short_term(y)?, result));
+    }
+    if let Some((x, g)) = operation.split_once(" ^ ") {
+        let g = parse_short_term(g)?;
+        return match (g.left, g.mid) {
+            (Some(g), None) => Ok(Step::Conjugate(parse_short_term(x)?, g, result)),
+            _ => Err(format!("{} is not a literal", g))
+        };
+    }
+    Err(format!("expected * or ^ in {}", s))
+}
+
+fn parse_short_term(s: &str) -> Result<ShortFreeGroupTerm, String> {
+    let term: FreeGroupTerm = s.trim().parse().map_err(|e: l_group_formulas::parsing_error::ParsingError| e.to_string())?;
+    match term.literals.len() <= 3 {
+        true  => Ok(ShortFreeGroupTerm::from(term)),
+        false => Err(format!("{} is longer than 3", term))
+    }
+}
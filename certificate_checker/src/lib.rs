@@ -0,0 +1,266 @@
+//! Checks certificates of the validity of l-group equations and inequations,
+//! as `l_group_validity::certify` writes them, without searching for orders.
+//! So a formula can be trusted to be valid without trusting the search.
+//!
+//! # Trusted code
+//! The checker does not check the meetands and clauses of a certificate step
+//! by step. Instead, it computes them again, by `split_at_meets` and
+//! `ThreeCNF::with_atoms` of `l_group_cnf`, and compares. This is the same
+//! code the search uses, so a bug in `l_group_cnf`, or in the terms and their
+//! reduction in `l_group_formulas`, is not noticed, and both crates are
+//! trusted. What the checker verifies independently are the refutations of
+//! the clauses, with every product and conjugate computed in the free group.
+
+use std::collections::BTreeSet;
+use l_group_formulas::formula::LGroupFormula;
+use l_group_formulas::free_group_term::FreeGroupTerm;
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::literal::Literal;
+use l_group_formulas::short_free_group_term::{ShortFreeGroupTerm, Len};
+use l_group_formulas::Term;
+use l_group_cnf::split_at_meets::split_at_meets;
+use l_group_cnf::three_cnf::ThreeCNF;
+use certificate::{Certificate, Clause, Refutation, Step, join};
+
+/// Certificates, and their text format.
+pub mod certificate;
+
+/// Why a `Certificate` does not show that its formula is valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckError {
+    /// The meetands are not those of splitting the inequations of the formula at meets.
+    Meetands,
+    /// The clauses of the meetand are not those of its `ThreeCNF`, with the
+    /// single atoms kept.
+    Clauses(LGroupTerm),
+    /// The clause has no refutation.
+    Unrefuted(Clause),
+    /// The refutation of the clause branches on the element, which is `e` or
+    /// not in the ball of radius 2 in the letters of the clause.
+    Branch(Clause, ShortFreeGroupTerm),
+    /// The step in the refutation of the clause does not hold, or uses an
+    /// element not known to be below `e`.
+    Step(Clause, Box<Step>),
+    /// A closure in the refutation of the clause does not give `e`.
+    NoIdentity(Clause)
+}
+
+impl std::fmt::Display for CheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CheckError::Meetands => write!(f, "the meetands are not those of the formula"),
+            CheckError::Clauses(meetand) => write!(f, "the clauses of {} are not those of its normal form", meetand),
+            CheckError::Unrefuted(clause) => write!(f, "the clause {} is not refuted", join(clause)),
+            CheckError::Branch(clause, x) => write!(f, "the refutation of {} cannot branch on {}", join(clause), x),
+            CheckError::Step(clause, step) => write!(f, "the step {} in the refutation of {} does not follow", step, join(clause)),
+            CheckError::NoIdentity(clause) => write!(f, "a closure in the refutation of {} does not give e", join(clause))
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+/// Returns whether `certificate` shows that its formula holds in all
+/// l-groups, or in all representable l-groups if it says so, or what is
+/// wrong with it otherwise.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use certificate_checker::{check, CheckError};
+/// use certificate_checker::certificate::Certificate;
+/// let text = "formula e <= x v X
+/// order right
+/// meetand (x v X) * e
+/// clause v1, V1xv2, V1Xv2, V2
+/// refutation v1, V1xv2, V1Xv2, V2
+/// closure v1 * V1xv2 = xv2, xv2 * V2 = x, v1 * V1Xv2 = Xv2, Xv2 * V2 = X, x * X = e";
+/// let certificate: Certificate = text.parse().unwrap();
+/// assert_eq!(Ok(()), check(&certificate));
+/// let wrong: Certificate = text.replace("xv2 * V2 = x", "xv2 * v2 = x").parse().unwrap();
+/// assert!(matches!(check(&wrong), Err(CheckError::Step(_, _))));
+/// ```
+pub fn check(certificate: &Certificate) -> Result<(), CheckError> {
+    let meetands: Vec<LGroupTerm> = inequations(&certificate.formula).into_iter().flat_map(split_at_meets).collect();
+    if !meetands.iter().eq(certificate.meetands.iter().map(|meetand| &meetand.term)) {
+        return Err(CheckError::Meetands);
+    }
+    let mut checked = BTreeSet::new();
+    for meetand in &certificate.meetands {
+        let clauses: Vec<Clause> = ThreeCNF::with_atoms(meetand.term.clone()).meetands.into_iter().collect();
+        if clauses != meetand.clauses {
+            return Err(CheckError::Clauses(meetand.term.clone()));
+        }
+        for clause in clauses {
+            if checked.contains(&clause) {
+                continue;
+            }
+            match certificate.refutations.get(&clause) {
+                Some(refutation) => check_refutation(&clause, refutation, certificate.representable)?,
+                None => return Err(CheckError::Unrefuted(clause))
+            };
+            checked.insert(clause);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the terms `t` such that the `LGroupFormula` holds if, and only if,
+/// `e <= t` holds for all of them.
+fn inequations(formula: &LGroupFormula) -> Vec<LGroupTerm> {
+    match formula {
+        LGroupFormula::LGroupInequation(lhs, rhs) => vec![rhs.clone() * lhs.inverse()],
+        LGroupFormula::LGroupEquation(lhs, rhs) => vec![
+            rhs.clone() * lhs.inverse(),
+            lhs.clone() * rhs.inverse()
+        ]
+    }
+}
+
+/// Returns whether `refutation` shows that `clause` does not extend to a
+/// right order, or to a bi-order if `normal` is set, or what is wrong with
+/// it otherwise. That is, there is no
+/// subset of the ball of radius 3 in the letters of `clause` containing it,
+/// which does not contain `e`, is closed under products staying in the
+/// ball, and under conjugates by letters if `normal` is set, and contains
+/// every element of the ball of radius 2 or its inverse.
+pub fn check_refutation(clause: &Clause, refutation: &Refutation, normal: bool) -> Result<(), CheckError> {
+    let mut letters = BTreeSet::new();
+    for x in clause {
+        for a in word(x) {
+            letters.insert(a);
+            letters.insert(a.inverse());
+        }
+    }
+    let identity = ShortFreeGroupTerm::new(None, None, None);
+    // the refutations left, with the elements below `e` they may use
+    let mut todo = vec![(refutation, clause.clone())];
+    while let Some((refutation, mut below)) = todo.pop() {
+        match refutation {
+            Refutation::Branch(x, with, with_inverse) => {
+                if x.is_empty() || x.len() > 2 || !is_reduced_in(x, &letters) {
+                    return Err(CheckError::Branch(clause.clone(), *x));
+                }
+                let mut with_x = below.clone();
+                with_x.insert(*x);
+                below.insert(x.inverse());
+                todo.push((with, with_x));
+                todo.push((with_inverse, below));
+            },
+            Refutation::Closure(steps) => {
+                for step in steps {
+                    let (follows, result) = match step {
+                        Step::Product(x, y, product) => (
+                            below.contains(x) && below.contains(y) && short_product(&[*x, *y]) == Some(*product),
+                            product
+                        ),
+                        Step::Conjugate(x, g, conjugate) => (
+                            normal && letters.contains(g) && below.contains(x)
+                                && short_product(&[ShortFreeGroupTerm::from(g.inverse()), *x, ShortFreeGroupTerm::from(*g)]) == Some(*conjugate),
+                            conjugate
+                        )
+                    };
+                    if !follows {
+                        return Err(CheckError::Step(clause.clone(), Box::new(*step)));
+                    }
+                    below.insert(*result);
+                }
+                if !below.contains(&identity) {
+                    return Err(CheckError::NoIdentity(clause.clone()));
+                }
+            }
+        };
+    }
+    Ok(())
+}
+
+/// Returns the reduced product of `factors`, if it has length at most 3.
+fn short_product(factors: &[ShortFreeGroupTerm]) -> Option<ShortFreeGroupTerm> {
+    let product = factors.iter().fold(FreeGroupTerm::new(Vec::new()), |product, x| product * FreeGroupTerm::from(*x));
+    match product.literals.len() <= 3 {
+        true  => Some(ShortFreeGroupTerm::from(product)),
+        false => None
+    }
+}
+
+/// Returns whether `x` is a reduced word in `letters`.
+fn is_reduced_in(x: &ShortFreeGroupTerm, letters: &BTreeSet<Literal>) -> bool {
+    let word = word(x);
+    word.iter().all(|a| letters.contains(a)) && word.windows(2).all(|w| w[1] != w[0].inverse())
+}
+
+fn word(x: &ShortFreeGroupTerm) -> Vec<Literal> {
+    [x.left, x.mid, x.right].iter().flatten().copied().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CERTIFICATE: &str = "formula e <= x v yXY
+order bi
+meetand ((x v yXY) * e)
+clause v1, V1xv2, V1yv3, V2, V3Xv4, V4Yv2
+refutation v1, V1xv2, V1yv3, V2, V3Xv4, V4Yv2
+closure V1xv2 * V2 = V1x, v1 ^ V3 = v3v1V3, V1yv3 ^ V3 = v3V1y, v1 ^ v4 = V4v1v4, V4Yv2 * V2 = V4Y, V3Xv4 ^ V3 = Xv4V3, V1x * Xv4V3 = V1v4V3, v3v1V3 * v3V1y = v3y, V4v1v4 * V4Y = V4v1Y, V1v4V3 * v3y = V1v4y, V4v1Y ^ y = YV4v1, V1v4y * YV4v1 = e";
+
+    fn certificate() -> Certificate {
+        CERTIFICATE.parse().unwrap()
+    }
+
+    #[test]
+    fn test_conjugates_need_bi_orders() {
+        assert_eq!(Ok(()), check(&certificate()));
+        let right = Certificate { representable: false, ..certificate() };
+        assert!(matches!(check(&right), Err(CheckError::Step(_, step)) if matches!(*step, Step::Conjugate(_, _, _))));
+    }
+
+    #[test]
+    fn test_meetands_and_clauses() {
+        let mut certificate = certificate();
+        certificate.formula = "e <= y v yXY".parse().unwrap();
+        assert_eq!(Err(CheckError::Meetands), check(&certificate));
+
+        let mut certificate = self::certificate();
+        certificate.meetands[0].clauses[0].remove(&ShortFreeGroupTerm::from("v1"));
+        assert!(matches!(check(&certificate), Err(CheckError::Clauses(_))));
+
+        let mut certificate = self::certificate();
+        certificate.refutations.clear();
+        assert!(matches!(check(&certificate), Err(CheckError::Unrefuted(_))));
+
+        // the meetand `xyzw` is a long atom, whose clause has to be listed
+        let certificate: Certificate = "formula e <= xyzw\norder right\nmeetand (xyzw * e)".parse().unwrap();
+        assert!(matches!(check(&certificate), Err(CheckError::Clauses(_))));
+        let certificate: Certificate = "formula e <= xyzw\norder right\nmeetand (xyzw * e)\nclause xyv1, V1zw".parse().unwrap();
+        assert!(matches!(check(&certificate), Err(CheckError::Unrefuted(_))));
+    }
+
+    #[test]
+    fn test_refutations() {
+        let certificate = certificate();
+        let (clause, refutation) = certificate.refutations.iter().next().unwrap();
+        let steps = match refutation {
+            Refutation::Closure(steps) => steps.clone(),
+            _ => panic!("The refutation does not branch.")
+        };
+        let branch = |x: &str| Refutation::Branch(ShortFreeGroupTerm::from(x), Box::new(refutation.clone()), Box::new(refutation.clone()));
+        assert_eq!(Ok(()), check_refutation(clause, &branch("v1y"), true));
+        for x in &["e", "v1yv3", "z"] {
+            assert_eq!(Err(CheckError::Branch(clause.clone(), ShortFreeGroupTerm::from(*x))), check_refutation(clause, &branch(x), true));
+        }
+
+        let unfinished = Refutation::Closure(steps[.. steps.len() - 1].to_vec());
+        assert_eq!(Err(CheckError::NoIdentity(clause.clone())), check_refutation(clause, &unfinished, true));
+        // the steps have to be in order
+        let mut reversed = steps.clone();
+        reversed.reverse();
+        assert!(matches!(check_refutation(clause, &Refutation::Closure(reversed), true), Err(CheckError::Step(_, _))));
+        // the elements chosen in a branch are only known below it
+        let choice = Refutation::Branch(ShortFreeGroupTerm::from("x"), Box::new(Refutation::Closure(Vec::new())), Box::new(refutation.clone()));
+        assert_eq!(Err(CheckError::NoIdentity(clause.clone())), check_refutation(clause, &choice, true));
+        let choice = Refutation::Branch(ShortFreeGroupTerm::from("X"), Box::new(refutation.clone()),
+            Box::new(Refutation::Closure(vec![Step::Product(ShortFreeGroupTerm::from("x"), ShortFreeGroupTerm::from("X"), ShortFreeGroupTerm::from("e"))])));
+        assert!(matches!(check_refutation(clause, &choice, true), Err(CheckError::Step(_, _))));
+    }
+}
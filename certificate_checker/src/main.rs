@@ -0,0 +1,33 @@
+use std::io::{self, Read};
+use certificate_checker::check;
+use certificate_checker::certificate::Certificate;
+
+/// Reads a certificate from the file given as the argument, or from standard
+/// input if there is none, and checks it. Exits with status 1 if the
+/// certificate cannot be read or does not show that its formula holds.
+fn main() -> io::Result<()> {
+    let text = match std::env::args().nth(1) {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        }
+    };
+    let certificate: Certificate = match text.parse() {
+        Ok(certificate) => certificate,
+        Err(e) => {
+            println!("The certificate cannot be read, at {}.", e);
+            std::process::exit(1);
+        }
+    };
+    match check(&certificate) {
+        Ok(()) => println!("{} holds in all {}l-groups.", certificate.formula,
+                           if certificate.representable { "representable " } else { "" }),
+        Err(e) => {
+            println!("The certificate does not show that {} holds, as {}.", certificate.formula, e);
+            std::process::exit(1);
+        }
+    };
+    Ok(())
+}
@@ -1,20 +1,163 @@
-use std::io;
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use std::collections::BTreeSet;
 use l_group_formulas::formula::LGroupFormula;
-use l_group_validity::is_valid;
+use l_group_formulas::mv_formula::MVFormula;
+use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
+use l_group_validity::{is_valid_with, is_valid_certified, is_valid_mv, write_dimacs};
+use l_group_validity::options::{Backend, Options};
+use l_group_validity::mv::MVValidityResult;
+use l_group_validity::validity_result::{ValidityResult, Direction};
+use l_group_validity::counterexample::find_counterexample_with;
 
+/// Reads a formula from standard input and checks it. It is read as an
+/// MV-formula if it has one of `⊕⊙¬→~+` or `->`, which l-group formulas do
+/// not, or with `--mv`, e.g., for MV-formulas with `*` only. With `--sat`, clauses
+/// are checked by the SAT solver, with `--dimacs <directory>`, they are
+/// also written to files in the DIMACS format, with `--certificate <file>`, a
+/// certificate of a valid formula is written to the file, which the
+/// `certificate_checker` checks, found by the search in the same run, and
+/// with `--timeout <seconds>`, the check
+/// gives up after that many seconds. With `--verbosity <level>`,
+/// one of `off`, `error`, `warn`, `info` (the default), `debug` and `trace`,
+/// the steps of the check are logged up to that level, with the time of each
+/// step from `debug` on.
 fn main() -> io::Result<()> {
-    println!("Please enter an l-group equation or inequation.");
+    let mut options = Options::default();
+    let mut dimacs_directory = None;
+    let mut certificate_path = None;
+    let mut verbosity = LevelFilter::INFO;
+    let mut mv = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--sat" => options.backend = Backend::Sat,
+            "--mv" => mv = true,
+            "--dimacs" => dimacs_directory = args.next(),
+            "--certificate" => certificate_path = args.next(),
+            "--timeout" => match args.next().and_then(|seconds| seconds.parse().ok()) {
+                Some(seconds) => options.limits.time = Some(Duration::from_secs_f64(seconds)),
+                None => {
+                    println!("The argument --timeout needs a number of seconds.");
+                    return Ok(());
+                }
+            },
+            "--verbosity" => match args.next().and_then(|level| level.parse().ok()) {
+                Some(level) => verbosity = level,
+                None => {
+                    println!("The argument --verbosity needs one of off, error, warn, info, debug and trace.");
+                    return Ok(());
+                }
+            },
+            _ => {
+                println!("Unknown argument {}. The arguments are --mv, --sat, --dimacs <directory>, --certificate <file>, --timeout <seconds> and --verbosity <level>.", arg);
+                return Ok(());
+            }
+        };
+    }
+    tracing_subscriber::fmt()
+        .with_max_level(verbosity)
+        .with_ansi(io::stdout().is_terminal())
+        // without the time of events, but with the time spent in spans
+        .event_format(tracing_subscriber::fmt::format().with_target(false).without_time())
+        .with_span_events(match verbosity >= LevelFilter::DEBUG {
+            true  => FmtSpan::CLOSE,
+            false => FmtSpan::NONE
+        })
+        .init();
+
+    println!("Please enter an l-group equation or inequation, or an MV-formula.");
     let mut buffer = String::new();
     io::stdin().read_line(&mut buffer)?;
 
-    let formula = LGroupFormula::from(buffer.as_str());
-    println!("You entered: {}", formula.to_string());
-    let validity_string = match is_valid(formula.clone()) {
-        true => "valid",
-        false => "invalid"
+    // operations which only MV-formulas have
+    if mv || buffer.contains(|c| "⊕⊙¬→~+".contains(c)) || buffer.contains("->") {
+        match buffer.trim_end().parse::<MVFormula>() {
+            Ok(formula) => check_mv(formula),
+            Err(e) => println!("{}", e.render())
+        };
+        return Ok(());
+    }
+
+    let formula: LGroupFormula = match buffer.trim_end().parse() {
+        Ok(formula) => formula,
+        Err(e) => {
+            println!("{}", e.render());
+            return Ok(());
+        }
+    };
+    println!("You entered: {}", formula);
+    if let Some(directory) = dimacs_directory {
+        let paths = write_dimacs(formula.clone(), false, std::path::Path::new(&directory))?;
+        println!("Wrote {} clauses in the DIMACS format to {}.", paths.len(), directory);
+    }
+    if certificate_path.is_some() && options.backend == Backend::Sat {
+        println!("Certificates are found by the search, so --sat is ignored.");
+        options.backend = Backend::Search;
+    }
+    let start = Instant::now();
+    let (result, certificate) = match certificate_path {
+        Some(_) => is_valid_certified(formula.clone(), &options),
+        None => (is_valid_with(formula.clone(), &options), None)
+    };
+    println!("\n{} is {}", formula, result);
+    if let (Some(certificate), Some(path)) = (certificate, &certificate_path) {
+        std::fs::write(path, certificate.to_string())?;
+        println!("Wrote a certificate to {}.", path);
+    }
+    if let ValidityResult::Unknown { limit, .. } = &result {
+        println!("The check gave up at {}.", limit);
+    }
+    if let ValidityResult::Invalid { direction, countermodel, .. } = &result {
+        let (lhs, rhs) = match &formula {
+            LGroupFormula::LGroupEquation(lhs, rhs) | LGroupFormula::LGroupInequation(lhs, rhs) => (lhs, rhs)
+        };
+        match direction {
+            Direction::LeftBelowRight => println!("The inequation {} <= {} fails,", lhs, rhs),
+            Direction::RightBelowLeft => println!("The inequation {} <= {} fails,", rhs, lhs)
+        };
+        println!("because e <= {} fails.", countermodel.meetand);
+        println!("The clause {} extends to a right order, in which the following are below e:",
+                 join_short_terms(&countermodel.clause));
+        println!("{}", join_short_terms(&countermodel.negative_cone));
+        println!("\nIn the l-group of order-preserving bijections of the rationals:");
+        match find_counterexample_with(&formula, countermodel, &remaining(&options, start)) {
+            Some(Ok(counterexample)) => println!("{}", counterexample),
+            Some(Err(limit)) => println!("The search for a counterexample gave up at {}.", limit),
+            None => println!("The search for a counterexample found none.")
+        };
+    }
+    let statistics = result.statistics();
+    match options.backend {
+        Backend::Search => println!("\nChecked {} meetands and {} clauses, visiting {} subgroups.",
+                                    statistics.meetands, statistics.clauses, statistics.search_nodes),
+        Backend::Sat => println!("\nChecked {} meetands and {} clauses, running into {} conflicts.",
+                                 statistics.meetands, statistics.clauses, statistics.conflicts)
+    };
+
+    Ok(())
+}
+
+fn check_mv(formula: MVFormula) {
+    println!("You entered an MV-formula, which translates to {}", formula.formula);
+    match is_valid_mv(formula) {
+        MVValidityResult::Valid => println!("\nIt holds in all MV-algebras."),
+        MVValidityResult::Invalid { countermodel, .. } => {
+            println!("\nIt fails in the standard MV-algebra [0, 1]:");
+            println!("{}", countermodel);
+        }
     };
+}
 
-    println!("\n{} is {}", formula.to_string(), validity_string);
+/// Returns `options` with the time of `options.limits.time` left since `start`.
+fn remaining(options: &Options, start: Instant) -> Options {
+    let mut options = options.clone();
+    options.limits.time = options.limits.time.map(|time| time.saturating_sub(start.elapsed()));
+    options
+}
 
-    return Ok(());
+fn join_short_terms(terms: &BTreeSet<ShortFreeGroupTerm>) -> String {
+    terms.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(", ")
 }
@@ -0,0 +1,68 @@
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::literal::Literal;
+use std::collections::BTreeSet;
+
+/// A supply of variables `v1`, `v2`, ..., skipping the ones which are in use.
+///
+/// The transformations introducing new variables, i.e., `CNF` and `ThreeCNF`,
+/// take them from one supply, so they never clash with each other or with the
+/// variables of the input, and the output is the same on every run.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::l_group_term::LGroupTerm;
+/// use l_group_formulas::literal::Literal;
+/// use l_group_cnf::fresh_variables::FreshVariables;
+/// let mut fresh = FreshVariables::for_term(&LGroupTerm::from("x V2 v V1"));
+/// assert_eq!(Some(Literal::new('v', 3, false)), fresh.next());
+/// assert_eq!(Some(Literal::new('v', 4, false)), fresh.next());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreshVariables {
+    used:    BTreeSet<Literal>,
+    next_id: usize
+}
+
+impl FreshVariables {
+    /// Returns a supply avoiding `used`, where inverted literals
+    /// count as their variable.
+    pub fn avoiding(used: &BTreeSet<Literal>) -> FreshVariables {
+        let used = used.iter().map(|l| Literal::new(l.character, l.id, false)).collect();
+        FreshVariables { used, next_id: 1 }
+    }
+
+    /// Returns a supply avoiding the variables of `term`.
+    pub fn for_term(term: &LGroupTerm) -> FreshVariables {
+        FreshVariables::avoiding(&term.variables())
+    }
+}
+
+impl Iterator for FreshVariables {
+    type Item = Literal;
+
+    fn next(&mut self) -> Option<Literal> {
+        loop {
+            let literal = Literal::new('v', self.next_id, false);
+            self.next_id += 1;
+            if self.used.insert(literal) {
+                return Some(literal);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_avoids_used_variables() {
+        let mut used = BTreeSet::new();
+        used.insert(Literal::new('v', 1, true));
+        used.insert(Literal::new('v', 3, false));
+        let fresh: Vec<Literal> = FreshVariables::avoiding(&used).take(3).collect();
+        let ids: Vec<usize> = fresh.iter().map(|l| l.id).collect();
+        assert_eq!(vec![2, 4, 5], ids);
+    }
+}
@@ -1,2 +1,5 @@
+pub mod fresh_variables;
 pub mod normal_cnf;
 pub mod three_cnf;
+pub mod sup_inf_normal_form;
+pub mod split_at_meets;
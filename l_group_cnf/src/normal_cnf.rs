@@ -1,11 +1,11 @@
 use l_group_formulas::free_group_term::len;
 use l_group_formulas::free_group_term::FreeGroupTerm;
 use l_group_formulas::l_group_term::LGroupTerm;
-use l_group_formulas::literal::Literal;
 use l_group_formulas::Reducable;
 use std::collections::BTreeSet;
 use l_group_formulas::Term;
-use rand::Rng;
+use super::fresh_variables::FreshVariables;
+use tracing::{debug, info_span};
 
 /// Represents a meet of joins of free group terms.
 /// 
@@ -20,8 +20,8 @@ pub struct CNF {
     pub meetands: BTreeSet<BTreeSet<FreeGroupTerm>>
 }
 
-impl ToString for CNF {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for CNF {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut string = String::new();
         for meetand in &self.meetands {
             string.push('(');
@@ -32,16 +32,26 @@ impl ToString for CNF {
             string = string[0 .. string.len() - 3].to_string();
             string.push_str(") ^ ");
         }
-        if string.len() == 0 {
-            return String::from("(())")
+        if string.is_empty() {
+            return write!(f, "(())")
         }
-        string[0..string.len() - 3].to_string()
+        write!(f, "{}", &string[0..string.len() - 3])
     }
 }
 
 impl From<LGroupTerm> for CNF {
     fn from(term: LGroupTerm) -> CNF {
-        let cnf_term = to_cnf(term);
+        let mut fresh = FreshVariables::for_term(&term);
+        CNF::with_fresh_variables(term, &mut fresh)
+    }
+}
+
+impl CNF {
+    /// Constructs the CNF of `term` as `CNF::from` does, taking new variables
+    /// from `fresh`, which has to avoid the variables of `term`.
+    pub fn with_fresh_variables(term: LGroupTerm, fresh: &mut FreshVariables) -> CNF {
+        let _span = info_span!("cnf").entered();
+        let cnf_term = to_cnf(term, fresh);
         let mut meetands = BTreeSet::new();
         match cnf_term {
             LGroupTerm::Meet(xs) => {
@@ -84,6 +94,7 @@ impl From<LGroupTerm> for CNF {
             }
             LGroupTerm::Prod(_) => panic!("CNF failed")
         };
+        debug!(meetands = meetands.len(), size = meetands.iter().map(|m| m.len()).sum::<usize>());
         CNF { meetands }
     }
 }
@@ -122,19 +133,19 @@ impl CNF {
     }
 }
 
-fn to_cnf(term: LGroupTerm) -> LGroupTerm {
+fn to_cnf(term: LGroupTerm, fresh: &mut FreshVariables) -> LGroupTerm {
     // if is_in_cnf(&term) {
     //     return term;
     // }
 
     match term {
-        LGroupTerm::Atom(_) => { return term }
+        LGroupTerm::Atom(_) => { term }
         LGroupTerm::Meet(xs) => { 
             let mut meetands = BTreeSet::new();
             for x in xs {
-                meetands.insert(to_cnf(x));
+                meetands.insert(to_cnf(x, fresh));
             }
-            return LGroupTerm::Meet(meetands).reduced();
+            LGroupTerm::Meet(meetands).reduced()
         },
         LGroupTerm::Join(xs) => {
             let mut rest = BTreeSet::new();
@@ -149,7 +160,7 @@ fn to_cnf(term: LGroupTerm) -> LGroupTerm {
                         for meetand in meetands {
                             let mut set = rest.clone();
                             set.insert(meetand.clone());
-                            new_meetands.insert(to_cnf(LGroupTerm::Join(set)).reduced());
+                            new_meetands.insert(to_cnf(LGroupTerm::Join(set), fresh).reduced());
                         }
                         return LGroupTerm::Meet(new_meetands).reduced();
                     },
@@ -160,8 +171,8 @@ fn to_cnf(term: LGroupTerm) -> LGroupTerm {
             }
             // term doesn't contain any meets
             let mut new_joinands = BTreeSet::new();
-            for x in xs { new_joinands.insert(to_cnf(x)); }
-            return LGroupTerm::Join(new_joinands).reduced();
+            for x in xs { new_joinands.insert(to_cnf(x, fresh)); }
+            LGroupTerm::Join(new_joinands).reduced()
         },
         LGroupTerm::Prod(xs) => {
             let mut rest_left : Vec<LGroupTerm> = Vec::new();
@@ -189,20 +200,17 @@ fn to_cnf(term: LGroupTerm) -> LGroupTerm {
                         if rest_left.len() == 1 && rest_right.len() == 1 {
                             let left = rest_left.first().unwrap();
                             let right = rest_right.first().unwrap();
-                            match (&left, &right) {
-                                (LGroupTerm::Atom(literals_left), LGroupTerm::Atom(literals_right)) => {
-                                    if len(literals_left) == 1 && len(literals_right) == 1 {
-                                        let mut new_joinands = BTreeSet::new();
-                                        for joinand in joinands {
-                                            let vec = vec![LGroupTerm::Prod(rest_left.clone()),
-                                                           joinand.clone(),
-                                                           LGroupTerm::Prod(rest_right.clone())];
-                                            new_joinands.insert(to_cnf(LGroupTerm::Prod(vec).reduced()));
-                                        }
-                                        return to_cnf(LGroupTerm::Join(new_joinands));
-                                   }
-                                },
-                                _ => {}
+                            if let (LGroupTerm::Atom(literals_left), LGroupTerm::Atom(literals_right)) = (&left, &right) {
+                                if len(literals_left) == 1 && len(literals_right) == 1 {
+                                    let mut new_joinands = BTreeSet::new();
+                                    for joinand in joinands {
+                                        let vec = vec![LGroupTerm::Prod(rest_left.clone()),
+                                                       joinand.clone(),
+                                                       LGroupTerm::Prod(rest_right.clone())];
+                                        new_joinands.insert(to_cnf(LGroupTerm::Prod(vec).reduced(), fresh));
+                                    }
+                                    return to_cnf(LGroupTerm::Join(new_joinands), fresh);
+                               }
                             }
                         }
                         // The term
@@ -210,24 +218,23 @@ fn to_cnf(term: LGroupTerm) -> LGroupTerm {
                         // is transformed to
                         // Join(rest_left * x, X * joinand1 * y, X * joinand2 * y, ..., X * joinandn * y, Y * rest_right).
                         // Here, x and y are new variables not appearing in the whole term.
-                        let mut rng = rand::thread_rng();
-                        let x = Literal::new('v', rng.gen::<usize>(), false);
-                        let y = Literal::new('v', rng.gen::<usize>(), false);
+                        let x = fresh.next().expect("There are infinitely many fresh variables.");
+                        let y = fresh.next().expect("There are infinitely many fresh variables.");
                         let mut new_joinands = BTreeSet::new();
                         rest_left.push(LGroupTerm::Atom(FreeGroupTerm::from(x)));
-                        new_joinands.insert(to_cnf(LGroupTerm::Prod(rest_left).reduced()));
+                        new_joinands.insert(to_cnf(LGroupTerm::Prod(rest_left).reduced(), fresh));
                         let mut new_rest_right = vec![LGroupTerm::Atom(FreeGroupTerm::from(y.inverse()))];
                         for t in rest_right {
                             new_rest_right.push(t);
                         }
-                        new_joinands.insert(to_cnf(LGroupTerm::Prod(new_rest_right).reduced()));
+                        new_joinands.insert(to_cnf(LGroupTerm::Prod(new_rest_right).reduced(), fresh));
                         for joinand in joinands {
                             let new_factors = vec![LGroupTerm::from(x.inverse()),
                                                    joinand.clone(),
                                                    LGroupTerm::from(y)];
-                            new_joinands.insert(to_cnf(LGroupTerm::Prod(new_factors).reduced()));
+                            new_joinands.insert(to_cnf(LGroupTerm::Prod(new_factors).reduced(), fresh));
                         }
-                        return to_cnf(LGroupTerm::Join(new_joinands).reduced())
+                        return to_cnf(LGroupTerm::Join(new_joinands).reduced(), fresh)
                     },
                     LGroupTerm::Meet(meetands) => {
                         let mut rest_right = Vec::new();
@@ -247,9 +254,9 @@ fn to_cnf(term: LGroupTerm) -> LGroupTerm {
                         let mut new_meetands = BTreeSet::new();
                         for meetand in meetands {
                             let vec = vec![LGroupTerm::Prod(rest_left.clone()), meetand.clone(), LGroupTerm::Prod(rest_right.clone())];
-                            new_meetands.insert(to_cnf(LGroupTerm::Prod(vec).reduced()));
+                            new_meetands.insert(to_cnf(LGroupTerm::Prod(vec).reduced(), fresh));
                         }
-                        return to_cnf(LGroupTerm::Meet(new_meetands));
+                        return to_cnf(LGroupTerm::Meet(new_meetands), fresh);
                     },
                     _ => {
                         rest_left.push(x);
@@ -258,10 +265,10 @@ fn to_cnf(term: LGroupTerm) -> LGroupTerm {
             }
             // term doesn't contain any meets
             let mut new_joinands = BTreeSet::new();
-            for x in xs { new_joinands.insert(to_cnf(x)); }
-            return LGroupTerm::Join(new_joinands).reduced();
+            for x in xs { new_joinands.insert(to_cnf(x, fresh)); }
+            LGroupTerm::Join(new_joinands).reduced()
         }
-    };
+    }
 }
 
 // fn is_in_cnf(term: &LGroupTerm) -> bool {
@@ -316,4 +323,12 @@ mod tests {
 
         assert_eq!(String::from("(w v z) ^ (x v y)"), CNF::new(meetands).to_string());
     }
+
+    #[test]
+    fn test_fresh_variables_are_reproducible() {
+        let term = LGroupTerm::from("V1x(z v w)u");
+        let cnf = CNF::from(term.clone()).to_string();
+        assert_eq!(cnf, CNF::from(term).to_string());
+        assert_eq!("(V1xv2 v V2wv3 v V2zv3 v V3u)", cnf);
+    }
 }
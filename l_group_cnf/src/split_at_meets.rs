@@ -0,0 +1,113 @@
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::Reducable;
+use std::collections::BTreeSet;
+
+/// Returns the terms without meets whose meet is `term`, obtained by
+/// distributing joins and products over meets.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::l_group_term::LGroupTerm;
+/// use l_group_cnf::split_at_meets::split_at_meets;
+/// let meetands = split_at_meets(LGroupTerm::from("x v (y ^ z)"));
+/// assert!(meetands.contains(&LGroupTerm::from("x v y")));
+/// assert!(meetands.contains(&LGroupTerm::from("x v z")));
+/// assert_eq!(2, meetands.len());
+/// ```
+pub fn split_at_meets(term: LGroupTerm) -> BTreeSet<LGroupTerm> {
+    match split_at_meets_checked(term, &|_| Ok::<(), ()>(())) {
+        Ok(terms) => terms,
+        Err(()) => unreachable!("Splitting without checks fails.")
+    }
+}
+
+/// Splits `term` as `split_at_meets` does, calling `check` on every set of
+/// terms obtained on the way, and gives up as soon as it returns an error.
+pub fn split_at_meets_checked<E>(
+        term: LGroupTerm,
+        check: &dyn Fn(&BTreeSet<LGroupTerm>) -> Result<(), E>) -> Result<BTreeSet<LGroupTerm>, E> {
+    let mut terms : BTreeSet<LGroupTerm> = BTreeSet::new();
+    match term {
+        LGroupTerm::Meet(xs) => {
+            for x in xs {
+                for y in split_at_meets_checked(x, check)? {
+                    terms.insert(y);
+                }
+                check(&terms)?;
+            }
+        },
+        LGroupTerm::Atom(x) => {
+            terms.insert(LGroupTerm::Atom(x));
+        },
+        LGroupTerm::Join(xs) => {
+            let mut splits = Vec::new();
+            for x in xs {
+                splits.push(split_at_meets_checked(x, check)?);
+            }
+            for y in join_all_combinations(splits, check)? {
+                terms.insert(y);
+            }
+        },
+        LGroupTerm::Prod(xs) => {
+            let mut splits = Vec::new();
+            for x in xs {
+                splits.push(split_at_meets_checked(x, check)?);
+            }
+            for y in multiply_all_combinations(splits, check)? {
+                terms.insert(y);
+            }
+        }
+    };
+    Ok(terms)
+}
+
+fn join_all_combinations<E>(
+        sets_of_joinands: Vec<BTreeSet<LGroupTerm>>,
+        check: &dyn Fn(&BTreeSet<LGroupTerm>) -> Result<(), E>) -> Result<BTreeSet<LGroupTerm>, E> {
+    let mut result = BTreeSet::new();
+    let mut iterator = sets_of_joinands.iter();
+    if let Some(first_set) = iterator.next() {
+        for x in first_set {
+            result.insert(x.clone());
+        }
+    }
+    for joinands in iterator {
+        let mut tmp = BTreeSet::new();
+        for r in &result {
+            for j in joinands {
+                let mut tmp_joinands = BTreeSet::new();
+                tmp_joinands.insert(r.clone());
+                tmp_joinands.insert(j.clone());
+                let new_r = LGroupTerm::Join(tmp_joinands).reduced();
+                tmp.insert(new_r);
+            }
+            check(&tmp)?;
+        }
+        result = tmp;
+    }
+    Ok(result)
+}
+
+fn multiply_all_combinations<E>(
+        sets_of_factors: Vec<BTreeSet<LGroupTerm>>,
+        check: &dyn Fn(&BTreeSet<LGroupTerm>) -> Result<(), E>) -> Result<BTreeSet<LGroupTerm>, E> {
+    let mut result = BTreeSet::new();
+    let mut iterator = sets_of_factors.iter();
+    if let Some(first_set) = iterator.next() {
+        for x in first_set {
+            result.insert(x.clone());
+        }
+    }
+    for factors in iterator {
+        let mut tmp = BTreeSet::new();
+        for r in &result {
+            for f in factors {
+                tmp.insert(r.clone() * f.clone());
+            }
+            check(&tmp)?;
+        }
+        result = tmp;
+    }
+    Ok(result)
+}
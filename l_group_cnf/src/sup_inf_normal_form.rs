@@ -0,0 +1,177 @@
+use l_group_formulas::free_group_term::{len, FreeGroupTerm};
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::Reducable;
+use std::collections::BTreeSet;
+
+/// Represents a join of meets of free group terms, which is provably equal
+/// to the term it was constructed from.
+///
+/// Unlike `CNF`, no new variables are introduced. Products are distributed
+/// over meets and joins, and meets over joins, so the result may be
+/// exponentially larger than the original term. Meets containing all
+/// meetands of another joinand are absorbed by it.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::l_group_term::LGroupTerm;
+/// use l_group_cnf::sup_inf_normal_form::SupInfNormalForm;
+/// let normal_form = SupInfNormalForm::from(&LGroupTerm::from("(x v y)(z ^ w)"));
+/// assert_eq!("(xw ^ xz) v (yw ^ yz)", normal_form.to_string());
+/// assert_eq!(LGroupTerm::from("(xw ^ xz) v (yw ^ yz)"), normal_form.to_term());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SupInfNormalForm {
+    pub joinands: BTreeSet<BTreeSet<FreeGroupTerm>>
+}
+
+/// The size of a `SupInfNormalForm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Size {
+    /// The number of meets which are joined.
+    pub joinands:        usize,
+    /// The number of words in all meets together.
+    pub words:           usize,
+    /// The number of literals in all words together.
+    pub literals:        usize,
+    /// The largest number of words in a meet.
+    pub max_meet_size:   usize,
+    /// The largest number of literals in a word.
+    pub max_word_length: usize
+}
+
+impl SupInfNormalForm {
+    /// Returns the normal form as an `LGroupTerm`, which is equal to the
+    /// term it was constructed from.
+    pub fn to_term(&self) -> LGroupTerm {
+        let joinands = self.joinands.iter()
+            .map(|meetands| LGroupTerm::Meet(meetands.iter().cloned().map(LGroupTerm::Atom).collect()).reduced())
+            .collect();
+        LGroupTerm::Join(joinands).reduced()
+    }
+
+    /// Returns the size of the normal form.
+    ///
+    /// # Examples
+    /// Basic usage:
+    /// ```
+    /// use l_group_formulas::l_group_term::LGroupTerm;
+    /// use l_group_cnf::sup_inf_normal_form::SupInfNormalForm;
+    /// let size = SupInfNormalForm::from(&LGroupTerm::from("(x v y)(z ^ w)")).size();
+    /// assert_eq!((2, 4, 8), (size.joinands, size.words, size.literals));
+    /// ```
+    pub fn size(&self) -> Size {
+        let mut size = Size { joinands: self.joinands.len(), ..Size::default() };
+        for meetands in &self.joinands {
+            size.words += meetands.len();
+            size.max_meet_size = size.max_meet_size.max(meetands.len());
+            for word in meetands {
+                size.literals += len(word);
+                size.max_word_length = size.max_word_length.max(len(word));
+            }
+        }
+        size
+    }
+}
+
+impl From<&LGroupTerm> for SupInfNormalForm {
+    fn from(term: &LGroupTerm) -> SupInfNormalForm {
+        SupInfNormalForm { joinands: absorbed(sup_inf(term)) }
+    }
+}
+
+impl std::fmt::Display for SupInfNormalForm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let joinands: Vec<String> = self.joinands.iter()
+            .map(|meetands| {
+                let words: Vec<String> = meetands.iter().map(|w| w.to_string()).collect();
+                if words.len() == 1 { words.concat() } else { format!("({})", words.join(" ^ ")) }
+            })
+            .collect();
+        write!(f, "{}", joinands.join(" v "))
+    }
+}
+
+/// Returns the meets whose join is `term`.
+fn sup_inf(term: &LGroupTerm) -> BTreeSet<BTreeSet<FreeGroupTerm>> {
+    match term {
+        LGroupTerm::Atom(x) => singleton(singleton(x.clone())),
+        LGroupTerm::Join(xs) => xs.iter().flat_map(sup_inf).collect(),
+        // (a v b) ^ (c v d) = (a ^ c) v (a ^ d) v (b ^ c) v (b ^ d)
+        LGroupTerm::Meet(xs) => xs.iter().fold(singleton(BTreeSet::new()), |joinands, x| {
+            let mut new_joinands = BTreeSet::new();
+            for meetands in &joinands {
+                for other_meetands in sup_inf(x) {
+                    new_joinands.insert(meetands.union(&other_meetands).cloned().collect());
+                }
+            }
+            absorbed(new_joinands)
+        }),
+        // (a v b)(c ^ d) = (ac ^ ad) v (bc ^ bd)
+        LGroupTerm::Prod(xs) => xs.iter().fold(singleton(singleton(FreeGroupTerm::new(Vec::new()))), |joinands, x| {
+            let mut new_joinands = BTreeSet::new();
+            for meetands in &joinands {
+                for other_meetands in sup_inf(x) {
+                    let mut products = BTreeSet::new();
+                    for u in meetands {
+                        for v in &other_meetands {
+                            products.insert(u.clone() * v.clone());
+                        }
+                    }
+                    new_joinands.insert(products);
+                }
+            }
+            absorbed(new_joinands)
+        })
+    }
+}
+
+/// Removes the meets which contain all meetands of another one,
+/// because they are below it.
+fn absorbed(joinands: BTreeSet<BTreeSet<FreeGroupTerm>>) -> BTreeSet<BTreeSet<FreeGroupTerm>> {
+    joinands.iter()
+        .filter(|meetands| !joinands.iter().any(|other| other != *meetands && other.is_subset(meetands)))
+        .cloned()
+        .collect()
+}
+
+fn singleton<T: Ord>(x: T) -> BTreeSet<T> {
+    let mut set = BTreeSet::new();
+    set.insert(x);
+    set
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normal_form(s: &str) -> String {
+        SupInfNormalForm::from(&LGroupTerm::from(s)).to_string()
+    }
+
+    #[test]
+    fn test_distributes_meets_over_joins() {
+        assert_eq!("(x ^ z) v (y ^ z)", normal_form("(x v y) ^ z"));
+        assert_eq!("x v (y ^ z)", normal_form("(x v y) ^ (x v z)"));
+    }
+
+    #[test]
+    fn test_distributes_products() {
+        assert_eq!("e v Xy", normal_form("(Y v X) y"));
+        assert_eq!("(e ^ x) v (y ^ yx)", normal_form("(e v y)(X ^ e)x"));
+    }
+
+    #[test]
+    fn test_absorption() {
+        assert_eq!("x", normal_form("x v (x ^ y)"));
+        assert_eq!("x", normal_form("x ^ (x v y)"));
+    }
+
+    #[test]
+    fn test_normal_forms_are_normal_forms() {
+        for s in &["x(y v z ^ w)X", "-(x v y) ^ -(z v e)", "(x ^ e)(y v Z)(x ^ y)"] {
+            let normal_form = SupInfNormalForm::from(&LGroupTerm::from(*s));
+            assert_eq!(normal_form, SupInfNormalForm::from(&normal_form.to_term()));
+        }
+    }
+}
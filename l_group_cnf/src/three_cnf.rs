@@ -1,9 +1,11 @@
 use std::collections::BTreeSet;
-use l_group_formulas::literal::Literal;
+use l_group_formulas::Term;
 use l_group_formulas::free_group_term::{FreeGroupTerm, FREE_GROUP_IDENTITY};
 use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
 use l_group_formulas::l_group_term::LGroupTerm;
 use super::normal_cnf::CNF;
+use super::fresh_variables::FreshVariables;
+use tracing::{debug, info, info_span};
 
 /// Represents a meet of joins of free group terms of length at most three.
 /// 
@@ -57,10 +59,38 @@ pub struct ThreeCNF {
 
 impl From<LGroupTerm> for ThreeCNF {
     fn from(term: LGroupTerm) -> ThreeCNF {
-        let normal_cnf = CNF::from(term);
-        println!("The long normal form is {}.", normal_cnf.to_string());
+        let mut fresh = FreshVariables::for_term(&term);
+        ThreeCNF::with_fresh_variables(term, &mut fresh)
+    }
+}
+
+impl ThreeCNF {
+    /// Constructs the `ThreeCNF` of `term` as `ThreeCNF::from` does, taking new
+    /// variables from `fresh`, which has to avoid the variables of `term`.
+    pub fn with_fresh_variables(term: LGroupTerm, fresh: &mut FreshVariables) -> ThreeCNF {
+        ThreeCNF::shortened(term, fresh, false)
+    }
+
+    /// Constructs the `ThreeCNF` of `term` as `ThreeCNF::from` does, but keeps
+    /// the meetands consisting of a single atom, split as the others are.
+    /// Then `e <= term` holds if, and only if, none of the meetands extends to
+    /// a right order, also when some of them are single atoms.
+    /// ```
+    /// use l_group_formulas::l_group_term::LGroupTerm;
+    /// use l_group_cnf::three_cnf::ThreeCNF;
+    /// let three_cnf = ThreeCNF::with_atoms(LGroupTerm::from("xyzw"));
+    /// assert_eq!("(V1zw v xyv1)", three_cnf.to_string());
+    /// ```
+    pub fn with_atoms(term: LGroupTerm) -> ThreeCNF {
+        let mut fresh = FreshVariables::for_term(&term);
+        ThreeCNF::shortened(term, &mut fresh, true)
+    }
+
+    fn shortened(term: LGroupTerm, fresh: &mut FreshVariables, keep_atoms: bool) -> ThreeCNF {
+        let normal_cnf = CNF::with_fresh_variables(term, fresh);
+        let _span = info_span!("three_cnf").entered();
+        info!(meetands = normal_cnf.meetands.len(), "The long normal form is {}.", normal_cnf);
         let mut new_meetands = BTreeSet::new();
-        let mut count = 1;
         for meetand in normal_cnf.meetands {
             match meetand.len() {
                 0 => panic!("empty meet!"),
@@ -70,12 +100,14 @@ impl From<LGroupTerm> for ThreeCNF {
                         let mut singleton_set = BTreeSet::new();
                         singleton_set.insert(ShortFreeGroupTerm::new(None, None, None));
                         new_meetands.insert(singleton_set);
+                    } else if keep_atoms {
+                        new_meetands.insert(split(element.clone(), fresh));
                     } // otherwise this always extends to a partial order, so we leave out long individual atoms (they cannot be split anyway)
                 },  
                 _ => {
                     let mut joinands = BTreeSet::new();
                     for term in meetand {
-                        for new_term in split(term, &mut count) {
+                        for new_term in split(term, fresh) {
                             joinands.insert(new_term);
                         }
                     }
@@ -83,12 +115,13 @@ impl From<LGroupTerm> for ThreeCNF {
                 }
             };
         }
+        debug!(meetands = new_meetands.len(), size = new_meetands.iter().map(|m| m.len()).sum::<usize>());
         ThreeCNF { meetands: new_meetands }
     }
 }
 
-impl ToString for ThreeCNF {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for ThreeCNF {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut string = String::new();
         for meetand in &self.meetands {
             string.push('(');
@@ -99,37 +132,37 @@ impl ToString for ThreeCNF {
             string = string[0 .. string.len() - 3].to_string();
             string.push_str(") ^ ");
         }
-        if string.len() == 0 {
-            return String::from("(())")
+        if string.is_empty() {
+            return write!(f, "(())")
         }
-        string[0..string.len() - 3].to_string()
+        write!(f, "{}", &string[0..string.len() - 3])
     }
 }
 
 /// applies the rule
 /// `p v sx v Xt / p v st`
-/// to avoid long group terms
-fn split(term: FreeGroupTerm, counter: &mut usize) -> BTreeSet<ShortFreeGroupTerm> {
+/// to avoid long group terms, taking the new variables from `fresh`.
+pub fn split(term: FreeGroupTerm, fresh: &mut FreshVariables) -> BTreeSet<ShortFreeGroupTerm> {
     let mut output = BTreeSet::new();
     if term.literals.len() <= 3 {
         output.insert(ShortFreeGroupTerm::from(term.clone()));
         return output;
     }
+    let x = fresh.next().expect("There are infinitely many fresh variables.");
     output.insert(ShortFreeGroupTerm {
         left:  Some(term.literals[0]),
         mid:   Some(term.literals[1]),
-        right: Some(Literal::new('v', *counter, false))
+        right: Some(x)
     });
 
     let mut rest_literals = Vec::new();
-    rest_literals.push(Literal::new('v', *counter, true));
-    *counter += 1;
+    rest_literals.push(x.inverse());
     for x in &term.literals[2 .. term.literals.len()] {
         rest_literals.push(*x);
     }
     let rest_term = FreeGroupTerm { literals: rest_literals };
-    for x in split(rest_term, counter) {
+    for x in split(rest_term, fresh) {
         output.insert(x);
     }
-    return output;
+    output
 }
@@ -3,7 +3,6 @@ use l_group_formulas::l_group_term::*;
 use l_group_formulas::literal::*;
 use std::collections::BTreeSet;
 use l_group_formulas::*;
-use std::iter;
 
     fn meet_of_depth(n: usize) -> LGroupTerm {
         let mut current_meetand = LGroupTerm::from(Literal::new('x', 0, false));
@@ -14,8 +13,7 @@ use std::iter;
             meetands.insert(LGroupTerm::from(new_literal));
             current_meetand = LGroupTerm::Meet(meetands);
         }
-        println!("{}", current_meetand.to_string());
-        return current_meetand;
+        current_meetand
     }
 
     #[allow(dead_code)]
@@ -30,7 +28,7 @@ fn criterion_benchmark(c: &mut Criterion) {
             BenchmarkId::from_parameter(depth), 
             depth,
             |b, &depth| {
-                b.iter(|| iter::repeat(0u8).take(depth).collect::<Vec<_>>());
+                b.iter(|| std::iter::repeat_n(0u8, depth).collect::<Vec<_>>());
             }
         );
 
@@ -1,39 +1,153 @@
 use super::l_group_term::LGroupTerm;
+use super::parsing::ParseOptions;
+use super::parsing_error::ParsingError;
+use super::symbol_table::SymbolTable;
 
-#[derive(Debug, Clone)]
+mod parse_formula;
+
+/// An equation `lhs = rhs` or an inequation `lhs <= rhs` between `LGroupTerm`s.
+///
+/// # Examples
+/// Formulas can be parsed from strings, with the terms as in `LGroupTerm::from(&str)`.
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_formulas::l_group_term::LGroupTerm;
+/// let formula: LGroupFormula = "x ^ y <= x".parse().unwrap();
+/// assert_eq!(LGroupFormula::LGroupInequation(LGroupTerm::from("x ^ y"), LGroupTerm::from("x")), formula);
+/// assert!("x ^ y".parse::<LGroupFormula>().is_err());
+/// ```
+/// The formula may be preceded by definitions, separated by semicolons,
+/// each of which may use the ones before it. Also, `s >= t` means `t <= s`.
+/// ```
+/// # use l_group_formulas::formula::LGroupFormula;
+/// let formula: LGroupFormula = "let p = x ^ e; q = p y P; q v e >= e".parse().unwrap();
+/// assert_eq!(LGroupFormula::from("e <= (x ^ e) y -(x ^ e) v e"), formula);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LGroupFormula {
     LGroupEquation(LGroupTerm, LGroupTerm),
     LGroupInequation(LGroupTerm, LGroupTerm)
 }
 
+impl LGroupFormula {
+    /// Parses a formula, returning an error instead of panicking on invalid input.
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<LGroupFormula, ParsingError> {
+        LGroupFormula::parse_with_symbols(s, options, &mut SymbolTable::new())
+    }
+
+    /// Parses a formula as `LGroupFormula::parse_with` does, interning the names
+    /// of variables in `symbols` in the `Standard` dialect.
+    /// ```
+    /// use l_group_formulas::formula::LGroupFormula;
+    /// use l_group_formulas::parsing::{ParseOptions, Dialect};
+    /// use l_group_formulas::symbol_table::SymbolTable;
+    /// let standard = ParseOptions { dialect: Dialect::Standard, ..ParseOptions::default() };
+    /// let mut symbols = SymbolTable::new();
+    /// let formula = LGroupFormula::parse_with_symbols("x_left ∧ x_right <= x_left", &standard, &mut symbols).unwrap();
+    /// assert_eq!("(x_left /\\ x_right) <= x_left", formula.to_string_with(&symbols));
+    /// ```
+    pub fn parse_with_symbols(
+            s: &str,
+            options: &ParseOptions,
+            symbols: &mut SymbolTable) -> Result<LGroupFormula, ParsingError> {
+        parse_formula::parse(s, options, symbols)
+    }
+}
+
+impl LGroupFormula {
+    /// Prints the formula in the `Standard` dialect, with the names in `symbols`.
+    pub fn to_string_with(&self, symbols: &SymbolTable) -> String {
+        match self {
+            LGroupFormula::LGroupEquation(lhs, rhs) =>
+                format!("{} = {}", lhs.to_string_with(symbols), rhs.to_string_with(symbols)),
+            LGroupFormula::LGroupInequation(lhs, rhs) =>
+                format!("{} <= {}", lhs.to_string_with(symbols), rhs.to_string_with(symbols))
+        }
+    }
+
+    /// Prints the formula as `to_string_with` does, but with the terms
+    /// printed by `LGroupTerm::to_folded_string_with`.
+    pub fn to_folded_string_with(&self, symbols: &SymbolTable) -> String {
+        match self {
+            LGroupFormula::LGroupEquation(lhs, rhs) =>
+                format!("{} = {}", lhs.to_folded_string_with(symbols), rhs.to_folded_string_with(symbols)),
+            LGroupFormula::LGroupInequation(lhs, rhs) =>
+                format!("{} <= {}", lhs.to_folded_string_with(symbols), rhs.to_folded_string_with(symbols))
+        }
+    }
+}
+
+impl std::str::FromStr for LGroupFormula {
+    type Err = ParsingError;
+    fn from_str(s: &str) -> Result<LGroupFormula, ParsingError> {
+        LGroupFormula::parse_with(s, &ParseOptions::default())
+    }
+}
+
 impl From<&str> for LGroupFormula {
+    /// Panics on invalid input. Use `str::parse` to handle errors instead.
     fn from(s: &str) -> LGroupFormula {
-        let string = s.to_string();
-        let delimiter: &str;
-        match s.contains('<') {
-            true => delimiter = "<=",
-            false => delimiter = "="
-        };
-        let strings: Vec<&str> = string.split(delimiter).collect();
-        match delimiter {
-            "<=" => LGroupFormula::LGroupInequation(
-                LGroupTerm::from(strings[0].to_string().as_str()),
-                LGroupTerm::from(strings[1].to_string().as_str())
-            ),
-            "="  => LGroupFormula::LGroupEquation(
-                LGroupTerm::from(strings[0].to_string().as_str()),
-                LGroupTerm::from(strings[1].to_string().as_str())
-            ),
-            _   => panic!("impossible")
+        match s.parse() {
+            Ok(formula) => formula,
+            Err(e) => panic!("{}", e.render())
         }
     }
 }
 
-impl ToString for LGroupFormula {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for LGroupFormula {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            LGroupFormula::LGroupEquation(lhs, rhs) => format!("{}={}", lhs.to_string(), rhs.to_string()),
-            LGroupFormula::LGroupInequation(lhs, rhs) => format!("{}<={}", lhs.to_string(), rhs.to_string())
+            LGroupFormula::LGroupEquation(lhs, rhs) => write!(f, "{}={}", lhs, rhs),
+            LGroupFormula::LGroupInequation(lhs, rhs) => write!(f, "{}<={}", lhs, rhs)
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::parsing::Dialect;
+    use super::super::parsing_error::ParsingErrorKind;
+
+    #[test]
+    fn test_relation_errors() {
+        let error = "x v y".parse::<LGroupFormula>().unwrap_err();
+        assert_eq!((ParsingErrorKind::MissingRelation, 5), (error.kind, error.start));
+        let error = "x <= y = z".parse::<LGroupFormula>().unwrap_err();
+        assert_eq!((ParsingErrorKind::MultipleRelations, 7), (error.kind, error.start));
+        let error = "x < y".parse::<LGroupFormula>().unwrap_err();
+        assert_eq!(ParsingErrorKind::MissingRelation, error.kind);
+    }
+
+    #[test]
+    fn test_offsets_refer_to_the_whole_formula() {
+        let error = "x = y v (z".parse::<LGroupFormula>().unwrap_err();
+        assert_eq!((ParsingErrorKind::UnclosedBracket, 8), (error.kind.clone(), error.start));
+        assert_eq!("this bracket is never closed\nx = y v (z\n        ^", error.render());
+        let error = LGroupFormula::parse_with("x = y ** z", &ParseOptions { strict: true, ..ParseOptions::default() }).unwrap_err();
+        assert_eq!((ParsingErrorKind::UnexpectedCharacter('*'), 6), (error.kind, error.start));
+    }
+
+    #[test]
+    fn test_standard_dialect() {
+        let standard = ParseOptions { dialect: Dialect::Standard, ..ParseOptions::default() };
+        let formula = LGroupFormula::parse_with("x /\\ y <= x", &standard).unwrap();
+        assert_eq!(LGroupFormula::from("x ^ y <= x"), formula);
+        // v1 and e are variables, which the Legacy syntax prints with other names
+        let formula = LGroupFormula::parse_with("u | v1^-1 z w | x y v1 = e", &standard).unwrap();
+        assert_eq!(LGroupFormula::from("u v W1zw v xyw1 = e1"), LGroupFormula::from(formula.to_string().as_str()));
+        let formula = LGroupFormula::parse_with("e * x <= x | e", &standard).unwrap();
+        assert_eq!("e1x<=(e1 v x)", formula.to_string());
+    }
+
+    #[test]
+    fn test_printing_with_symbols_round_trips() {
+        let standard = ParseOptions { dialect: Dialect::Standard, ..ParseOptions::default() };
+        let mut symbols = SymbolTable::new();
+        let formula = LGroupFormula::parse_with_symbols(
+            "(α β⁻¹ ∧ 1) ∨ -(x_1 * X) = alpha", &standard, &mut symbols).unwrap();
+        let printed = formula.to_string_with(&symbols);
+        assert_eq!(Ok(formula), LGroupFormula::parse_with_symbols(&printed, &standard, &mut symbols));
+        assert_eq!(5, symbols.len());
+    }
+}
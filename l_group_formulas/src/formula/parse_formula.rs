@@ -0,0 +1,188 @@
+use super::super::formula::LGroupFormula;
+use super::super::l_group_term::parse_l_group_term::parse_range;
+use super::super::literal::Literal;
+use super::super::parsing::{Dialect, ParseOptions};
+use super::super::parsing_error::{ParsingError, ParsingErrorKind};
+use super::super::symbol_table::SymbolTable;
+use super::super::Reducable;
+use std::collections::BTreeMap;
+
+/// Parses a formula, which may be preceded by definitions, as in
+/// `let p = x ^ e; q = p y P; q v e >= e`. Each definition may use the
+/// ones before it, and the definitions are substituted into the formula.
+pub (super) fn parse(
+        s: &str,
+        options: &ParseOptions,
+        symbols: &mut SymbolTable) -> Result<LGroupFormula, ParsingError> {
+    let trimmed = s.trim_start();
+    let is_let = trimmed.strip_prefix("let").is_some_and(|rest| rest.starts_with(char::is_whitespace));
+    if !is_let {
+        return parse_formula(s, 0, s.len(), options, symbols);
+    }
+
+    // the ranges between the semicolons, the last one being the formula
+    let mut segments = Vec::new();
+    let mut start = s.len() - trimmed.len() + "let".len();
+    for (i, _) in s.match_indices(';') {
+        segments.push((start, i));
+        start = i + 1;
+    }
+    if segments.is_empty() {
+        return Err(ParsingError::new(ParsingErrorKind::ExpectedDefinition, start, s.len(), s));
+    }
+
+    // the defined names, with their byte ranges, and the ranges of their definitions
+    let mut names: Vec<(Literal, usize, usize, usize, usize)> = Vec::new();
+    for (start, end) in segments {
+        let (name, name_start, name_end) = parse_name(s, start, end, options, symbols)?;
+        if names.iter().any(|(other, _, _, _, _)| *other == name) {
+            let kind = ParsingErrorKind::DuplicateDefinition(s[name_start .. name_end].to_string());
+            return Err(ParsingError::new(kind, name_start, name_end, s));
+        }
+        let equals_sign = name_end + s[name_end .. end].find('=').expect("parse_name checks for '='");
+        names.push((name, name_start, name_end, equals_sign + 1, end));
+    }
+
+    let mut definitions = BTreeMap::new();
+    for (i, (name, name_start, name_end, start, end)) in names.iter().enumerate() {
+        let term = parse_range(s, *start, *end, options, symbols)?.reduced();
+        let variables = term.variables();
+        if variables.contains(name) {
+            let kind = ParsingErrorKind::RecursiveDefinition(s[*name_start .. *name_end].to_string());
+            return Err(ParsingError::new(kind, *start, *end, s));
+        }
+        if let Some((_, later_start, later_end, _, _)) = names[i + 1 ..].iter().find(|(x, _, _, _, _)| variables.contains(x)) {
+            let kind = ParsingErrorKind::UnboundName(s[*later_start .. *later_end].to_string());
+            return Err(ParsingError::new(kind, *start, *end, s));
+        }
+        let term = term.substituted(&definitions);
+        definitions.insert(*name, term);
+    }
+
+    Ok(match parse_formula(s, start, s.len(), options, symbols)? {
+        LGroupFormula::LGroupEquation(lhs, rhs) =>
+            LGroupFormula::LGroupEquation(lhs.substituted(&definitions), rhs.substituted(&definitions)),
+        LGroupFormula::LGroupInequation(lhs, rhs) =>
+            LGroupFormula::LGroupInequation(lhs.substituted(&definitions), rhs.substituted(&definitions))
+    })
+}
+
+/// Parses the name of the definition `name = term` in `s[start .. end]`,
+/// and returns its literal and byte range.
+fn parse_name(
+        s: &str,
+        start: usize,
+        end: usize,
+        options: &ParseOptions,
+        symbols: &mut SymbolTable) -> Result<(Literal, usize, usize), ParsingError> {
+    let equals_sign = match s[start .. end].find('=') {
+        Some(i) => start + i,
+        None => return Err(ParsingError::new(ParsingErrorKind::ExpectedDefinition, start, end, s))
+    };
+    let untrimmed = &s[start .. equals_sign];
+    let name = untrimmed.trim();
+    let name_start = start + untrimmed.len() - untrimmed.trim_start().len();
+    let name_end = name_start + name.len();
+    let invalid = || ParsingError::new(ParsingErrorKind::InvalidName(name.to_string()), name_start, name_end, s);
+    let literal = match options.dialect {
+        Dialect::Legacy => {
+            let strict = ParseOptions { strict: true, ..*options };
+            match Literal::parse_with(name, &strict) {
+                Ok(literal) if !literal.is_inverted && literal != Literal::from('e') && literal.character != 'v' => literal,
+                _ => return Err(invalid())
+            }
+        },
+        Dialect::Standard => {
+            let mut characters = name.chars();
+            let is_identifier = characters.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+                && characters.all(|c| c.is_alphanumeric() || c == '_');
+            match is_identifier {
+                true  => symbols.intern(name),
+                false => return Err(invalid())
+            }
+        }
+    };
+    Ok((literal, name_start, name_end))
+}
+
+/// Parses `s[start .. end]` as an equation or inequation, where `>=` is
+/// the inequation with both sides swapped.
+fn parse_formula(
+        s: &str,
+        start: usize,
+        end: usize,
+        options: &ParseOptions,
+        symbols: &mut SymbolTable) -> Result<LGroupFormula, ParsingError> {
+    // the relations, with their byte ranges
+    let mut relations = Vec::new();
+    let mut characters = s[start .. end].char_indices().map(|(i, c)| (start + i, c)).peekable();
+    while let Some((i, c)) = characters.next() {
+        match c {
+            '<' | '>' if characters.peek().map(|(_, d)| *d) == Some('=') => {
+                characters.next();
+                relations.push((c, i, i + 2));
+            },
+            '=' => relations.push((c, i, i + 1)),
+            _ => {}
+        };
+    }
+    let (relation, relation_start, relation_end) = match relations.as_slice() {
+        [] => return Err(ParsingError::new(ParsingErrorKind::MissingRelation, end, end, s)),
+        [relation] => *relation,
+        [_, (_, start, end), ..] => return Err(ParsingError::new(ParsingErrorKind::MultipleRelations, *start, *end, s))
+    };
+
+    let lhs = parse_range(s, start, relation_start, options, symbols)?.reduced();
+    let rhs = parse_range(s, relation_end, end, options, symbols)?.reduced();
+    Ok(match relation {
+        '<' => LGroupFormula::LGroupInequation(lhs, rhs),
+        '>' => LGroupFormula::LGroupInequation(rhs, lhs),
+        _   => LGroupFormula::LGroupEquation(lhs, rhs)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_legacy(s: &str) -> Result<LGroupFormula, ParsingError> {
+        parse(s, &ParseOptions::default(), &mut SymbolTable::new())
+    }
+
+    fn error_of(s: &str) -> (ParsingErrorKind, usize) {
+        let error = parse_legacy(s).unwrap_err();
+        (error.kind, error.start)
+    }
+
+    #[test]
+    fn test_definitions() {
+        let expected = LGroupFormula::from("e <= (x ^ e) y -(x ^ e) v e");
+        assert_eq!(Ok(expected), parse_legacy("let p = x ^ e; q = p y P; q v e >= e"));
+        let expected = LGroupFormula::from("xyXY = e");
+        assert_eq!(Ok(expected), parse_legacy("  let c = xyXY;c = e"));
+    }
+
+    #[test]
+    fn test_let_is_a_keyword_only_before_whitespace() {
+        assert_eq!(Ok(LGroupFormula::from("l e t = e")), parse_legacy("let=e"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!((ParsingErrorKind::ExpectedDefinition, 3), error_of("let x = e"));
+        assert_eq!((ParsingErrorKind::ExpectedDefinition, 3), error_of("let x; x = e"));
+        assert_eq!((ParsingErrorKind::InvalidName(String::from("P")), 4), error_of("let P = x; P = e"));
+        assert_eq!((ParsingErrorKind::InvalidName(String::from("v")), 4), error_of("let v = x; v = e"));
+        assert_eq!((ParsingErrorKind::DuplicateDefinition(String::from("p")), 11), error_of("let p = x; p = y; p = e"));
+        assert_eq!((ParsingErrorKind::RecursiveDefinition(String::from("p")), 7), error_of("let p = xP; p = e"));
+        assert_eq!((ParsingErrorKind::UnboundName(String::from("q")), 7), error_of("let p = q; q = x; p = e"));
+    }
+
+    #[test]
+    fn test_standard_dialect() {
+        let standard = ParseOptions { dialect: Dialect::Standard, ..ParseOptions::default() };
+        let mut symbols = SymbolTable::new();
+        let formula = parse("let pos = x | 1; conj = pos^y; conj >= 1", &standard, &mut symbols);
+        assert_eq!(parse("1 <= (x | 1)^y", &standard, &mut symbols), formula);
+    }
+}
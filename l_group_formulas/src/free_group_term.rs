@@ -1,9 +1,12 @@
 use super::*;
 use super::literal::*;
 use super::short_free_group_term::ShortFreeGroupTerm;
+use super::parsing::ParseOptions;
+use super::parsing_error::ParsingError;
+use super::symbol_table::SymbolTable;
 use std::ops::{Mul, MulAssign};
 
-mod parse_free_group_term;
+pub (crate) mod parse_free_group_term;
 
 /// An element of the group algebra.
 /// 
@@ -36,7 +39,7 @@ mod parse_free_group_term;
 /// 
 /// This ignores all non-alphanumeric characters, such as `*`. Perhaps dangerously,
 /// this also ignores symbols like `^`, and treats `v` as the name of a symbol.
-/// The input is parsed by 
+/// Use `FreeGroupTerm::parse_with` in strict mode to reject such characters.
 /// 
 /// # Examples
 /// Basic usage:
@@ -59,16 +62,14 @@ pub struct FreeGroupTerm {
 
 impl FreeGroupTerm {
     pub fn new(literals: Vec<literal::Literal>) -> FreeGroupTerm {
-        FreeGroupTerm { literals: literals }.reduced()
+        FreeGroupTerm { literals }.reduced()
     }
 }
 
 pub const FREE_GROUP_IDENTITY: FreeGroupTerm = FreeGroupTerm { literals: vec![] };
 
 pub fn len(t: &FreeGroupTerm) -> usize {
-    match t {
-        FreeGroupTerm { literals } => literals.len()
-    }
+    t.literals.len()
 }
 
 impl From<Literal> for FreeGroupTerm {
@@ -83,12 +84,53 @@ impl From<char> for FreeGroupTerm {
     }
 }
 
+impl FreeGroupTerm {
+    /// Parses a free group term, returning an error instead of panicking on invalid input.
+    /// ```
+    /// use l_group_formulas::free_group_term::FreeGroupTerm;
+    /// use l_group_formulas::parsing::ParseOptions;
+    /// let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+    /// assert_eq!(Ok(FreeGroupTerm::from("xy")), FreeGroupTerm::parse_with("x y", &strict));
+    /// assert!(FreeGroupTerm::parse_with("x^y", &strict).is_err());
+    /// ```
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<FreeGroupTerm, ParsingError> {
+        FreeGroupTerm::parse_with_symbols(s, options, &mut SymbolTable::new())
+    }
+
+    /// Parses a free group term as `FreeGroupTerm::parse_with` does, interning
+    /// the names of variables in `symbols` in the `Standard` dialect.
+    pub fn parse_with_symbols(
+            s: &str,
+            options: &ParseOptions,
+            symbols: &mut SymbolTable) -> Result<FreeGroupTerm, ParsingError> {
+        parse_free_group_term::parse(s, options, symbols)
+    }
+
+    /// Prints the term in the `Standard` dialect, with the names in `symbols`.
+    pub fn to_string_with(&self, symbols: &SymbolTable) -> String {
+        if self.literals.is_empty() {
+            return String::from("1");
+        }
+        self.literals.iter()
+            .map(|x| x.to_string_with(symbols))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+impl std::str::FromStr for FreeGroupTerm {
+    type Err = ParsingError;
+    fn from_str(s: &str) -> Result<FreeGroupTerm, ParsingError> {
+        FreeGroupTerm::parse_with(s, &ParseOptions::default())
+    }
+}
+
 impl From<&str> for FreeGroupTerm {
+    /// Panics on invalid input. Use `str::parse` to handle errors instead.
     fn from(s: &str) -> FreeGroupTerm {
-        let result = parse_free_group_term::parse(s);
-        match result {
+        match s.parse() {
             Ok(t) => t,
-            Err(e) => panic!(e)
+            Err(e) => panic!("{}", e.render())
         }
     }
 }
@@ -122,8 +164,8 @@ impl Mul for FreeGroupTerm {
     type Output = FreeGroupTerm;
     
     fn mul(self, other: FreeGroupTerm) -> FreeGroupTerm {
-        if self.literals.len() == 0 { other } 
-        else if other.literals.len() == 0 { self } 
+        if self.literals.is_empty() { other } 
+        else if other.literals.is_empty() { self } 
         else {
             FreeGroupTerm::new([&self.literals[..], &other.literals[..]].concat())
         }
@@ -147,16 +189,15 @@ impl MulAssign for FreeGroupTerm {
     }
 }
 
-impl ToString for FreeGroupTerm {
-    fn to_string(&self) -> String {
-        let mut result = String::from("");
-        for l in &self.literals {
-            result.push_str(&l.to_string());
+impl std::fmt::Display for FreeGroupTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.literals.is_empty() {
+            return write!(f, "e");
         }
-        if result == "" {
-            return String::from("e");
+        for l in &self.literals {
+            write!(f, "{}", l)?;
         }
-        return result;
+        Ok(())
     }
 }
 
@@ -172,7 +213,7 @@ impl Reducable for FreeGroupTerm {
             DidNotReduce
         }
 
-        while literals.len() > 0 && index < literals.len() - 1 {
+        while !literals.is_empty() && index < literals.len() - 1 {
             let mut reducing_state = ReducingState::DidNotReduce;
             if literals[index] == literals[index + 1].inverse() {
                 literals.remove(index);
@@ -188,7 +229,7 @@ impl Reducable for FreeGroupTerm {
                 ReducingState::DidNotReduce       => index + 1
             };
         }
-        return FreeGroupTerm { literals: (literals).to_vec() }
+        FreeGroupTerm { literals: (literals).to_vec() }
     }
 }
 
@@ -1,58 +1,52 @@
 use super::super::free_group_term::FreeGroupTerm;
-use super::super::literal::Literal;
-use super::parsing_error::ParsingError;
+use super::super::literal::{self, Literal};
+use super::super::l_group_term::parse_standard_l_group_term;
+use super::super::parsing::{Cursor, Dialect, ParseOptions};
+use super::super::symbol_table::SymbolTable;
+use super::parsing_error::{ParsingError, ParsingErrorKind};
 
 
-
-pub (super) fn parse(s: &str) -> Result<FreeGroupTerm, ParsingError> {
-    if s == "e" {
-        return Ok(FreeGroupTerm::new(Vec::new()));
+pub (super) fn parse(s: &str, options: &ParseOptions, symbols: &mut SymbolTable) -> Result<FreeGroupTerm, ParsingError> {
+    if options.dialect == Dialect::Standard {
+        return parse_standard_l_group_term::parse_free_group_term(s, symbols);
     }
-    let mut literals = Vec::new();
-    let mut iterator = s.chars();
-    let first = iterator.next();
-    let mut current_literal_string = String::new();
-    let first_char: char;
-    match first {
-        None => return Err(ParsingError::EmptyFreeGroupTermError),
-        Some(c) => first_char = c
-    };
-    current_literal_string.push(first_char);
-    for c in iterator {
-        if c.is_numeric() {
-            current_literal_string.push(c);
-        } else if c.is_alphabetic() {
-            let parsed_literal = std::panic::catch_unwind(|| Literal::from(current_literal_string.as_str()));
-            match parsed_literal {
-                Ok(literal) => {
-                    if literal != Literal::new('e', 0, false) {
-                        literals.push(literal);
-                    }
-                },
-                Err(_) => return Err(ParsingError::InvalidLiteralError(current_literal_string))
-            }
-            current_literal_string = String::new();
-            current_literal_string.push(c);
-        } 
+    let mut cursor = Cursor::new(s, 0, s.len(), char::is_alphanumeric, options)?;
+    if cursor.is_at_end() {
+        return Err(cursor.error(ParsingErrorKind::EmptyTerm, 0, s.len()));
     }
-    if current_literal_string != String::new() {
-        let parsed_literal = std::panic::catch_unwind(|| Literal::from(current_literal_string.as_str()));
-        match parsed_literal {
-            Ok(literal) => {
-                if literal != Literal::new('e', 0, false) {
-                    literals.push(literal);
-                }
-            },
-            Err(_) => return Err(ParsingError::InvalidLiteralError(current_literal_string))
+    let term = parse_literals(&mut cursor, |c| c.is_alphabetic())?;
+    match cursor.is_at_end() {
+        true  => Ok(term),
+        false => Err(cursor.unexpected())
+    }
+}
+
+/// Parses a (possibly empty) sequence of literals, whose letters satisfy `is_letter`.
+/// The literal `e` stands for the identity.
+pub (crate) fn parse_literals(cursor: &mut Cursor, is_letter: fn(char) -> bool) -> Result<FreeGroupTerm, ParsingError> {
+    let mut literals = Vec::new();
+    while let Some(c) = cursor.peek() {
+        if !is_letter(c) { break; }
+        let literal = literal::parse(cursor, is_letter)?;
+        if literal != Literal::new('e', 0, false) {
+            literals.push(literal);
         }
     }
-    return Ok(FreeGroupTerm::new(literals));
+    Ok(FreeGroupTerm::new(literals))
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::super::literal::Literal;
     use super::super::super::free_group_term::FreeGroupTerm;
+    use super::super::super::parsing::ParseOptions;
+    use super::super::super::symbol_table::SymbolTable;
+    use super::super::super::parsing_error::ParsingError;
+
+    fn parse(s: &str, options: &ParseOptions) -> Result<FreeGroupTerm, ParsingError> {
+        super::parse(s, options, &mut SymbolTable::new())
+    }
+    use super::super::super::parsing_error::ParsingErrorKind;
 
     #[test]
     fn test_parse() {
@@ -61,6 +55,24 @@ mod tests {
         let y = Literal::new('y', 0, false);
         let z = Literal::new('z', 39, false);
         let term = FreeGroupTerm::new(vec![x, y, z]);
-        assert_eq!(Ok(term), super::parse(&string));
+        assert_eq!(Ok(term), parse(&string, &ParseOptions::default()));
+    }
+
+    #[test]
+    fn test_strict() {
+        let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+        assert!(parse("X31 yz39", &strict).is_ok());
+        let error = parse("X31*yz39", &strict).unwrap_err();
+        assert_eq!(ParsingErrorKind::UnexpectedCharacter('*'), error.kind);
+        assert_eq!(3, error.start);
+    }
+
+    #[test]
+    fn test_errors() {
+        let options = ParseOptions::default();
+        assert_eq!(ParsingErrorKind::EmptyTerm, parse(" ", &options).unwrap_err().kind);
+        assert_eq!(ParsingErrorKind::UnexpectedCharacter('3'), parse("3x", &options).unwrap_err().kind);
+        let error = parse("xy99999999999999999999999", &options).unwrap_err();
+        assert_eq!((1, 25), (error.start, error.end));
     }
 }
\ No newline at end of file
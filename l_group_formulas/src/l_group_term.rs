@@ -1,11 +1,16 @@
 use super::free_group_term::*;
 use super::*;
 use super::literal::*;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Mul;
 use super::l_group_term_reducing::*;
+use super::parsing::ParseOptions;
+use super::parsing_error::ParsingError;
+use super::symbol_table::SymbolTable;
 
-mod parse_l_group_term;
+pub (crate) mod parse_l_group_term;
+pub (crate) mod parse_standard_l_group_term;
+mod fold_l_group_term;
 
 /// An element the term algebra of l-groups.
 /// 
@@ -56,6 +61,175 @@ pub enum LGroupTerm {
 
 pub const IDENTITY: LGroupTerm = LGroupTerm::Atom(FreeGroupTerm { literals: vec![] } );
 
+impl LGroupTerm {
+    /// Returns the variables occurring in the term, as non-inverted literals.
+    /// ```
+    /// use l_group_formulas::l_group_term::LGroupTerm;
+    /// use l_group_formulas::literal::Literal;
+    /// let variables = LGroupTerm::from("x(Y v z2)").variables();
+    /// let expected = vec![Literal::from('x'), Literal::from('y'), Literal::new('z', 2, false)];
+    /// assert_eq!(expected, variables.into_iter().collect::<Vec<Literal>>());
+    /// ```
+    pub fn variables(&self) -> BTreeSet<Literal> {
+        let mut variables = BTreeSet::new();
+        match self {
+            LGroupTerm::Atom(x) => {
+                for literal in &x.literals {
+                    variables.insert(Literal::new(literal.character, literal.id, false));
+                }
+            },
+            LGroupTerm::Meet(xs) | LGroupTerm::Join(xs) => {
+                for x in xs { variables.append(&mut x.variables()); }
+            },
+            LGroupTerm::Prod(xs) => {
+                for x in xs { variables.append(&mut x.variables()); }
+            }
+        };
+        variables
+    }
+
+    /// Replaces the variables which have a definition in `definitions`
+    /// by their definition, and their inverses by its inverse.
+    /// ```
+    /// use l_group_formulas::l_group_term::LGroupTerm;
+    /// use l_group_formulas::literal::Literal;
+    /// use std::collections::BTreeMap;
+    /// let mut definitions = BTreeMap::new();
+    /// definitions.insert(Literal::from('p'), LGroupTerm::from("x ^ e"));
+    /// let term = LGroupTerm::from("pyP").substituted(&definitions);
+    /// assert_eq!(LGroupTerm::from("(x ^ e) y -(x ^ e)"), term);
+    /// ```
+    pub fn substituted(&self, definitions: &BTreeMap<Literal, LGroupTerm>) -> LGroupTerm {
+        match self {
+            LGroupTerm::Atom(x) => {
+                let is_defined = |l: &Literal| definitions.contains_key(&Literal::new(l.character, l.id, false));
+                if !x.literals.iter().any(is_defined) {
+                    return self.clone();
+                }
+                let factors = x.literals.iter()
+                    .map(|l| match definitions.get(&Literal::new(l.character, l.id, false)) {
+                        Some(term) if l.is_inverted => term.inverse(),
+                        Some(term) => term.clone(),
+                        None => LGroupTerm::from(*l)
+                    })
+                    .collect();
+                LGroupTerm::Prod(factors).reduced()
+            },
+            LGroupTerm::Meet(xs) => LGroupTerm::Meet(xs.iter().map(|x| x.substituted(definitions)).collect()).reduced(),
+            LGroupTerm::Join(xs) => LGroupTerm::Join(xs.iter().map(|x| x.substituted(definitions)).collect()).reduced(),
+            LGroupTerm::Prod(xs) => LGroupTerm::Prod(xs.iter().map(|x| x.substituted(definitions)).collect()).reduced()
+        }
+    }
+
+    /// Returns the positive part `x⁺ = x ∨ e`.
+    pub fn positive_part(&self) -> LGroupTerm {
+        join_of(vec![self.clone(), IDENTITY])
+    }
+
+    /// Returns the negative part `x⁻ = x⁻¹ ∨ e`.
+    pub fn negative_part(&self) -> LGroupTerm {
+        join_of(vec![self.inverse(), IDENTITY])
+    }
+
+    /// Returns the absolute value `|x| = x ∨ x⁻¹`.
+    pub fn absolute_value(&self) -> LGroupTerm {
+        join_of(vec![self.clone(), self.inverse()])
+    }
+
+    /// Returns the commutator `[x, y] = x⁻¹y⁻¹xy`.
+    /// ```
+    /// use l_group_formulas::l_group_term::LGroupTerm;
+    /// let commutator = LGroupTerm::from('x').commutator(&LGroupTerm::from('y'));
+    /// assert_eq!(LGroupTerm::from("XYxy"), commutator);
+    /// ```
+    pub fn commutator(&self, other: &LGroupTerm) -> LGroupTerm {
+        LGroupTerm::Prod(vec![self.inverse(), other.inverse(), self.clone(), other.clone()]).reduced()
+    }
+
+    /// Returns the conjugate `x^y = y⁻¹xy`.
+    pub fn conjugate(&self, by: &LGroupTerm) -> LGroupTerm {
+        LGroupTerm::Prod(vec![by.inverse(), self.clone(), by.clone()]).reduced()
+    }
+
+    /// Returns the `n`-th power, where negative powers are powers of the inverse.
+    /// ```
+    /// use l_group_formulas::l_group_term::LGroupTerm;
+    /// assert_eq!(LGroupTerm::from("-(x v y)-(x v y)"), LGroupTerm::from("x v y").power(-2));
+    /// ```
+    pub fn power(&self, n: i64) -> LGroupTerm {
+        let base = match n < 0 {
+            true  => self.inverse(),
+            false => self.clone()
+        };
+        match n.unsigned_abs() {
+            0 => IDENTITY,
+            1 => base,
+            k => LGroupTerm::Prod(vec![base; k as usize]).reduced()
+        }
+    }
+}
+
+fn join_of(joinands: Vec<LGroupTerm>) -> LGroupTerm {
+    LGroupTerm::Join(joinands.into_iter().collect()).reduced()
+}
+
+impl LGroupTerm {
+    /// Parses an l-group term as `LGroupTerm::from(&str)` does, but returns an error
+    /// instead of panicking on invalid input.
+    /// ```
+    /// use l_group_formulas::l_group_term::LGroupTerm;
+    /// use l_group_formulas::parsing::ParseOptions;
+    /// let error = LGroupTerm::parse_with("x v (y ^ z))", &ParseOptions::default()).unwrap_err();
+    /// assert_eq!(11, error.start);
+    /// ```
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<LGroupTerm, ParsingError> {
+        LGroupTerm::parse_with_symbols(s, options, &mut SymbolTable::new())
+    }
+
+    /// Parses an l-group term as `LGroupTerm::parse_with` does, interning the
+    /// names of variables in `symbols` in the `Standard` dialect. In the
+    /// `Legacy` dialect, `symbols` is left unchanged.
+    /// ```
+    /// use l_group_formulas::l_group_term::LGroupTerm;
+    /// use l_group_formulas::parsing::{ParseOptions, Dialect};
+    /// use l_group_formulas::symbol_table::SymbolTable;
+    /// let standard = ParseOptions { dialect: Dialect::Standard, ..ParseOptions::default() };
+    /// let mut symbols = SymbolTable::new();
+    /// let term = LGroupTerm::parse_with_symbols("alpha^-1 * beta | 1", &standard, &mut symbols).unwrap();
+    /// assert_eq!("(1 \\/ (alpha^-1 * beta))", term.to_string_with(&symbols));
+    /// ```
+    pub fn parse_with_symbols(
+            s: &str,
+            options: &ParseOptions,
+            symbols: &mut SymbolTable) -> Result<LGroupTerm, ParsingError> {
+        parse_l_group_term::parse(s, options, symbols).map(|term| term.reduced())
+    }
+
+    /// Prints the term in the `Standard` dialect, with the names in `symbols`.
+    pub fn to_string_with(&self, symbols: &SymbolTable) -> String {
+        let (delimiter, elements): (&str, Vec<&LGroupTerm>) = match self {
+            LGroupTerm::Atom(x) => return x.to_string_with(symbols),
+            LGroupTerm::Meet(xs) => (" /\\ ", xs.iter().collect()),
+            LGroupTerm::Join(xs) => (" \\/ ", xs.iter().collect()),
+            LGroupTerm::Prod(xs) => (" * ", xs.iter().collect())
+        };
+        let elements: Vec<String> = elements.iter().map(|x| x.to_string_with(symbols)).collect();
+        format!("({})", elements.join(delimiter))
+    }
+
+    /// Prints the term as `to_string_with` does, but folds expanded derived
+    /// operations back into short forms, e.g., `x ∨ e` into `x^+`.
+    /// ```
+    /// use l_group_formulas::l_group_term::LGroupTerm;
+    /// use l_group_formulas::symbol_table::SymbolTable;
+    /// let term = LGroupTerm::from('x').absolute_value() * LGroupTerm::from("XYxy");
+    /// assert_eq!("(|x| * [x, y])", term.to_folded_string_with(&SymbolTable::new()));
+    /// ```
+    pub fn to_folded_string_with(&self, symbols: &SymbolTable) -> String {
+        fold_l_group_term::fold(self, symbols)
+    }
+}
+
 impl From<FreeGroupTerm> for LGroupTerm {
     fn from(x: FreeGroupTerm) -> LGroupTerm {
         LGroupTerm::Atom(x).reduced()
@@ -93,15 +267,23 @@ impl From<&str> for LGroupTerm {
     /// let term = LGroupTerm::from('x') *  LGroupTerm::Meet(meetands);
     /// assert_eq!(term, LGroupTerm::from("x(y^z)"));
     /// ```
+    ///
+    /// Panics on invalid input. Use `str::parse` to handle errors instead.
     fn from(s: &str) -> LGroupTerm {
-        let result = parse_l_group_term::parse(s);
-        match result {
-            Ok(term) => term.reduced(),
-            Err(e) => panic!(e)
+        match s.parse() {
+            Ok(term) => term,
+            Err(e) => panic!("{}", e.render())
         }
     }
 }
 
+impl std::str::FromStr for LGroupTerm {
+    type Err = ParsingError;
+    fn from_str(s: &str) -> Result<LGroupTerm, ParsingError> {
+        LGroupTerm::parse_with(s, &ParseOptions::default())
+    }
+}
+
 impl From<char> for LGroupTerm {
     fn from(c: char) -> LGroupTerm {
         LGroupTerm::Atom(FreeGroupTerm::from(c))
@@ -168,12 +350,12 @@ impl Reducable for LGroupTerm {
     }
 }
 
-impl ToString for LGroupTerm {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for LGroupTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let delimiter: char;
         let mut elements = Vec::new();
         match self {
-            LGroupTerm::Atom(x) => return x.to_string(),
+            LGroupTerm::Atom(x) => return write!(f, "{}", x),
             LGroupTerm::Meet(xs) => {
                 delimiter = '^';
                 for x in xs { elements.push(x); }
@@ -188,15 +370,15 @@ impl ToString for LGroupTerm {
             }
         }
 
-        if elements.len() == 0 {
-            return format!("Empty '{}'", delimiter);
+        if elements.is_empty() {
+            return write!(f, "Empty '{}'", delimiter);
         }
 
-        let mut string = format!("{}", elements[0].to_string());
-        for i in 1 .. elements.len() {
-            string.push_str(format!(" {} {}", delimiter, elements[i].to_string()).as_str());
+        write!(f, "({}", elements[0])?;
+        for element in &elements[1..] {
+            write!(f, " {} {}", delimiter, element)?;
         }
-        return format!("({})", string);
+        write!(f, ")")
     }
 }
 
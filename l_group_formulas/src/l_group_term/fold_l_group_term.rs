@@ -0,0 +1,177 @@
+use super::super::free_group_term::FreeGroupTerm;
+use super::super::l_group_term::{LGroupTerm, IDENTITY};
+use super::super::symbol_table::SymbolTable;
+use super::super::Term;
+
+/// Where a printed term may appear without brackets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Form {
+    /// An identifier, or a term in brackets, which can be a conjugator.
+    Primary,
+    /// A term ending with a postfix operator, which can be followed by another one.
+    Postfix,
+    /// A product written by juxtaposition.
+    Compound
+}
+
+/// Prints `term` in the `Standard` dialect, where joins with the identity,
+/// joins with the inverse, commutators, conjugates, and powers are written
+/// as `x^+`, `x^-`, `|x|`, `[x, y]`, `x^y`, and `x**n`.
+pub (super) fn fold(term: &LGroupTerm, symbols: &SymbolTable) -> String {
+    fold_with_form(term, symbols).0
+}
+
+fn fold_with_form(term: &LGroupTerm, symbols: &SymbolTable) -> (String, Form) {
+    match term {
+        LGroupTerm::Atom(w) => fold_word(w, symbols),
+        LGroupTerm::Join(xs) if xs.len() == 2 => {
+            let mut joinands = xs.iter();
+            let (a, b) = (joinands.next().unwrap(), joinands.next().unwrap());
+            if *a == IDENTITY || *b == IDENTITY {
+                let x = if *a == IDENTITY { b } else { a };
+                return match is_inverted_word(x) {
+                    true  => (format!("{}^-", operand(&x.inverse(), symbols)), Form::Postfix),
+                    false => (format!("{}^+", operand(x, symbols)), Form::Postfix)
+                };
+            }
+            if *b == a.inverse() {
+                let x = if is_inverted_word(a) { b } else { a };
+                return (format!("|{}|", fold(x, symbols)), Form::Primary);
+            }
+            (lattice_string(xs.iter(), " \\/ ", symbols), Form::Primary)
+        },
+        LGroupTerm::Join(xs) => (lattice_string(xs.iter(), " \\/ ", symbols), Form::Primary),
+        LGroupTerm::Meet(xs) => (lattice_string(xs.iter(), " /\\ ", symbols), Form::Primary),
+        LGroupTerm::Prod(xs) => {
+            if xs.len() == 4 && xs[0] == xs[2].inverse() && xs[1] == xs[3].inverse() {
+                return (format!("[{}, {}]", fold(&xs[2], symbols), fold(&xs[3], symbols)), Form::Primary);
+            }
+            if xs.len() == 3 && xs[0] == xs[2].inverse() && !is_inverted_word(&xs[2]) {
+                return (format!("{}^{}", operand(&xs[1], symbols), conjugator(&xs[2], symbols)), Form::Postfix);
+            }
+            let mut runs: Vec<(&LGroupTerm, usize)> = Vec::new();
+            for x in xs {
+                match runs.last_mut() {
+                    Some((y, k)) if *y == x => *k += 1,
+                    _ => runs.push((x, 1))
+                };
+            }
+            let mut factors: Vec<(String, Form)> = runs.into_iter()
+                .map(|(x, k)| match (k, is_inverted_word(x)) {
+                    (1, _) => fold_with_form(x, symbols),
+                    (k, false) => (format!("{}**{}", operand(x, symbols), k), Form::Postfix),
+                    (k, true) => (format!("{}**-{}", operand(&x.inverse(), symbols), k), Form::Postfix)
+                })
+                .collect();
+            match factors.len() {
+                1 => factors.pop().unwrap(),
+                _ => {
+                    let factors: Vec<String> = factors.into_iter().map(|(s, _)| s).collect();
+                    (format!("({})", factors.join(" * ")), Form::Primary)
+                }
+            }
+        }
+    }
+}
+
+fn lattice_string<'a>(
+        xs: impl Iterator<Item = &'a LGroupTerm>,
+        delimiter: &str,
+        symbols: &SymbolTable) -> String {
+    let elements: Vec<String> = xs.map(|x| fold(x, symbols)).collect();
+    format!("({})", elements.join(delimiter))
+}
+
+fn fold_word(w: &FreeGroupTerm, symbols: &SymbolTable) -> (String, Form) {
+    let literals = &w.literals;
+    let name = |i: usize| symbols.name_of_variable(&literals[i]);
+    if literals.is_empty() {
+        return (String::from("1"), Form::Primary);
+    }
+    if literals.len() == 4 && literals[0] == literals[2].inverse() && literals[1] == literals[3].inverse()
+            && !literals[2].is_inverted && !literals[3].is_inverted {
+        return (format!("[{}, {}]", name(2), name(3)), Form::Primary);
+    }
+    if literals.len() == 3 && literals[0] == literals[2].inverse() && !literals[2].is_inverted {
+        return (format!("{}^{}", literals[1].to_string_with(symbols), name(2)), Form::Postfix);
+    }
+
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for i in 0 .. literals.len() {
+        match runs.last_mut() {
+            Some((j, k)) if literals[*j] == literals[i] => *k += 1,
+            _ => runs.push((i, 1))
+        };
+    }
+    let mut factors: Vec<String> = runs.iter()
+        .map(|(i, k)| match (k, literals[*i].is_inverted) {
+            (1, _) => literals[*i].to_string_with(symbols),
+            (k, false) => format!("{}**{}", name(*i), k),
+            (k, true) => format!("{}**-{}", name(*i), k)
+        })
+        .collect();
+    match (factors.len(), runs[0].1 == 1 && !literals[0].is_inverted) {
+        (1, true)  => (factors.pop().unwrap(), Form::Primary),
+        (1, false) => (factors.pop().unwrap(), Form::Postfix),
+        _ => (factors.join(" "), Form::Compound)
+    }
+}
+
+/// Whether `x` is a non-empty word of inverted literals, which reads
+/// better as the inverse of a word of variables.
+fn is_inverted_word(x: &LGroupTerm) -> bool {
+    match x {
+        LGroupTerm::Atom(w) => !w.literals.is_empty() && w.literals.iter().all(|l| l.is_inverted),
+        _ => false
+    }
+}
+
+/// The string of `x` as the operand of a postfix operator.
+fn operand(x: &LGroupTerm, symbols: &SymbolTable) -> String {
+    match fold_with_form(x, symbols) {
+        (s, Form::Compound) => format!("({})", s),
+        (s, _) => s
+    }
+}
+
+/// The string of `x` as a conjugator, which has to be primary.
+fn conjugator(x: &LGroupTerm, symbols: &SymbolTable) -> String {
+    match fold_with_form(x, symbols) {
+        (s, Form::Primary) => s,
+        (s, _) => format!("({})", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::parsing::{Dialect, ParseOptions};
+
+    fn check_round_trip(s: &str, expected: &str) {
+        let standard = ParseOptions { dialect: Dialect::Standard, ..ParseOptions::default() };
+        let mut symbols = SymbolTable::new();
+        let term = LGroupTerm::parse_with_symbols(s, &standard, &mut symbols).unwrap();
+        let folded = fold(&term, &symbols);
+        assert_eq!(expected, folded);
+        assert_eq!(Ok(term), LGroupTerm::parse_with_symbols(&folded, &standard, &mut symbols));
+    }
+
+    #[test]
+    fn test_lattice_parts() {
+        check_round_trip("x^+", "x^+");
+        check_round_trip("x⁻", "x^-");
+        check_round_trip("|x|", "|x|");
+        check_round_trip("|x y| * z", "(|(x * y)| * z)");
+        check_round_trip("(x | y)^+", "(1 \\/ x \\/ y)");
+    }
+
+    #[test]
+    fn test_group_operations() {
+        check_round_trip("[x, y]", "[x, y]");
+        check_round_trip("x^y", "x^y");
+        check_round_trip("x**3 y**-2", "x**3 y**-2");
+        check_round_trip("(x | y)**-2", "(x^-1 /\\ y^-1)**2");
+        check_round_trip("[x | y, z]", "[(x \\/ y), z]");
+        check_round_trip("(x | y)^(z w)", "(x \\/ y)^(z w)");
+    }
+}
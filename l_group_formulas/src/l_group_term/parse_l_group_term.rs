@@ -1,281 +1,201 @@
-use super::super::free_group_term::FreeGroupTerm;
+use super::super::free_group_term::parse_free_group_term::parse_literals;
 use super::super::l_group_term::LGroupTerm;
+use super::super::parsing::{Cursor, Dialect, ParseOptions};
+use super::super::parsing_error::{ParsingError, ParsingErrorKind};
+use super::super::symbol_table::SymbolTable;
 use super::super::Term;
 use std::collections::BTreeSet;
-use super::super::parsing_error::ParsingError;
 
-
-pub (super) fn parse(s: &str) -> Result<LGroupTerm, ParsingError> {
-    let mut string = s.to_string();
-    // remove whitespace and outer brackets
-    string.retain(|c| !c.is_whitespace());
-    string.retain(|c| c.is_alphanumeric() || c == '(' || c == ')' || c == '^' || c == '-');
-    loop {
-        let result = has_outermost_brackets(&string);
-        match result {
-            Err(e) => return Err(e),
-            Ok(can_be_stripped) => {
-                if string.len() >= 2 && can_be_stripped {
-                    string = string[1..string.len() - 1].to_string();
-                }
-                else {
-                    break;
-                }
-            }
-        }
+/// Parses `input[start .. end]` in the dialect given by `options`, interning
+/// the names of variables in `symbols` in the `Standard` dialect.
+/// The offsets in errors refer to all of `input`.
+pub (crate) fn parse_range(
+        input: &str,
+        start: usize,
+        end: usize,
+        options: &ParseOptions,
+        symbols: &mut SymbolTable) -> Result<LGroupTerm, ParsingError> {
+    match options.dialect {
+        Dialect::Legacy => parse_legacy_range(input, start, end, options),
+        Dialect::Standard => super::parse_standard_l_group_term::parse_range(input, start, end, symbols)
     }
+}
 
-    if is_atom(&string) { 
-        let parsed_free_group_term = std::panic::catch_unwind(|| FreeGroupTerm::from(string.as_str()));
-        match parsed_free_group_term {
-            Err(_) => return Err(ParsingError::ParsingAtomError(string)),
-            Ok(term) => return Ok(LGroupTerm::Atom(term))
-        };
-    }
-    
-    if is_inverse(&string) {
-        let result = parse(&string[1..string.len()].to_string());
-        match result {
-            Ok(term) => return Ok(term.inverse()),
-            Err(e) => return Err(ParsingError::ParsingInverseError(string, e.to_string()))
-        };
+/// Parses `input[start .. end]` in the `Legacy` dialect, i.e., in the grammar
+/// ```text
+/// meet    := join ('^' join)*
+/// join    := product ('v' product)*
+/// product := factor* ('-' factor factor*)*
+/// factor  := '(' meet ')' | literal literal*
+/// ```
+/// where the literals of a factor form a single atom, a product is not
+/// empty, and a `-` inverts the rest of the product up to the next `-`, so
+/// that `-(x v y)z` is the inverse of `(x v y)z`, and `x-yz-w` is
+/// `x(yz)⁻¹w⁻¹`.
+fn parse_legacy_range(
+        input: &str,
+        start: usize,
+        end: usize,
+        options: &ParseOptions) -> Result<LGroupTerm, ParsingError> {
+    let mut cursor = Cursor::new(input, start, end, is_meaningful, options)?;
+    if cursor.is_at_end() {
+        return Err(cursor.error(ParsingErrorKind::EmptyTerm, start, end));
     }
-
-    if is_meet(&string) {
-        let mut meetands = BTreeSet::new();
-        let result = split_at_outermost_meet(&string);
-        match result {
-            Err(e) => return Err(e),
-            Ok(strings) => {
-                for term_string in strings {
-                    let result = parse(&term_string);
-                    match result {
-                        Ok(term) => meetands.insert(term),
-                        Err(e) => return Err(ParsingError::ParsingMeetError(string, e.to_string()))
-                    };
-                }
-            }
-        };
-        return Ok(LGroupTerm::Meet(meetands));
+    let term = parse_meet(&mut cursor)?;
+    match cursor.peek() {
+        None => Ok(term),
+        Some(')') => Err(cursor.error(ParsingErrorKind::UnopenedBracket, cursor.offset(), cursor.offset() + 1)),
+        Some(_) => Err(cursor.unexpected())
     }
+}
 
-    if is_join(&string) {
-        let mut joinands = BTreeSet::new();
-        let result = split_at_outermost_join(&string);
-        match result {
-            Err(e) => return Err(e),
-            Ok(strings) => {
-                for term_string in strings {
-                    let result = parse(&term_string);
-                    match result {
-                        Ok(term) => joinands.insert(term),
-                        Err(e) => return Err(ParsingError::ParsingJoinError(string, e.to_string()))
-                    };
-                }
-            }
-        };
-        return Ok(LGroupTerm::Join(joinands));
-    }
+pub (super) fn parse(s: &str, options: &ParseOptions, symbols: &mut SymbolTable) -> Result<LGroupTerm, ParsingError> {
+    parse_range(s, 0, s.len(), options, symbols)
+}
 
-    let mut factors = Vec::new();
-    let mut current_factor = String::new();
-    let mut depth = 0;
-    for c in string.chars() {
-        match &c {
-            '(' => {
-                if depth == 0 && current_factor.len() > 0 {
-                    let result = parse(&current_factor);
-                    match result {
-                        Ok(term) => factors.push(term),
-                        Err(e) => return Err(ParsingError::ParsingProductError(string, current_factor, e.to_string()))
-                    };
-                    current_factor = String::new();
-                }
-                depth += 1;
-                current_factor.push(c);
-            },
-            ')' => {
-                match depth {
-                    0 => return Err(ParsingError::NonMatchingBracketsError(string.to_string())),
-                    _ => depth -= 1
-                };
-                current_factor.push(c);
-                if depth == 0 {
-                    let result = parse(&current_factor);
-                    match result {
-                        Ok(term) => factors.push(term),
-                        Err(e) => return Err(ParsingError::ParsingProductError(string, current_factor, e.to_string()))
-                    };
-                    current_factor = String::new();
-                }
-            },
-            '-' => {
-                if depth == 0 && current_factor.len() > 0 {
-                    let result = parse(&current_factor);
-                    match result {
-                        Ok(term) => factors.push(term),
-                        Err(e) => return Err(ParsingError::ParsingProductError(string, current_factor, e.to_string()))
-                    };
-                    current_factor = String::new();
-                }
-                current_factor.push(c);
-            }
-            _ => current_factor.push(c)
-        };
-    }
-    if current_factor.len() > 0 {
-        let result = parse(&current_factor);
-            match result {
-                Ok(term) => factors.push(term),
-                Err(e) => return Err(ParsingError::ParsingProductError(string, current_factor, e.to_string()))
-            };
-    }
-    return Ok(LGroupTerm::Prod(factors));
+/// The characters which are not dropped in non-strict mode.
+fn is_meaningful(c: char) -> bool {
+    c.is_alphanumeric() || c == '(' || c == ')' || c == '^' || c == '-'
 }
 
-fn split_at_outermost_join(s: &String) -> Result<Vec<String>, ParsingError> {
-    let mut depth = 0;
-    let mut strings = Vec::new();
-    let mut current_string = String::new();
-    for c in s.chars() {
-        match c {
-            '(' => {
-                depth += 1;
-                current_string.push(c);
-            },
-            ')' => {
-                match depth {
-                    0 => return Err(ParsingError::NonMatchingBracketsError(s.clone())),
-                    _ => depth -= 1
-                };
-                current_string.push(c);
-            },
-            'v' => {
-                if depth == 0 {
-                    strings.push(current_string.clone());
-                    current_string = String::new();
-                }
-                else {
-                    current_string.push(c);
-                }
-            },
-            _ => current_string.push(c)
-        };
-    }
-    strings.push(current_string);
-    return Ok(strings);
+/// `v` denotes joins, so it cannot be the name of a variable.
+fn is_letter(c: char) -> bool {
+    c.is_alphabetic() && c != 'v'
 }
 
-fn split_at_outermost_meet(s: &String) -> Result<Vec<String>, ParsingError> {
-    let mut depth = 0;
-    let mut strings = Vec::new();
-    let mut current_string = String::new();
-    for c in s.chars() {
-        match c {
-            '(' => depth += 1,
-            ')' => {
-                match depth {
-                    0 => return Err(ParsingError::NonMatchingBracketsError(s.clone())),
-                    _ => depth -= 1
-                };
-            },
-            '^' => {
-                if depth == 0 {
-                    strings.push(current_string.clone());
-                    current_string = String::new();
-                }
-                else {
-                    current_string.push(c);
-                }
-            },
-            _ => current_string.push(c)
-        };
+fn parse_meet(cursor: &mut Cursor) -> Result<LGroupTerm, ParsingError> {
+    let mut meetands = vec![parse_join(cursor)?];
+    while cursor.peek() == Some('^') {
+        cursor.next();
+        meetands.push(parse_join(cursor)?);
     }
-    strings.push(current_string);
-    return Ok(strings);
+    Ok(match meetands.len() {
+        1 => meetands.pop().unwrap(),
+        _ => LGroupTerm::Meet(meetands.into_iter().collect::<BTreeSet<LGroupTerm>>())
+    })
 }
 
-/// Returns whether the input contains `^`, `v`, or `-`
-fn is_atom(s: &String) -> bool {
-    for c in s.chars() {
-        if c == '^' || c == 'v' || c == '-' { return false; }
+fn parse_join(cursor: &mut Cursor) -> Result<LGroupTerm, ParsingError> {
+    let mut joinands = vec![parse_product(cursor)?];
+    while cursor.peek() == Some('v') {
+        cursor.next();
+        joinands.push(parse_product(cursor)?);
     }
-    return true;
+    Ok(match joinands.len() {
+        1 => joinands.pop().unwrap(),
+        _ => LGroupTerm::Join(joinands.into_iter().collect::<BTreeSet<LGroupTerm>>())
+    })
 }
 
-/// Warning: only save to call if it is known not to be an atom
-fn is_inverse(s: &String) -> bool {
-    let mut chars = s.chars();
-    let c = chars.next();
-    if c != Some('-') { return false };
-    let mut depth = 0;
-    for c in chars {
-        match &c {
-            '(' => depth += 1,
-            ')' => depth -= 1,
-            'v' | '^' | '-'  => { if depth == 0 { return false; }},
-            _ => {}
-        };
+fn parse_product(cursor: &mut Cursor) -> Result<LGroupTerm, ParsingError> {
+    let mut factors = parse_factors(cursor)?;
+    while cursor.peek() == Some('-') {
+        cursor.next();
+        let inverted = parse_factors(cursor)?;
+        if inverted.is_empty() {
+            return Err(cursor.unexpected());
+        }
+        factors.push(product(inverted).inverse());
+    }
+    match factors.is_empty() {
+        true => Err(match cursor.peek() {
+            None | Some('^') | Some('v') | Some(')') => {
+                let offset = cursor.offset();
+                cursor.error(ParsingErrorKind::EmptyTerm, offset, offset)
+            },
+            Some(_) => cursor.unexpected()
+        }),
+        false => Ok(product(factors))
     }
-    return true;
 }
 
-/// Warning: only save to call if it is known not to be an atom or an inverse
-fn is_meet(s: &String) -> bool {
-    let mut depth = 0;
-    for c in s.chars() {
-        match &c {
-            '(' => depth += 1,
-            ')' => depth -= 1,
-            _ => {}
-        };
-        if depth == 0 && c == '^' { return true };
+/// Parses factors up to the next character which does not start one, e.g., a `-`.
+fn parse_factors(cursor: &mut Cursor) -> Result<Vec<LGroupTerm>, ParsingError> {
+    let mut factors = Vec::new();
+    while let Some(c) = cursor.peek() {
+        if c != '(' && !is_letter(c) { break; }
+        factors.push(parse_factor(cursor)?);
     }
-    return false;
+    Ok(factors)
 }
 
-/// Warning: only save to call if it is known not to be an atom or an inverse
-fn is_join(s: &String) -> bool {
-    let mut depth = 0;
-    for c in s.chars() {
-        match &c {
-            '(' => depth += 1,
-            ')' => depth -= 1,
-            _ => {}
-        };
-        if depth == 0 && c == 'v' { return true };
+fn product(mut factors: Vec<LGroupTerm>) -> LGroupTerm {
+    match factors.len() {
+        1 => factors.pop().unwrap(),
+        _ => LGroupTerm::Prod(factors)
     }
-    return false;
 }
 
-
-/// returns true if it its outermost brackets are totally left and right,
-/// and are redundant.
-fn has_outermost_brackets(s: &String) -> Result<bool, ParsingError> {
-    let mut depth = 0;
-    let s = s[0 .. s.len() - 1].to_string();
-    for c in s.chars() {
-        match c {
-            '(' => depth += 1,
-            ')' => { 
-                match depth {
-                    0 => return Err(ParsingError::NonMatchingBracketsError(s)),
-                    _ => depth -= 1
-                }
-            },
-            _ => {}
-        };
-        if depth == 0 { return Ok(false); }
+fn parse_factor(cursor: &mut Cursor) -> Result<LGroupTerm, ParsingError> {
+    match cursor.peek() {
+        Some('(') => {
+            let (open, _) = cursor.next().unwrap();
+            let term = parse_meet(cursor)?;
+            match cursor.next() {
+                Some((_, ')')) => Ok(term),
+                _ => Err(cursor.error(ParsingErrorKind::UnclosedBracket, open, open + 1))
+            }
+        },
+        _ => Ok(LGroupTerm::Atom(parse_literals(cursor, is_letter)?))
     }
-    return Ok(true);
 }
 
 #[cfg(test)]
 mod tests {
-    
+    use super::*;
+    use super::super::super::Reducable;
+
+    fn parse(s: &str, options: &ParseOptions) -> Result<LGroupTerm, ParsingError> {
+        super::parse(s, options, &mut SymbolTable::new())
+    }
+
+    fn error_of(s: &str) -> (ParsingErrorKind, usize) {
+        let error = parse(s, &ParseOptions::default()).unwrap_err();
+        (error.kind, error.start)
+    }
+
     #[test]
     fn test_does_not_crash() {
         let string = String::from("(x v (z v (x ^ y)))");
-        assert_eq!(string, super::parse(&string).expect("crashed ...").to_string());
+        assert_eq!(string, parse(&string, &ParseOptions::default()).expect("crashed ...").to_string());
+    }
+
+    #[test]
+    fn test_precedence() {
+        let options = ParseOptions::default();
+        assert_eq!(parse("x ^ (y v z)", &options), parse("x ^ y v z", &options));
+        assert_eq!(parse("-(xy)", &options), parse("-xy", &options));
+    }
+
+    #[test]
+    fn test_inverses_of_products() {
+        // as the parser of earlier versions read them
+        let options = ParseOptions::default();
+        let display = |s| parse(s, &options).unwrap().reduced().to_string();
+        assert_eq!("(Z * (X ^ Y))", display("-(x v y)z"));
+        assert_eq!("YX", display("-x(y)"));
+        assert_eq!("xZYW", display("x-yz-w"));
+        assert_eq!("(XY v z)", display("-yx v z"));
+        assert_eq!((ParsingErrorKind::UnexpectedCharacter('-'), 2), error_of("x--y"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!((ParsingErrorKind::EmptyTerm, 0), error_of(""));
+        assert_eq!((ParsingErrorKind::EmptyTerm, 4), error_of("x v "));
+        assert_eq!((ParsingErrorKind::EmptyTerm, 1), error_of("(^x)"));
+        assert_eq!((ParsingErrorKind::UnclosedBracket, 2), error_of("x (y v z"));
+        assert_eq!((ParsingErrorKind::UnopenedBracket, 1), error_of("x)"));
+        assert_eq!((ParsingErrorKind::UnexpectedCharacter('3'), 3), error_of("(x)3"));
+        assert_eq!((ParsingErrorKind::UnexpectedEnd, 1), error_of("-"));
+    }
+
+    #[test]
+    fn test_strict() {
+        let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+        assert!(parse("x(y ^ z)", &strict).is_ok());
+        let error = parse("x * (y ^ z)", &strict).unwrap_err();
+        assert_eq!((ParsingErrorKind::UnexpectedCharacter('*'), 2), (error.kind, error.start));
+        assert!(parse("x * (y ^ z)", &ParseOptions::default()).is_ok());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,373 @@
+use super::super::free_group_term::FreeGroupTerm;
+use super::super::l_group_term::LGroupTerm;
+use super::super::parsing_error::{ParsingError, ParsingErrorKind};
+use super::super::symbol_table::SymbolTable;
+use super::super::tokenizer::{Token, Tokens};
+use super::super::Term;
+use std::collections::BTreeSet;
+
+/// Parses `input[start .. end]` in the `Standard` dialect, i.e., in the grammar
+/// ```text
+/// join    := meet (JOIN meet)*
+/// meet    := product (MEET product)*
+/// product := unary ('*'? unary)*
+/// unary   := '-' unary | postfix
+/// postfix := primary ('^-1' | '^+' | '^-' | '^' primary | '**' '-'? number)*
+/// primary := '(' join ')' | '|' join '|' | '[' join ',' join ']' | identifier | '1'
+/// ```
+/// where `|` is a join only outside of absolute values, and the derived
+/// operations are expanded as by the corresponding methods of `LGroupTerm`.
+/// Expansions with more than `MAX_EXPANSION_SIZE` atoms and operations are
+/// rejected.
+/// The names of variables are interned in `symbols`.
+/// The offsets in errors refer to all of `input`.
+pub (crate) fn parse_range(
+        input: &str,
+        start: usize,
+        end: usize,
+        symbols: &mut SymbolTable) -> Result<LGroupTerm, ParsingError> {
+    let mut parser = Parser { tokens: Tokens::new(input, start, end)?, symbols, allow_lattice: true, in_bars: false };
+    if parser.tokens.is_at_end() {
+        return Err(parser.tokens.error(ParsingErrorKind::EmptyTerm, start, end));
+    }
+    let term = parser.join()?;
+    parser.finish(term)
+}
+
+/// Parses a product of literals in the `Standard` dialect, i.e., as `parse_range`,
+/// but without meets and joins.
+pub (crate) fn parse_free_group_term(input: &str, symbols: &mut SymbolTable) -> Result<FreeGroupTerm, ParsingError> {
+    let mut parser = Parser { tokens: Tokens::new(input, 0, input.len())?, symbols, allow_lattice: false, in_bars: false };
+    if parser.tokens.is_at_end() {
+        return Err(parser.tokens.error(ParsingErrorKind::EmptyTerm, 0, input.len()));
+    }
+    let term = parser.product()?;
+    Ok(word(parser.finish(term)?))
+}
+
+/// The product of the atoms of a term without meets and joins.
+fn word(term: LGroupTerm) -> FreeGroupTerm {
+    match term {
+        LGroupTerm::Atom(x) => x,
+        LGroupTerm::Prod(xs) => xs.into_iter().fold(FreeGroupTerm::new(Vec::new()), |w, x| w * word(x)),
+        _ => panic!("The term should not contain meets or joins.")
+    }
+}
+
+/// The largest number of atoms and operations of an expanded derived
+/// operation, so that a large exponent or nested commutators give an error
+/// instead of running out of memory, as they copy their arguments.
+const MAX_EXPANSION_SIZE: usize = 1 << 16;
+
+/// The number of atoms and operations of `term`.
+fn size(term: &LGroupTerm) -> usize {
+    match term {
+        LGroupTerm::Atom(x) => x.literals.len().max(1),
+        LGroupTerm::Meet(xs) | LGroupTerm::Join(xs) => 1 + xs.iter().map(size).sum::<usize>(),
+        LGroupTerm::Prod(xs) => 1 + xs.iter().map(size).sum::<usize>()
+    }
+}
+
+/// Whether `token` can start a factor of a product written by juxtaposition.
+/// This excludes `|`, which is a join between factors.
+fn starts_unary(token: Token) -> bool {
+    matches!(token, Token::Inverse | Token::Open | Token::OpenBracket | Token::Identifier | Token::Number)
+}
+
+struct Parser<'a, 's> {
+    tokens:        Tokens<'a>,
+    symbols:       &'s mut SymbolTable,
+    /// Whether meets and joins are allowed.
+    allow_lattice: bool,
+    /// Whether the parser is inside an absolute value, where `|` is not a join.
+    in_bars:       bool
+}
+
+impl<'a, 's> Parser<'a, 's> {
+    fn finish(&self, term: LGroupTerm) -> Result<LGroupTerm, ParsingError> {
+        let offset = self.tokens.offset();
+        match self.tokens.peek() {
+            None => Ok(term),
+            Some(Token::Close) => Err(self.tokens.error(ParsingErrorKind::UnopenedBracket, offset, offset + 1)),
+            Some(_) => Err(self.tokens.unexpected())
+        }
+    }
+
+    fn join(&mut self) -> Result<LGroupTerm, ParsingError> {
+        let mut joinands = vec![self.meet()?];
+        while self.is_join(self.tokens.peek()) {
+            self.tokens.next();
+            joinands.push(self.meet()?);
+        }
+        Ok(match joinands.len() {
+            1 => joinands.pop().unwrap(),
+            _ => LGroupTerm::Join(joinands.into_iter().collect::<BTreeSet<LGroupTerm>>())
+        })
+    }
+
+    fn meet(&mut self) -> Result<LGroupTerm, ParsingError> {
+        let mut meetands = vec![self.product()?];
+        while self.tokens.peek() == Some(Token::Meet) {
+            self.tokens.next();
+            meetands.push(self.product()?);
+        }
+        Ok(match meetands.len() {
+            1 => meetands.pop().unwrap(),
+            _ => LGroupTerm::Meet(meetands.into_iter().collect::<BTreeSet<LGroupTerm>>())
+        })
+    }
+
+    fn product(&mut self) -> Result<LGroupTerm, ParsingError> {
+        let mut factors = vec![self.unary()?];
+        loop {
+            match self.tokens.peek() {
+                Some(Token::Times) => { self.tokens.next(); },
+                Some(token) if starts_unary(token) => {},
+                _ => break
+            };
+            factors.push(self.unary()?);
+        }
+        Ok(match factors.len() {
+            1 => factors.pop().unwrap(),
+            _ => LGroupTerm::Prod(factors)
+        })
+    }
+
+    /// Returns `term`, the expansion of the operation starting at `start`,
+    /// or an error if it has more than `MAX_EXPANSION_SIZE` atoms and operations.
+    fn bounded(&self, term: LGroupTerm, start: usize) -> Result<LGroupTerm, ParsingError> {
+        match size(&term) <= MAX_EXPANSION_SIZE {
+            true  => Ok(term),
+            false => Err(self.tokens.error(ParsingErrorKind::ExpansionTooLarge, start, self.tokens.previous_end()))
+        }
+    }
+
+    fn is_join(&self, token: Option<Token>) -> bool {
+        match token {
+            Some(Token::Join) => true,
+            Some(Token::Bar) => !self.in_bars,
+            _ => false
+        }
+    }
+
+    fn unary(&mut self) -> Result<LGroupTerm, ParsingError> {
+        if self.tokens.peek() == Some(Token::Inverse) {
+            self.tokens.next();
+            return Ok(self.unary()?.inverse());
+        }
+        let start = self.tokens.offset();
+        let mut term = self.primary()?;
+        loop {
+            term = match self.tokens.peek() {
+                Some(Token::PostfixInverse) => term.inverse(),
+                Some(Token::PositivePart) if self.allow_lattice => {
+                    self.tokens.next();
+                    term = self.bounded(term.positive_part(), start)?;
+                    continue;
+                },
+                Some(Token::NegativePart) if self.allow_lattice => {
+                    self.tokens.next();
+                    term = self.bounded(term.negative_part(), start)?;
+                    continue;
+                },
+                Some(Token::Caret) => {
+                    self.tokens.next();
+                    let conjugator = self.primary()?;
+                    term = self.bounded(term.conjugate(&conjugator), start)?;
+                    continue;
+                },
+                Some(Token::Power) => {
+                    self.tokens.next();
+                    let exponent = self.exponent(&term)?;
+                    term = term.power(exponent);
+                    continue;
+                },
+                _ => return Ok(term)
+            };
+            self.tokens.next();
+        }
+    }
+
+    /// Parses the exponent of a power of `base`.
+    fn exponent(&mut self, base: &LGroupTerm) -> Result<i64, ParsingError> {
+        let is_negative = self.tokens.peek() == Some(Token::Inverse);
+        if is_negative {
+            self.tokens.next();
+        }
+        if self.tokens.peek() != Some(Token::Number) {
+            return Err(self.tokens.unexpected());
+        }
+        let (_, start, end) = self.tokens.next().unwrap();
+        let digits = self.tokens.text(start, end);
+        match digits.parse::<i64>() {
+            Ok(n) if (n as u64).saturating_mul(size(base) as u64) <= MAX_EXPANSION_SIZE as u64 => match is_negative {
+                true  => Ok(-n),
+                false => Ok(n)
+            },
+            _ => Err(self.tokens.error(ParsingErrorKind::InvalidNumber(digits.to_string()), start, end))
+        }
+    }
+
+    /// Parses a term enclosed by an opening token at `open` and a `close` token.
+    /// Inside, `|` is a join again, unless `in_bars` is set.
+    fn enclosed(&mut self, open: usize, close: Token, in_bars: bool) -> Result<LGroupTerm, ParsingError> {
+        let was_in_bars = self.in_bars;
+        self.in_bars = in_bars;
+        let term = match self.allow_lattice {
+            true  => self.join(),
+            false => self.product()
+        };
+        self.in_bars = was_in_bars;
+        let term = term?;
+        match self.tokens.peek() {
+            Some(token) if token == close => {
+                self.tokens.next();
+                Ok(term)
+            },
+            None => Err(self.tokens.error(ParsingErrorKind::UnclosedBracket, open, open + 1)),
+            Some(_) => Err(self.tokens.unexpected())
+        }
+    }
+
+    fn primary(&mut self) -> Result<LGroupTerm, ParsingError> {
+        let offset = self.tokens.offset();
+        match self.tokens.peek() {
+            Some(Token::Open) => {
+                self.tokens.next();
+                self.enclosed(offset, Token::Close, false)
+            },
+            Some(Token::Bar) if self.allow_lattice => {
+                self.tokens.next();
+                let term = self.enclosed(offset, Token::Bar, true)?.absolute_value();
+                self.bounded(term, offset)
+            },
+            Some(Token::OpenBracket) => {
+                self.tokens.next();
+                let x = self.enclosed(offset, Token::Comma, false)?;
+                let y = self.enclosed(offset, Token::CloseBracket, false)?;
+                self.bounded(x.commutator(&y), offset)
+            },
+            Some(Token::Identifier) => {
+                let (_, start, end) = self.tokens.next().unwrap();
+                Ok(LGroupTerm::from(self.symbols.intern(self.tokens.text(start, end))))
+            },
+            Some(Token::Number) => {
+                let (_, start, end) = self.tokens.next().unwrap();
+                match self.tokens.text(start, end) {
+                    "1" => Ok(LGroupTerm::Atom(FreeGroupTerm::new(Vec::new()))),
+                    number => Err(self.tokens.error(ParsingErrorKind::UnexpectedToken(number.to_string()), start, end))
+                }
+            },
+            None => Err(self.tokens.error(ParsingErrorKind::UnexpectedEnd, offset, offset)),
+            Some(Token::Join) | Some(Token::Meet) | Some(Token::Close) if self.allow_lattice => {
+                Err(self.tokens.error(ParsingErrorKind::EmptyTerm, offset, offset))
+            },
+            _ => Err(self.tokens.unexpected())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::literal::Literal;
+
+    fn parse(s: &str) -> Result<LGroupTerm, ParsingError> {
+        parse_range(s, 0, s.len(), &mut SymbolTable::new())
+    }
+
+    fn error_of(s: &str) -> (ParsingErrorKind, usize) {
+        let error = parse(s).unwrap_err();
+        (error.kind, error.start)
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(parse("((-x) * y) /\\ z \\/ w"), parse("-x y & z | w"));
+        assert_eq!(parse("x /\\ (y * z)"), parse("x ∧ y z"));
+        assert_eq!(parse("(x /\\ y) \\/ (z /\\ w)"), parse("x & y ∨ z & w"));
+        assert_eq!(parse("-(x y)"), parse("(x y)^-1"));
+        assert_eq!(parse("x (y^-1)"), parse("x y⁻¹"));
+        assert_eq!(parse("(x^y)^-1"), parse("x^y^-1"));
+        assert_eq!(parse("-(x**2)"), parse("-x**2"));
+        assert_eq!(parse("x | (|y| * z)"), parse("x | |y| z"));
+    }
+
+    #[test]
+    fn test_v_and_e_are_variables() {
+        let mut symbols = SymbolTable::new();
+        let term = parse_range("v | e", 0, 5, &mut symbols);
+        let v = LGroupTerm::from(symbols.literal("v").unwrap());
+        let e = LGroupTerm::from(symbols.literal("e").unwrap());
+        let joinands = vec![v, e].into_iter().collect::<BTreeSet<LGroupTerm>>();
+        assert_eq!(Ok(LGroupTerm::Join(joinands)), term);
+        assert_eq!(Ok(LGroupTerm::Atom(FreeGroupTerm::new(Vec::new()))), parse("1"));
+    }
+
+    #[test]
+    fn test_identifiers() {
+        let mut symbols = SymbolTable::new();
+        let term = parse_range("alpha x_left^-1 | xy", 0, 20, &mut symbols).unwrap();
+        assert_eq!(3, symbols.len());
+        assert_eq!(3, term.variables().len());
+        let xy = symbols.literal("xy").unwrap();
+        assert!(term.variables().contains(&xy));
+        assert!(!term.variables().contains(&Literal::from('x')));
+    }
+
+    #[test]
+    fn test_derived_operations() {
+        let x = LGroupTerm::from(Literal::from('x'));
+        let y = LGroupTerm::from(Literal::from('y'));
+        assert_eq!(Ok(x.positive_part()), parse("x⁺"));
+        assert_eq!(Ok(x.negative_part()), parse("x^-"));
+        assert_eq!(Ok(x.absolute_value()), parse("|x|"));
+        assert_eq!(Ok(x.commutator(&y)), parse("[x, y]"));
+        assert_eq!(Ok(x.conjugate(&y)), parse("x^y"));
+        assert_eq!(Ok(x.power(-3)), parse("x**-3"));
+        assert_eq!(Ok(LGroupTerm::Atom(FreeGroupTerm::new(Vec::new()))), parse("x**0"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!((ParsingErrorKind::EmptyTerm, 0), error_of(" "));
+        assert_eq!((ParsingErrorKind::EmptyTerm, 0), error_of("& x"));
+        assert_eq!((ParsingErrorKind::UnclosedBracket, 0), error_of("| x"));
+        assert_eq!((ParsingErrorKind::UnexpectedEnd, 4), error_of("x | "));
+        assert_eq!((ParsingErrorKind::UnclosedBracket, 2), error_of("x (y | z"));
+        assert_eq!((ParsingErrorKind::UnopenedBracket, 1), error_of("x)"));
+        assert_eq!((ParsingErrorKind::EmptyTerm, 3), error_of("x *& y"));
+        assert_eq!((ParsingErrorKind::UnexpectedToken(String::from("^-1")), 0), error_of("^-1"));
+        assert_eq!((ParsingErrorKind::UnexpectedToken(String::from("2")), 2), error_of("x 2"));
+        assert_eq!((ParsingErrorKind::UnexpectedToken(String::from("y")), 3), error_of("x**y"));
+        assert_eq!((ParsingErrorKind::InvalidNumber(String::from("99999999999999")), 3), error_of("x**99999999999999"));
+        assert_eq!((ParsingErrorKind::InvalidNumber(String::from("300")), 14), error_of("(x**300 | y)**300"));
+        assert_eq!((ParsingErrorKind::UnexpectedToken(String::from("]")), 2), error_of("[x]"));
+    }
+
+    #[test]
+    fn test_nested_expansions() {
+        // every level copies the one inside twice
+        let commutators = (0 .. 20).fold(String::from("x"), |term, _| format!("[{}, y]", term));
+        assert_eq!((ParsingErrorKind::ExpansionTooLarge, 5), error_of(&commutators));
+        let absolute_values = (0 .. 20).fold(String::from("x"), |term, _| format!("|{}|", term));
+        assert_eq!((ParsingErrorKind::ExpansionTooLarge, 4), error_of(&absolute_values));
+        let conjugates = (0 .. 20).fold(String::from("x"), |term, _| format!("y^({})", term));
+        assert_eq!(ParsingErrorKind::ExpansionTooLarge, error_of(&conjugates).0);
+        let positive_parts = (0 .. 20).fold(String::from("x"), |term, _| format!("[{}, y]^+", term));
+        assert_eq!(ParsingErrorKind::ExpansionTooLarge, error_of(&positive_parts).0);
+        let commutators = (0 .. 5).fold(String::from("x"), |term, _| format!("[{}, y]", term));
+        assert!(parse(&commutators).is_ok());
+    }
+
+    #[test]
+    fn test_free_group_term() {
+        let mut symbols = SymbolTable::new();
+        let term = parse_free_group_term("x * -(v 1)", &mut symbols);
+        let x = symbols.literal("x").unwrap();
+        let v = symbols.literal("v").unwrap();
+        assert_eq!(Ok(FreeGroupTerm::new(vec![x, v.inverse()])), term);
+        let error = parse_free_group_term("x | v", &mut symbols).unwrap_err();
+        assert_eq!((ParsingErrorKind::UnexpectedToken(String::from("|")), 2), (error.kind, error.start));
+    }
+}
@@ -42,10 +42,7 @@ pub (super) fn meet_reduced(xs: BTreeSet<LGroupTerm>) -> Result<LGroupTerm, Stri
 
 fn contains_meets(xs: &BTreeSet<LGroupTerm>) -> bool {
     for x in xs {
-        match x {
-            LGroupTerm::Meet(_) => { return true; },
-            _ => {}
-        }
+        if let LGroupTerm::Meet(_) = x { return true; }
     }
     false
 }
@@ -83,10 +80,7 @@ pub (super) fn join_reduced(xs: BTreeSet<LGroupTerm>) -> Result<LGroupTerm, Stri
 
 fn contains_joins(xs: &BTreeSet<LGroupTerm>) -> bool {
     for x in xs {
-        match x {
-            LGroupTerm::Join(_) => { return true; },
-            _ => {}
-        }
+        if let LGroupTerm::Join(_) = x { return true; }
     }
     false
 }
@@ -97,7 +91,7 @@ pub (super) fn prod_reduced(xs: Vec<LGroupTerm>) -> Result<LGroupTerm, String> {
     let mut old_factors: Vec<LGroupTerm>;
     let mut not_done = contains_prods(&new_factors);
 
-    if (&new_factors).len() == 0 {
+    if new_factors.is_empty() {
         return Ok(IDENTITY);
     }
 
@@ -128,9 +122,9 @@ pub (super) fn prod_reduced(xs: Vec<LGroupTerm>) -> Result<LGroupTerm, String> {
     }
 
     match new_factors.len() {
-        0 => Ok(LGroupTerm::from(IDENTITY.clone())),
+        0 => Ok(IDENTITY.clone()),
         1 => {
-            let option = new_factors.iter().next();
+            let option = new_factors.first();
             match option {
                 None => Err(String::from("I have no words")),
                 Some(x) => Ok(x.clone())
@@ -141,18 +135,12 @@ pub (super) fn prod_reduced(xs: Vec<LGroupTerm>) -> Result<LGroupTerm, String> {
 }
 
 fn is_atom(x: &LGroupTerm) -> bool {
-    match x {
-        LGroupTerm::Atom(_) => true,
-        _ => false
-    }
+    matches!(x, LGroupTerm::Atom(_))
 }
 
 fn contains_prods(xs: &Vec<LGroupTerm>) -> bool {
     for x in xs {
-        match x {
-            LGroupTerm::Prod(_) => { return true; },
-            _ => {}
-        }
+        if let LGroupTerm::Prod(_) = x { return true; }
     }
     false
 }
@@ -39,5 +39,15 @@ mod l_group_term_reducing;
 pub mod short_free_group_term;
 
 
+pub mod parsing;
+
+/// The module containing the struct `SymbolTable`, which interns
+/// variable names as literals.
+pub mod symbol_table;
+mod tokenizer;
 pub mod parsing_error;
-pub mod formula;
\ No newline at end of file
+pub mod formula;
+
+/// The module containing the struct `MVFormula`, which translates
+/// formulas of MV-algebras into formulas of l-groups.
+pub mod mv_formula;
\ No newline at end of file
@@ -1,5 +1,7 @@
 use super::Term;
-use super::parsing_error::ParsingError;
+use super::parsing::{Cursor, ParseOptions};
+use super::symbol_table::SymbolTable;
+use super::parsing_error::{ParsingError, ParsingErrorKind};
 use super::free_group_term::FreeGroupTerm;
 use std::ops::Mul;
 
@@ -66,20 +68,18 @@ impl Term for Literal {
     }
 }
 
-impl ToString for Literal {
-    fn to_string(&self) -> String {
-        let mut result = String::new();
+impl std::fmt::Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self.is_inverted {
-            false => result.push(self.character),
+            false => write!(f, "{}", self.character)?,
             true => {
-                let upper = self.character.to_uppercase();
-                for c in upper { result.push(c); }
+                for c in self.character.to_uppercase() { write!(f, "{}", c)?; }
             }
         }
         if self.id != 0 {
-            result.push_str(&self.id.to_string());
+            write!(f, "{}", self.id)?;
         }
-        return result;
+        Ok(())
     }
 }
 
@@ -90,53 +90,79 @@ impl Mul for Literal {
     }
 }
 
-impl From<&str> for Literal {
-    fn from(s: &str) -> Literal {
-        let result = parse(s);
-        match result {
-            Ok(literal) => literal,
-            Err(e) => panic!(e)
+impl Literal {
+    /// Returns the name of the literal in `symbols`, where inverses are
+    /// written as a postfix `^-1`. This is the syntax of the `Standard` dialect.
+    pub fn to_string_with(&self, symbols: &SymbolTable) -> String {
+        let name = symbols.name_of_variable(self);
+        match self.is_inverted {
+            false => name,
+            true  => format!("{}^-1", name)
         }
     }
-}
 
+    /// Parses a literal, returning an error instead of panicking on invalid input.
+    /// ```
+    /// use l_group_formulas::literal::Literal;
+    /// use l_group_formulas::parsing::ParseOptions;
+    /// assert_eq!(Ok(Literal::new('x', 31, true)), Literal::parse_with("X31", &ParseOptions::default()));
+    /// assert!(Literal::parse_with("31", &ParseOptions::default()).is_err());
+    /// ```
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<Literal, ParsingError> {
+        let mut cursor = Cursor::new(s, 0, s.len(), char::is_alphanumeric, options)?;
+        let literal = parse(&mut cursor, |c| c.is_alphabetic())?;
+        match cursor.is_at_end() {
+            true  => Ok(literal),
+            false => Err(cursor.unexpected())
+        }
+    }
+}
 
-fn parse(s: &str) -> Result<Literal, ParsingError> {
-    let l = s.len();
-    if l == 0 {
-        return Err(ParsingError::EmptyLiteralError);
-    } else {
-        let is_inverted: bool;
-        let mut lower_case: std::char::ToLowercase;
-        match s.chars().next() {
-            None => return Err(ParsingError::EmptyLiteralError),
-            Some(c) => { 
-                is_inverted = c.is_uppercase(); 
-                lower_case = c.to_lowercase(); 
-            }
-        };
-        let character: char;
-        match lower_case.next() {
-            None => return Err(ParsingError::EmptyLiteralError),
-            Some(c) => character = c
-        };
-        if l == 1 { return Ok(Literal::new(character, 0, is_inverted)); }
+impl std::str::FromStr for Literal {
+    type Err = ParsingError;
+    fn from_str(s: &str) -> Result<Literal, ParsingError> {
+        Literal::parse_with(s, &ParseOptions::default())
+    }
+}
 
-        let result = without_first(s).parse::<usize>();
-        match result {
-            Ok(id) => Ok(Literal::new(character, id, is_inverted)),
-            Err(e) => Err(ParsingError::InvalidLiteralError(e.to_string()))
+impl From<&str> for Literal {
+    /// Panics on invalid input. Use `str::parse` to handle errors instead.
+    fn from(s: &str) -> Literal {
+        match s.parse() {
+            Ok(literal) => literal,
+            Err(e) => panic!("{}", e.render())
         }
     }
 }
 
-fn without_first(string: &str) -> String {
-    let mut result = String::new();
-    let mut iterator = string.chars();
-    iterator.next();
-    for c in iterator { result.push(c); }
+/// Parses a letter satisfying `is_letter`, followed by an optional id.
+/// Upper case letters denote inverted literals.
+pub (crate) fn parse(cursor: &mut Cursor, is_letter: fn(char) -> bool) -> Result<Literal, ParsingError> {
+    let (start, c) = match cursor.peek() {
+        Some(c) if is_letter(c) => cursor.next().unwrap(),
+        other => {
+            let start = cursor.offset();
+            let end = other.map_or(start, |c| start + c.len_utf8());
+            return Err(cursor.error(ParsingErrorKind::ExpectedLiteral, start, end));
+        }
+    };
+    let is_inverted = c.is_uppercase();
+    let character = c.to_lowercase().next().unwrap_or(c);
 
-    return result;
+    let mut id = String::new();
+    let mut end = start + c.len_utf8();
+    while let Some(d) = cursor.peek() {
+        if !d.is_ascii_digit() { break; }
+        id.push(d);
+        end = cursor.next().unwrap().0 + 1;
+    }
+    if id.is_empty() {
+        return Ok(Literal::new(character, 0, is_inverted));
+    }
+    match id.parse::<usize>() {
+        Ok(id) => Ok(Literal::new(character, id, is_inverted)),
+        Err(_) => Err(cursor.error(ParsingErrorKind::InvalidId(id), start, end))
+    }
 }
 
 
@@ -0,0 +1,315 @@
+use super::formula::LGroupFormula;
+use super::l_group_term::{LGroupTerm, IDENTITY};
+use super::literal::Literal;
+use super::parsing_error::{ParsingError, ParsingErrorKind};
+use super::symbol_table::SymbolTable;
+use super::tokenizer::{Token, Tokens};
+use super::{Reducable, Term};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An equation or inequation between MV-terms, translated into an `LGroupFormula`
+/// about unital abelian l-groups by Mundici's Γ functor.
+///
+/// MV-terms are built from variables, `0`, `1`, `⊕` (or `+`), `⊙` (or `*`),
+/// `¬` (or `~`), and `→` (or `->`), where `x → y` is `¬x ⊕ y`. Negations bind
+/// strongest, followed by `⊙`, `⊕`, and finally `→`, which associates to the
+/// right. A formula is `s = t`, `s <= t`, `s >= t`, or a single term `t`, which
+/// stands for the tautology `t = 1`. Variables are identifiers as in the
+/// `Standard` dialect.
+///
+/// In the translation `bounded`, the unit is a new variable `u`, which is read
+/// under the hypotheses `e <= x <= u` for all variables `x`. In `formula`, the
+/// unit is `|u|` instead, and every variable `x` is replaced by `(x ∨ e) ∧ |u|`,
+/// so the MV-formula holds in all MV-algebras if, and only if, `formula` holds
+/// in all abelian l-groups. With `z` the unit, the operations are
+/// - `x ⊕ y = xy ∧ z`,
+/// - `x ⊙ y = xyz⁻¹ ∨ e`,
+/// - `¬x = zx⁻¹`,
+/// - `0 = e`, and `1 = z`.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::mv_formula::MVFormula;
+/// use l_group_formulas::l_group_term::LGroupTerm;
+/// use l_group_formulas::literal::Literal;
+/// let formula: MVFormula = "x ⊕ ¬x".parse().unwrap();
+/// assert_eq!(Literal::from('u'), formula.unit);
+/// assert_eq!(LGroupTerm::from("(x v e) ^ (u v U)"), formula.variables[&Literal::from('x')]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MVFormula {
+    pub formula:   LGroupFormula,
+    /// The translation with the variables and `u` standing for themselves.
+    pub bounded:   LGroupFormula,
+    /// The variable `u`, whose absolute value is the unit.
+    pub unit:      Literal,
+    /// The variables of the MV-terms, with their translations into the unit interval.
+    pub variables: BTreeMap<Literal, LGroupTerm>
+}
+
+impl MVFormula {
+    /// Parses an MV-formula, interning the names of its variables in `symbols`.
+    /// The unit gets the first of the names `u`, `u_1`, `u_2`, ..., which
+    /// is not in `symbols` after parsing.
+    pub fn parse_with_symbols(s: &str, symbols: &mut SymbolTable) -> Result<MVFormula, ParsingError> {
+        // the relations, with their byte ranges
+        let mut relations = Vec::new();
+        let mut characters = s.char_indices().peekable();
+        while let Some((i, c)) = characters.next() {
+            match c {
+                '<' | '>' if characters.peek().map(|(_, d)| *d) == Some('=') => {
+                    characters.next();
+                    relations.push((c, i, i + 2));
+                },
+                '=' => relations.push((c, i, i + 1)),
+                _ => {}
+            };
+        }
+        let mut variables = BTreeSet::new();
+        let (relation, lhs, rhs) = match relations.as_slice() {
+            [] => ('=', parse_range(s, 0, s.len(), symbols, &mut variables)?, MVTerm::One),
+            [(relation, start, end)] => (
+                *relation,
+                parse_range(s, 0, *start, symbols, &mut variables)?,
+                parse_range(s, *end, s.len(), symbols, &mut variables)?
+            ),
+            [_, (_, start, end), ..] =>
+                return Err(ParsingError::new(ParsingErrorKind::MultipleRelations, *start, *end, s))
+        };
+
+        let name = (0 ..)
+            .map(|k| match k {
+                0 => "u".to_string(),
+                k => format!("u_{}", k)
+            })
+            .find(|name| symbols.literal(name).is_none())
+            .expect("There are infinitely many names.");
+        let unit = symbols.intern(&name);
+        let translation = Translation { unit: LGroupTerm::from(unit) };
+        let (lhs, rhs) = (translation.translate(&lhs), translation.translate(&rhs));
+        let bounded = match relation {
+            '<' => LGroupFormula::LGroupInequation(lhs, rhs),
+            '>' => LGroupFormula::LGroupInequation(rhs, lhs),
+            _   => LGroupFormula::LGroupEquation(lhs, rhs)
+        };
+
+        let one = LGroupTerm::from(unit).absolute_value();
+        let variables: BTreeMap<_, _> = variables.iter()
+            .map(|x| (*x, meet(LGroupTerm::from(*x).positive_part(), one.clone())))
+            .collect();
+        let mut definitions = variables.clone();
+        definitions.insert(unit, one);
+        let formula = match &bounded {
+            LGroupFormula::LGroupInequation(lhs, rhs) =>
+                LGroupFormula::LGroupInequation(lhs.substituted(&definitions), rhs.substituted(&definitions)),
+            LGroupFormula::LGroupEquation(lhs, rhs) =>
+                LGroupFormula::LGroupEquation(lhs.substituted(&definitions), rhs.substituted(&definitions))
+        };
+        Ok(MVFormula { formula, bounded, unit, variables })
+    }
+}
+
+impl std::str::FromStr for MVFormula {
+    type Err = ParsingError;
+    fn from_str(s: &str) -> Result<MVFormula, ParsingError> {
+        MVFormula::parse_with_symbols(s, &mut SymbolTable::new())
+    }
+}
+
+/// The syntax tree of an MV-term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MVTerm {
+    Variable(Literal),
+    Zero,
+    One,
+    Oplus(Box<MVTerm>, Box<MVTerm>),
+    Odot(Box<MVTerm>, Box<MVTerm>),
+    Not(Box<MVTerm>)
+}
+
+struct Translation {
+    unit: LGroupTerm
+}
+
+impl Translation {
+    fn translate(&self, term: &MVTerm) -> LGroupTerm {
+        match term {
+            MVTerm::Variable(x) => LGroupTerm::from(*x),
+            MVTerm::Zero => IDENTITY,
+            MVTerm::One => self.unit.clone(),
+            MVTerm::Oplus(x, y) => meet(LGroupTerm::Prod(vec![self.translate(x), self.translate(y)]).reduced(), self.unit.clone()),
+            MVTerm::Odot(x, y) => {
+                let product = LGroupTerm::Prod(vec![self.translate(x), self.translate(y), self.unit.inverse()]).reduced();
+                product.positive_part()
+            },
+            MVTerm::Not(x) => LGroupTerm::Prod(vec![self.unit.clone(), self.translate(x).inverse()]).reduced()
+        }
+    }
+}
+
+fn meet(x: LGroupTerm, y: LGroupTerm) -> LGroupTerm {
+    LGroupTerm::Meet(vec![x, y].into_iter().collect()).reduced()
+}
+
+/// Parses `input[start .. end]` as an MV-term, in the grammar
+/// ```text
+/// implication := sum ('->' implication)?
+/// sum         := product ('+' product)*
+/// product     := unary ('*' unary)*
+/// unary       := '~' unary | primary
+/// primary     := '(' implication ')' | identifier | '0' | '1'
+/// ```
+/// adding the variables to `variables`.
+fn parse_range(
+        input: &str,
+        start: usize,
+        end: usize,
+        symbols: &mut SymbolTable,
+        variables: &mut BTreeSet<Literal>) -> Result<MVTerm, ParsingError> {
+    let mut parser = Parser { tokens: Tokens::new(input, start, end)?, symbols, variables };
+    if parser.tokens.is_at_end() {
+        return Err(parser.tokens.error(ParsingErrorKind::EmptyTerm, start, end));
+    }
+    let term = parser.implication()?;
+    let offset = parser.tokens.offset();
+    match parser.tokens.peek() {
+        None => Ok(term),
+        Some(Token::Close) => Err(parser.tokens.error(ParsingErrorKind::UnopenedBracket, offset, offset + 1)),
+        Some(_) => Err(parser.tokens.unexpected())
+    }
+}
+
+struct Parser<'a, 's> {
+    tokens:    Tokens<'a>,
+    symbols:   &'s mut SymbolTable,
+    variables: &'s mut BTreeSet<Literal>
+}
+
+impl<'a, 's> Parser<'a, 's> {
+    fn implication(&mut self) -> Result<MVTerm, ParsingError> {
+        let premise = self.sum()?;
+        if self.tokens.peek() != Some(Token::Implies) {
+            return Ok(premise);
+        }
+        self.tokens.next();
+        let conclusion = self.implication()?;
+        Ok(MVTerm::Oplus(Box::new(MVTerm::Not(Box::new(premise))), Box::new(conclusion)))
+    }
+
+    fn sum(&mut self) -> Result<MVTerm, ParsingError> {
+        let mut sum = self.product()?;
+        while self.tokens.peek() == Some(Token::Oplus) {
+            self.tokens.next();
+            sum = MVTerm::Oplus(Box::new(sum), Box::new(self.product()?));
+        }
+        Ok(sum)
+    }
+
+    fn product(&mut self) -> Result<MVTerm, ParsingError> {
+        let mut product = self.unary()?;
+        while matches!(self.tokens.peek(), Some(Token::Odot) | Some(Token::Times)) {
+            self.tokens.next();
+            product = MVTerm::Odot(Box::new(product), Box::new(self.unary()?));
+        }
+        Ok(product)
+    }
+
+    fn unary(&mut self) -> Result<MVTerm, ParsingError> {
+        if self.tokens.peek() == Some(Token::Not) {
+            self.tokens.next();
+            return Ok(MVTerm::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<MVTerm, ParsingError> {
+        let offset = self.tokens.offset();
+        match self.tokens.peek() {
+            Some(Token::Open) => {
+                self.tokens.next();
+                let term = self.implication()?;
+                match self.tokens.peek() {
+                    Some(Token::Close) => {
+                        self.tokens.next();
+                        Ok(term)
+                    },
+                    None => Err(self.tokens.error(ParsingErrorKind::UnclosedBracket, offset, offset + 1)),
+                    Some(_) => Err(self.tokens.unexpected())
+                }
+            },
+            Some(Token::Identifier) => {
+                let (_, start, end) = self.tokens.next().unwrap();
+                let x = self.symbols.intern(self.tokens.text(start, end));
+                self.variables.insert(x);
+                Ok(MVTerm::Variable(x))
+            },
+            Some(Token::Number) => {
+                let (_, start, end) = self.tokens.next().unwrap();
+                match self.tokens.text(start, end) {
+                    "0" => Ok(MVTerm::Zero),
+                    "1" => Ok(MVTerm::One),
+                    number => Err(self.tokens.error(ParsingErrorKind::UnexpectedToken(number.to_string()), start, end))
+                }
+            },
+            None => Err(self.tokens.error(ParsingErrorKind::UnexpectedEnd, offset, offset)),
+            _ => Err(self.tokens.unexpected())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_term(s: &str) -> Result<MVTerm, ParsingError> {
+        parse_range(s, 0, s.len(), &mut SymbolTable::new(), &mut BTreeSet::new())
+    }
+
+    fn x() -> Box<MVTerm> {
+        Box::new(MVTerm::Variable(Literal::from('x')))
+    }
+
+    fn y() -> Box<MVTerm> {
+        Box::new(MVTerm::Variable(Literal::from('y')))
+    }
+
+    #[test]
+    fn test_precedence() {
+        let expected = MVTerm::Oplus(Box::new(MVTerm::Not(x())), Box::new(MVTerm::Odot(x(), y())));
+        assert_eq!(Ok(expected.clone()), parse_term("¬x ⊕ x ⊙ y"));
+        assert_eq!(Ok(expected.clone()), parse_term("~x + x * y"));
+        assert_eq!(Ok(expected), parse_term("x -> x ⊙ y"));
+        let expected = MVTerm::Oplus(Box::new(MVTerm::Not(x())), Box::new(MVTerm::Oplus(Box::new(MVTerm::Not(y())), x())));
+        assert_eq!(Ok(expected), parse_term("x → y → x"));
+        assert_eq!(Ok(MVTerm::Not(Box::new(MVTerm::Oplus(Box::new(MVTerm::Zero), Box::new(MVTerm::One))))), parse_term("¬(0 ⊕ 1)"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = parse_term("x ⊕ 2").unwrap_err();
+        assert_eq!((ParsingErrorKind::UnexpectedToken("2".to_string()), 6), (error.kind, error.start));
+        let error = parse_term("(x ⊕ y").unwrap_err();
+        assert_eq!((ParsingErrorKind::UnclosedBracket, 0), (error.kind, error.start));
+        let error = parse_term("x y").unwrap_err();
+        assert_eq!((ParsingErrorKind::UnexpectedToken("y".to_string()), 2), (error.kind, error.start));
+        let error = "x = y = z".parse::<MVFormula>().unwrap_err();
+        assert_eq!(ParsingErrorKind::MultipleRelations, error.kind);
+    }
+
+    #[test]
+    fn test_unit_avoids_variables() {
+        let mut symbols = SymbolTable::new();
+        let formula = MVFormula::parse_with_symbols("u ⊙ u_1 <= u", &mut symbols).unwrap();
+        assert_eq!(Some("u_2"), symbols.name(&formula.unit));
+        assert_eq!(2, formula.variables.len());
+        let formula: MVFormula = "x >= 0".parse().unwrap();
+        assert_eq!(LGroupFormula::LGroupInequation(IDENTITY, formula.variables[&Literal::from('x')].clone()), formula.formula);
+    }
+
+    #[test]
+    fn test_e_is_a_variable() {
+        let formula: MVFormula = "e ⊕ e = e".parse().unwrap();
+        assert_eq!(LGroupTerm::from("(e1 v e) ^ (u v U)"), formula.variables[&Literal::new('e', 1, false)]);
+    }
+}
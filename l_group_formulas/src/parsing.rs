@@ -0,0 +1,127 @@
+use super::parsing_error::{ParsingError, ParsingErrorKind};
+
+/// The syntax of terms and formulas.
+///
+/// In the `Legacy` dialect, `v` always denotes a join, `^` a meet, and `e` the
+/// identity, meets bind weaker than joins, and a prefix `-` inverts the rest
+/// of the product following it, up to the next `v`, `^`, or `-`, e.g., `-xy`
+/// is the inverse of `xy`, and `-(x v y)z` of `(x v y)z`. This is the syntax
+/// `LGroupTerm::from(&str)` and `Display` use.
+///
+/// In the `Standard` dialect, variables are identifiers, i.e., a letter or `_`,
+/// followed by letters, digits, and `_`. They are interned in a `SymbolTable`,
+/// so `v`, `e`, `alpha`, and `X` are all variables, and `xy` is a single
+/// variable, not a product. The identity is `1`, and the operators are,
+/// from binding strongest to binding weakest:
+/// - postfix operators, which are inverses `x^-1` or `x⁻¹`, positive parts
+///   `x^+` or `x⁺`, negative parts `x^-` or `x⁻`, conjugates `x^y`, and
+///   powers `x**3` or `x**-2`,
+/// - inverses, written as a prefix `-`,
+/// - products, written by juxtaposition or with an explicit `*`,
+/// - meets, written `/\`, `&`, or `∧`,
+/// - joins, written `\/`, `|`, or `∨`.
+///
+/// Additionally, `|x|` is the absolute value, and `[x, y]` the commutator.
+/// Inside an absolute value, joins have to be written `\/` or `∨`, and an
+/// absolute value following another factor needs an explicit `*`.
+///
+/// For example, `-x y & z | v` is the join of `(x^-1 * y) /\ z` and `v`.
+/// Characters without a meaning are always an error in `Standard`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    Legacy,
+    Standard
+}
+
+/// Options for the fallible parsers, e.g., `LGroupTerm::parse_with`.
+///
+/// By default, the `Legacy` dialect is used, and characters which have no
+/// meaning in its grammar, such as `*`, are silently dropped. In `strict`
+/// mode, they are rejected instead. Whitespace is ignored in both modes.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::l_group_term::LGroupTerm;
+/// use l_group_formulas::literal::Literal;
+/// use l_group_formulas::parsing::{ParseOptions, Dialect};
+/// let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+/// assert!(LGroupTerm::parse_with("x*y", &ParseOptions::default()).is_ok());
+/// assert!(LGroupTerm::parse_with("x*y", &strict).is_err());
+///
+/// let standard = ParseOptions { dialect: Dialect::Standard, ..ParseOptions::default() };
+/// let term = LGroupTerm::parse_with("x /\\ (v \\/ e)", &standard).unwrap();
+/// assert_eq!(3, term.variables().len());
+/// assert!(!term.variables().contains(&Literal::from('v')));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    pub strict:  bool,
+    pub dialect: Dialect
+}
+
+/// The characters of a part of the input which are meaningful to a parser,
+/// together with their byte offsets.
+pub (crate) struct Cursor<'a> {
+    input:    &'a str,
+    chars:    Vec<(usize, char)>,
+    position: usize,
+    end:      usize
+}
+
+impl<'a> Cursor<'a> {
+    /// Collects the characters of `input[start .. end]` satisfying `is_meaningful`.
+    /// Whitespace is skipped, and so are all other characters, unless `options`
+    /// is strict, in which case they are an error.
+    pub (crate) fn new(
+            input: &'a str,
+            start: usize,
+            end: usize,
+            is_meaningful: fn(char) -> bool,
+            options: &ParseOptions) -> Result<Cursor<'a>, ParsingError> {
+        let mut chars = Vec::new();
+        for (i, c) in input[start .. end].char_indices() {
+            if is_meaningful(c) {
+                chars.push((start + i, c));
+            } else if options.strict && !c.is_whitespace() {
+                return Err(ParsingError::new(
+                    ParsingErrorKind::UnexpectedCharacter(c), start + i, start + i + c.len_utf8(), input));
+            }
+        }
+        Ok(Cursor { input, chars, position: 0, end })
+    }
+
+    pub (crate) fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).map(|(_, c)| *c)
+    }
+
+    pub (crate) fn next(&mut self) -> Option<(usize, char)> {
+        let next = self.chars.get(self.position).copied();
+        if next.is_some() {
+            self.position += 1;
+        }
+        next
+    }
+
+    /// The byte offset of the next character, or of the end of the input.
+    pub (crate) fn offset(&self) -> usize {
+        self.chars.get(self.position).map_or(self.end, |(i, _)| *i)
+    }
+
+    pub (crate) fn is_at_end(&self) -> bool {
+        self.position == self.chars.len()
+    }
+
+    pub (crate) fn error(&self, kind: ParsingErrorKind, start: usize, end: usize) -> ParsingError {
+        ParsingError::new(kind, start, end, self.input)
+    }
+
+    /// An error about the next character, or about the input ending.
+    pub (crate) fn unexpected(&self) -> ParsingError {
+        match self.chars.get(self.position) {
+            Some((i, c)) => self.error(ParsingErrorKind::UnexpectedCharacter(*c), *i, i + c.len_utf8()),
+            None => self.error(ParsingErrorKind::UnexpectedEnd, self.end, self.end)
+        }
+    }
+}
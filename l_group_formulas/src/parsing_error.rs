@@ -1,42 +1,110 @@
-
-/// An error that arises by parsing literals from strings.
-#[derive(Debug, PartialEq, Eq)]
-pub enum ParsingError {
-    EmptyLiteralError,
-    EmptyFreeGroupTermError,
-    NoLowerCaseError(char),
-    InvalidLiteralError(String),
-    NonMatchingBracketsError(String),
-    ParsingAtomError(String),
-    ParsingInverseError(String, String),
-    ParsingMeetError(String, String),
-    ParsingJoinError(String, String),
-    ParsingProductError(String, String, String)
+/// What went wrong while parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsingErrorKind {
+    /// The input, or a part of it that should be a term, is empty.
+    EmptyTerm,
+    /// A literal was expected, e.g., after an inverse or at the start of an atom.
+    ExpectedLiteral,
+    /// A character that is not allowed at this position.
+    UnexpectedCharacter(char),
+    /// A token of the `Standard` dialect, which is not allowed at this position.
+    UnexpectedToken(String),
+    /// The input ended while more was expected.
+    UnexpectedEnd,
+    /// An opening bracket without a closing one.
+    UnclosedBracket,
+    /// A closing bracket without an opening one.
+    UnopenedBracket,
+    /// The id of a literal, which does not fit into a `usize`.
+    InvalidId(String),
+    /// An exponent which does not fit into an `i64`, or gives a power too
+    /// large to expand.
+    InvalidNumber(String),
+    /// A derived operation, such as a commutator, whose expansion is too large.
+    ExpansionTooLarge,
+    /// A `let` without definitions, or a definition without `=`.
+    ExpectedDefinition,
+    /// A defined name, which is not a variable.
+    InvalidName(String),
+    /// A name which is defined twice.
+    DuplicateDefinition(String),
+    /// A name which is used in its own definition.
+    RecursiveDefinition(String),
+    /// A name which is used before its definition.
+    UnboundName(String),
+    /// A formula without `=` or `<=`.
+    MissingRelation,
+    /// A formula with more than one `=` or `<=`.
+    MultipleRelations
 }
 
-impl std::fmt::Display for ParsingError {
+impl std::fmt::Display for ParsingErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            ParsingError::EmptyLiteralError => 
-                write!(f, "Empty literal could not be parsed."),
-            ParsingError::EmptyFreeGroupTermError => 
-                write!(f, "Empty free group term could not be parsed."),
-            ParsingError::NoLowerCaseError(c) => 
-                write!(f, "{}", format!("The character {} does not have a lower case.", c.to_string())),
-            ParsingError::InvalidLiteralError(s) =>
-                write!(f, "{}", format!("The literal {} could not be parsed.", s)),
-            ParsingError::NonMatchingBracketsError(s) =>
-                write!(f, "{}", format!("The brackets in {} did not match.", s)),
-            ParsingError::ParsingAtomError(s) =>
-                write!(f, "{}", format!("parsing this atom failed: {}", s)),
-            ParsingError::ParsingInverseError(s, e_string) =>
-                write!(f, "{}", format!("parsing this inverse failed: {}. That is, {}.", s, e_string)),
-            ParsingError::ParsingMeetError(s, e_string) =>
-                write!(f, "{}", format!("parsing this meet failed: {}. That is, {}.", s, e_string)),
-            ParsingError::ParsingJoinError(s, e_string) =>
-                write!(f, "{}", format!("parsing this join failed: {}. That is, {}.", s, e_string)),
-            ParsingError::ParsingProductError(s, t, e_string) =>
-                write!(f, "{}", format!("parsing the product {} at {} failed. That is, {}.", s, t, e_string))
+            ParsingErrorKind::EmptyTerm => write!(f, "expected a term"),
+            ParsingErrorKind::ExpectedLiteral => write!(f, "expected a literal"),
+            ParsingErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character '{}'", c),
+            ParsingErrorKind::UnexpectedToken(s) => write!(f, "unexpected '{}'", s),
+            ParsingErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParsingErrorKind::UnclosedBracket => write!(f, "this bracket is never closed"),
+            ParsingErrorKind::UnopenedBracket => write!(f, "this bracket is never opened"),
+            ParsingErrorKind::InvalidId(id) => write!(f, "the id {} is too large", id),
+            ParsingErrorKind::InvalidNumber(n) => write!(f, "the number {} is too large", n),
+            ParsingErrorKind::ExpansionTooLarge => write!(f, "this expands to a term which is too large"),
+            ParsingErrorKind::ExpectedDefinition => write!(f, "expected a definition 'name = term;'"),
+            ParsingErrorKind::InvalidName(name) => write!(f, "{} cannot be defined", name),
+            ParsingErrorKind::DuplicateDefinition(name) => write!(f, "{} is defined twice", name),
+            ParsingErrorKind::RecursiveDefinition(name) => write!(f, "{} is used in its own definition", name),
+            ParsingErrorKind::UnboundName(name) => write!(f, "{} is used before it is defined", name),
+            ParsingErrorKind::MissingRelation => write!(f, "expected '=' or '<='"),
+            ParsingErrorKind::MultipleRelations => write!(f, "a formula has exactly one '=' or '<='")
         }
     }
 }
+
+/// An error that arises by parsing literals, terms, or formulas from strings.
+///
+/// `start` and `end` are byte offsets into `input`, delimiting the offending part.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::l_group_term::LGroupTerm;
+/// use l_group_formulas::parsing_error::{ParsingError, ParsingErrorKind};
+/// let error = "x v (y ^ z".parse::<LGroupTerm>().unwrap_err();
+/// assert_eq!(ParsingErrorKind::UnclosedBracket, error.kind);
+/// assert_eq!(4, error.start);
+/// assert_eq!("this bracket is never closed\nx v (y ^ z\n    ^", error.render());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsingError {
+    pub kind:  ParsingErrorKind,
+    pub start: usize,
+    pub end:   usize,
+    pub input: String
+}
+
+impl ParsingError {
+    pub fn new(kind: ParsingErrorKind, start: usize, end: usize, input: &str) -> ParsingError {
+        ParsingError { kind, start, end, input: input.to_string() }
+    }
+
+    /// Returns the message, followed by the line of the input containing
+    /// the error, and carets below the offending part.
+    pub fn render(&self) -> String {
+        let line_start = self.input[.. self.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.input[self.start ..].find('\n').map_or(self.input.len(), |i| self.start + i);
+        let line = &self.input[line_start .. line_end];
+        let column = self.input[line_start .. self.start].chars().count();
+        let width = self.input[self.start .. self.end.min(line_end).max(self.start)].chars().count().max(1);
+        format!("{}\n{}\n{}{}", self.kind, line, " ".repeat(column), "^".repeat(width))
+    }
+}
+
+impl std::fmt::Display for ParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.start)
+    }
+}
+
+impl std::error::Error for ParsingError {}
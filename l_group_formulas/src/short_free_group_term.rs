@@ -17,9 +17,9 @@ impl ShortFreeGroupTerm {
         mid: Option<Literal>, 
         right: Option<Literal>) -> ShortFreeGroupTerm {
         ShortFreeGroupTerm {
-            left: left,
-            mid: mid,
-            right: right
+            left,
+            mid,
+            right
         }
     }
 }
@@ -55,19 +55,15 @@ impl From<Literal> for ShortFreeGroupTerm {
     }
 }
 
-impl ToString for ShortFreeGroupTerm {
-    fn to_string(&self) -> String {
-        let mut string = String::new();
-        for option in &[self.left, self.mid, self.right] {
-            match option {
-                Some(literal) => string.push_str(literal.to_string().as_str()),
-                None => {}
-            };
+impl std::fmt::Display for ShortFreeGroupTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.left.is_none() {
+            return write!(f, "e");
         }
-        if string.len() == 0 {
-            string.push('e');
+        for literal in [self.left, self.mid, self.right].iter().flatten() {
+            write!(f, "{}", literal)?;
         }
-        return string;
+        Ok(())
     }
 }
 
@@ -109,6 +105,10 @@ impl Term for ShortFreeGroupTerm {
 
 pub trait Len {
     fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 impl Len for ShortFreeGroupTerm {
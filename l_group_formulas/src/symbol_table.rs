@@ -0,0 +1,171 @@
+use super::literal::Literal;
+use std::collections::BTreeMap;
+
+/// Interns variable names as `Literal`s, and maps them back for printing.
+///
+/// A name which looks like the string of a non-inverted literal, such as
+/// `x` or `y31`, is interned as that literal if it is still free. Every other
+/// name, such as `alpha`, `x_left`, or `X`, gets a fresh literal, whose
+/// character is the lower case of the first character of the name.
+/// The literals `e` and `v`, `v1`, ... are reserved, as `Display` prints
+/// them as the identity and joins, so names such as `e` or `v` get fresh
+/// literals as well, which start with `w` instead of `v`.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::literal::Literal;
+/// use l_group_formulas::symbol_table::SymbolTable;
+/// use l_group_formulas::Term;
+/// let mut symbols = SymbolTable::new();
+/// assert_eq!(Literal::new('y', 31, false), symbols.intern("y31"));
+/// let alpha = symbols.intern("alpha");
+/// assert_ne!(Literal::from('a'), alpha);
+/// assert_eq!(alpha, symbols.intern("alpha"));
+/// assert_eq!(Some("alpha"), symbols.name(&alpha));
+/// assert_eq!("alpha^-1", alpha.inverse().to_string_with(&symbols));
+/// assert_eq!(Literal::new('e', 1, false), symbols.intern("e"));
+/// assert_eq!(Literal::new('w', 1, false), symbols.intern("v"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SymbolTable {
+    literals: BTreeMap<String, Literal>,
+    names:    BTreeMap<Literal, String>
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable::default()
+    }
+
+    /// Returns the literal of `name`, adding it to the table if it is new.
+    pub fn intern(&mut self, name: &str) -> Literal {
+        if let Some(literal) = self.literals.get(name) {
+            return *literal;
+        }
+        let literal = match canonical_literal(name) {
+            Some(literal) if !self.names.contains_key(&literal) && !is_reserved(&literal) => literal,
+            _ => self.fresh_literal(name)
+        };
+        self.literals.insert(name.to_string(), literal);
+        self.names.insert(literal, name.to_string());
+        literal
+    }
+
+    /// Returns the literal of `name`, if it has been interned.
+    pub fn literal(&self, name: &str) -> Option<Literal> {
+        self.literals.get(name).copied()
+    }
+
+    /// Returns the name of a non-inverted literal, if it has been interned.
+    pub fn name(&self, literal: &Literal) -> Option<&str> {
+        self.names.get(literal).map(|name| name.as_str())
+    }
+
+    /// Returns the name of the variable of `literal`. Literals which have not
+    /// been interned are named as by `Display`.
+    pub fn name_of_variable(&self, literal: &Literal) -> String {
+        let variable = Literal::new(literal.character, literal.id, false);
+        match self.name(&variable) {
+            Some(name) => name.to_string(),
+            None => variable.to_string()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.literals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.literals.is_empty()
+    }
+
+    fn fresh_literal(&self, name: &str) -> Literal {
+        let first = name.chars().next().expect("Names are not empty.");
+        let character = match first.to_lowercase().next().unwrap_or(first) {
+            'v' => 'w',
+            character => character
+        };
+        let mut id = 1;
+        while self.names.contains_key(&Literal::new(character, id, false))
+                || self.literals.contains_key(&Literal::new(character, id, false).to_string()) {
+            id += 1;
+        }
+        Literal::new(character, id, false)
+    }
+}
+
+/// Whether `literal` is printed as something else than a variable by `Display`.
+fn is_reserved(literal: &Literal) -> bool {
+    *literal == Literal::from('e') || literal.character == 'v'
+}
+
+/// The literal whose string is `name`, if there is one and it is not inverted.
+fn canonical_literal(name: &str) -> Option<Literal> {
+    let mut characters = name.chars();
+    let character = characters.next()?;
+    if !character.is_alphabetic() || character.is_uppercase() {
+        return None;
+    }
+    let digits = characters.as_str();
+    let id = match digits.is_empty() {
+        true  => 0,
+        false => digits.parse::<usize>().ok()?
+    };
+    let literal = Literal::new(character, id, false);
+    match literal.to_string() == name {
+        true  => Some(literal),
+        false => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Term;
+
+    #[test]
+    fn test_canonical_names() {
+        assert_eq!(Some(Literal::from('x')), canonical_literal("x"));
+        assert_eq!(Some(Literal::new('x', 12, false)), canonical_literal("x12"));
+        assert_eq!(None, canonical_literal("x0"));
+        assert_eq!(None, canonical_literal("x012"));
+        assert_eq!(None, canonical_literal("X"));
+        assert_eq!(None, canonical_literal("xy"));
+        assert_eq!(None, canonical_literal("_"));
+    }
+
+    #[test]
+    fn test_fresh_literals_avoid_canonical_names() {
+        let mut symbols = SymbolTable::new();
+        let alpha = symbols.intern("alpha");
+        assert_eq!(Literal::new('a', 1, false), alpha);
+        // the natural literal of a1 is taken, so a1 gets a fresh one
+        let a1 = symbols.intern("a1");
+        assert_eq!(Literal::new('a', 2, false), a1);
+        // and a2 may not reuse the literal of a1
+        assert_eq!(Literal::new('a', 3, false), symbols.intern("a2"));
+        assert_eq!(3, symbols.len());
+    }
+
+    #[test]
+    fn test_reserved_literals() {
+        let mut symbols = SymbolTable::new();
+        assert_eq!(Literal::new('e', 1, false), symbols.intern("e"));
+        assert_eq!(Literal::new('e', 2, false), symbols.intern("e2"));
+        assert_eq!(Literal::new('w', 1, false), symbols.intern("v"));
+        assert_eq!(Literal::new('w', 2, false), symbols.intern("v1"));
+        assert_eq!(Literal::new('w', 3, false), symbols.intern("value"));
+        assert_eq!(Literal::from('w'), symbols.intern("w"));
+    }
+
+    #[test]
+    fn test_case_sensitivity() {
+        let mut symbols = SymbolTable::new();
+        let x = symbols.intern("x");
+        let big_x = symbols.intern("X");
+        assert_ne!(x, big_x);
+        assert!(!big_x.is_inverted);
+        assert_eq!("X", symbols.name_of_variable(&big_x.inverse()));
+    }
+}
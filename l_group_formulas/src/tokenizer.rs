@@ -0,0 +1,201 @@
+use super::parsing_error::{ParsingError, ParsingErrorKind};
+
+/// The tokens of the `Standard` dialect and of MV-terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub (crate) enum Token {
+    /// A variable name, which is the text of the token.
+    Identifier,
+    /// A sequence of digits, which is the text of the token.
+    Number,
+    Join,
+    Meet,
+    /// `|`, which is a join, or delimits an absolute value.
+    Bar,
+    Times,
+    /// `**`, followed by an exponent.
+    Power,
+    Inverse,
+    /// A postfix `^-1` or `⁻¹`.
+    PostfixInverse,
+    /// A postfix `^+` or `⁺`.
+    PositivePart,
+    /// A postfix `^-` or `⁻`.
+    NegativePart,
+    /// `^` between a term and its conjugator.
+    Caret,
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+    Comma,
+    /// `⊕` or `+`, the strong disjunction of MV-terms.
+    Oplus,
+    /// `⊙`, the strong conjunction of MV-terms.
+    Odot,
+    /// `¬` or `~`, the negation of MV-terms.
+    Not,
+    /// `→` or `->`, the implication of MV-terms.
+    Implies
+}
+
+/// Splits `input[start .. end]` into tokens, together with their byte ranges in `input`.
+pub (crate) fn tokenize(input: &str, start: usize, end: usize) -> Result<Vec<(Token, usize, usize)>, ParsingError> {
+    let mut tokens = Vec::new();
+    let mut characters = input[start .. end].char_indices()
+        .map(|(i, c)| (start + i, c))
+        .peekable();
+    while let Some((i, c)) = characters.next() {
+        let rest = &input[i + c.len_utf8() .. end];
+        // tokens consisting of `c` and some of the characters after it
+        let (token, length) = match c {
+            c if c.is_whitespace() => continue,
+            c if c.is_alphabetic() || c == '_' => {
+                let length = rest.find(|d: char| !d.is_alphanumeric() && d != '_').unwrap_or(rest.len());
+                (Token::Identifier, length)
+            },
+            c if c.is_ascii_digit() => (Token::Number, rest.find(|d: char| !d.is_ascii_digit()).unwrap_or(rest.len())),
+            '\\' if rest.starts_with('/') => (Token::Join, 1),
+            '/' if rest.starts_with('\\') => (Token::Meet, 1),
+            '*' if rest.starts_with('*') => (Token::Power, 1),
+            '-' if rest.starts_with('>') => (Token::Implies, 1),
+            '^' if rest.starts_with("-1") => (Token::PostfixInverse, 2),
+            '^' if rest.starts_with('-') => (Token::NegativePart, 1),
+            '^' if rest.starts_with('+') => (Token::PositivePart, 1),
+            '⁻' if rest.starts_with('¹') => (Token::PostfixInverse, '¹'.len_utf8()),
+            '⁻' => (Token::NegativePart, 0),
+            '⁺' => (Token::PositivePart, 0),
+            '^' => (Token::Caret, 0),
+            '∨' => (Token::Join, 0),
+            '&' | '∧' => (Token::Meet, 0),
+            '|' => (Token::Bar, 0),
+            '*' => (Token::Times, 0),
+            '-' => (Token::Inverse, 0),
+            '(' => (Token::Open, 0),
+            ')' => (Token::Close, 0),
+            '[' => (Token::OpenBracket, 0),
+            ']' => (Token::CloseBracket, 0),
+            ',' => (Token::Comma, 0),
+            '⊕' | '+' => (Token::Oplus, 0),
+            '⊙' => (Token::Odot, 0),
+            '¬' | '~' => (Token::Not, 0),
+            '→' => (Token::Implies, 0),
+            c => return Err(ParsingError::new(
+                ParsingErrorKind::UnexpectedCharacter(c), i, i + c.len_utf8(), input))
+        };
+        let token_end = i + c.len_utf8() + length;
+        while characters.peek().is_some_and(|(j, _)| *j < token_end) {
+            characters.next();
+        }
+        tokens.push((token, i, token_end));
+    }
+    Ok(tokens)
+}
+
+/// The tokens of a part of the input, with the position of the parser.
+pub (crate) struct Tokens<'a> {
+    input:    &'a str,
+    tokens:   Vec<(Token, usize, usize)>,
+    position: usize,
+    end:      usize
+}
+
+impl<'a> Tokens<'a> {
+    pub (crate) fn new(input: &'a str, start: usize, end: usize) -> Result<Tokens<'a>, ParsingError> {
+        Ok(Tokens { input, tokens: tokenize(input, start, end)?, position: 0, end })
+    }
+
+    pub (crate) fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.position).map(|(token, _, _)| *token)
+    }
+
+    pub (crate) fn next(&mut self) -> Option<(Token, usize, usize)> {
+        let next = self.tokens.get(self.position).copied();
+        if next.is_some() {
+            self.position += 1;
+        }
+        next
+    }
+
+    /// The byte offset of the next token, or of the end of the input.
+    pub (crate) fn offset(&self) -> usize {
+        self.tokens.get(self.position).map_or(self.end, |(_, start, _)| *start)
+    }
+
+    /// The byte offset of the end of the last token read.
+    pub (crate) fn previous_end(&self) -> usize {
+        self.position.checked_sub(1).map_or(self.offset(), |i| self.tokens[i].2)
+    }
+
+    pub (crate) fn is_at_end(&self) -> bool {
+        self.position == self.tokens.len()
+    }
+
+    pub (crate) fn text(&self, start: usize, end: usize) -> &'a str {
+        &self.input[start .. end]
+    }
+
+    pub (crate) fn error(&self, kind: ParsingErrorKind, start: usize, end: usize) -> ParsingError {
+        ParsingError::new(kind, start, end, self.input)
+    }
+
+    /// An error about the next token, or about the input ending.
+    pub (crate) fn unexpected(&self) -> ParsingError {
+        match self.tokens.get(self.position) {
+            Some((_, start, end)) => self.error(
+                ParsingErrorKind::UnexpectedToken(self.input[*start .. *end].to_string()), *start, *end),
+            None => self.error(ParsingErrorKind::UnexpectedEnd, self.end, self.end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens_of(s: &str) -> Vec<Token> {
+        tokenize(s, 0, s.len()).unwrap().into_iter().map(|(token, _, _)| token).collect()
+    }
+
+    #[test]
+    fn test_spellings() {
+        assert_eq!(vec![Token::Join, Token::Bar, Token::Join], tokens_of("\\/ | ∨"));
+        assert_eq!(vec![Token::Meet; 3], tokens_of("/\\ & ∧"));
+    }
+
+    #[test]
+    fn test_identifiers() {
+        let expected = vec![Token::Identifier, Token::Number, Token::Times, Token::Identifier];
+        assert_eq!(expected, tokens_of("V12 1*e"));
+        let spans: Vec<(usize, usize)> = tokenize("x_left*αβ", 0, 11).unwrap()
+            .into_iter()
+            .map(|(_, start, end)| (start, end))
+            .collect();
+        assert_eq!(vec![(0, 6), (6, 7), (7, 11)], spans);
+    }
+
+    #[test]
+    fn test_postfix_operators() {
+        assert_eq!(vec![Token::Identifier, Token::PostfixInverse], tokens_of("x^-1"));
+        assert_eq!(vec![Token::Identifier, Token::PostfixInverse], tokens_of("x⁻¹"));
+        assert_eq!(vec![Token::Identifier, Token::NegativePart, Token::PositivePart], tokens_of("x^-⁺"));
+        assert_eq!(vec![Token::Identifier, Token::PositivePart, Token::NegativePart], tokens_of("x^+⁻"));
+        let expected = vec![Token::Identifier, Token::Power, Token::Inverse, Token::Number, Token::Caret, Token::Identifier];
+        assert_eq!(expected, tokens_of("x**-23^y"));
+    }
+
+    #[test]
+    fn test_mv_operators() {
+        let expected = vec![Token::Not, Token::Identifier, Token::Oplus, Token::Number, Token::Implies, Token::Odot];
+        assert_eq!(expected, tokens_of("¬x ⊕ 1 → ⊙"));
+        assert_eq!(expected, tokens_of("~x + 1 -> ⊙"));
+        assert_eq!(vec![Token::Inverse, Token::Identifier, Token::Inverse], tokens_of("-x -"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = tokenize("x / y", 0, 5).unwrap_err();
+        assert_eq!((ParsingErrorKind::UnexpectedCharacter('/'), 2), (error.kind, error.start));
+        let error = tokenize("x ; y", 0, 5).unwrap_err();
+        assert_eq!((ParsingErrorKind::UnexpectedCharacter(';'), 2), (error.kind, error.start));
+    }
+}
@@ -0,0 +1,168 @@
+use l_group_formulas::free_group_term::FreeGroupTerm;
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::literal::Literal;
+use l_group_formulas::Term;
+use l_group_cnf::fresh_variables::FreshVariables;
+use l_group_cnf::normal_cnf::CNF;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+use std::collections::{BTreeMap, BTreeSet};
+use super::linear_program::feasible_point;
+use super::split_at_meets::split_at_meets;
+use super::validity_result::Direction;
+
+/// An assignment of the variables of an `LGroupFormula` to rationals, under
+/// which the formula fails in the abelian l-group `ℚ`.
+///
+/// Products are sums, inverses negatives, and joins and meets maxima and minima.
+/// If the formula is the inequation `s <= t`, then `lhs_value > rhs_value`,
+/// where these are the values of `s` and `t`. If it is an equation, then they
+/// are different.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::is_valid_abelian;
+/// use l_group_validity::abelian::AbelianValidityResult;
+/// assert!(is_valid_abelian(LGroupFormula::from("xy = yx")).is_valid());
+/// match is_valid_abelian(LGroupFormula::from("x <= x ^ e")) {
+///     AbelianValidityResult::Invalid { countermodel, .. } => assert!(countermodel.lhs_value > countermodel.rhs_value),
+///     AbelianValidityResult::Valid => panic!("x <= e fails for x = 1")
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbelianCountermodel {
+    pub assignment: BTreeMap<Literal, BigRational>,
+    pub lhs_value:  BigRational,
+    pub rhs_value:  BigRational
+}
+
+impl std::fmt::Display for AbelianCountermodel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (x, value) in &self.assignment {
+            writeln!(f, "{} is {}", x, value)?;
+        }
+        write!(f, "The left hand side is {} and the right hand side is {}.", self.lhs_value, self.rhs_value)
+    }
+}
+
+/// The outcome of checking an `LGroupFormula` for validity in all abelian l-groups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbelianValidityResult {
+    Valid,
+    /// The inequation in `direction` fails, as witnessed by `countermodel`.
+    Invalid {
+        direction:    Direction,
+        countermodel: AbelianCountermodel
+    }
+}
+
+impl AbelianValidityResult {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, AbelianValidityResult::Valid)
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, AbelianValidityResult::Invalid { .. })
+    }
+}
+
+/// Returns the value of `term` in `ℚ`, where the variables are interpreted by `assignment`.
+///
+/// Panics if a variable of `term` is not assigned.
+pub fn evaluate(term: &LGroupTerm, assignment: &BTreeMap<Literal, BigRational>) -> BigRational {
+    match term {
+        LGroupTerm::Atom(x) => x.literals.iter()
+            .map(|l| {
+                let value = &assignment[&Literal::new(l.character, l.id, false)];
+                if l.is_inverted { -value } else { value.clone() }
+            })
+            .sum(),
+        LGroupTerm::Meet(xs) => xs.iter().map(|x| evaluate(x, assignment)).min().expect("Empty meet"),
+        LGroupTerm::Join(xs) => xs.iter().map(|x| evaluate(x, assignment)).max().expect("Empty join"),
+        LGroupTerm::Prod(xs) => xs.iter().map(|x| evaluate(x, assignment)).sum()
+    }
+}
+
+/// Returns an assignment of `variables` under which `term` is negative and
+/// all of `positive` are positive, or `None` if there is none, i.e., if
+/// `e <= term` holds in all abelian l-groups under these hypotheses.
+///
+/// `term` is split at meets, and each meetand is brought into its `CNF`, so
+/// that `term` is negative somewhere if, and only if, the words of a clause
+/// are all negative somewhere. Each clause is checked by a linear program on
+/// the exponent vectors of its words. The new variables of the `CNF` avoid
+/// `variables`, and are left out of the assignment. The conditions are
+/// strict homogeneous linear inequalities, so they have a solution if, and
+/// only if, they have one where each of them holds with a margin of `1`.
+pub (super) fn negative_assignment(
+        term: &LGroupTerm,
+        positive: &[FreeGroupTerm],
+        variables: &BTreeSet<Literal>) -> Option<BTreeMap<Literal, BigRational>> {
+    let mut fresh = FreshVariables::avoiding(variables);
+    for meetand in split_at_meets(term.clone()) {
+        for clause in CNF::with_fresh_variables(meetand, &mut fresh).meetands {
+            let mut clause_variables = variables.clone();
+            clause_variables.extend(clause.iter().flat_map(|w| w.literals.iter().map(variable)));
+            let index: BTreeMap<Literal, usize> = clause_variables.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+            let constraints: Vec<_> = clause.iter()
+                .map(|w| exponents(w, &index))
+                .chain(positive.iter().map(|p| exponents(&p.inverse(), &index)))
+                .map(|form| (form, -BigRational::one()))
+                .collect();
+            if let Some(point) = feasible_point(&constraints, index.len()) {
+                return Some(clause_variables.into_iter()
+                    .zip(point)
+                    .filter(|(x, _)| variables.contains(x))
+                    .collect());
+            }
+        }
+    }
+    None
+}
+
+/// The non-inverted literal of `literal`.
+fn variable(literal: &Literal) -> Literal {
+    Literal::new(literal.character, literal.id, false)
+}
+
+/// The exponent vector of `word`, i.e., its image in the free abelian group.
+fn exponents(word: &FreeGroupTerm, index: &BTreeMap<Literal, usize>) -> Vec<BigRational> {
+    let mut form = vec![BigRational::zero(); index.len()];
+    for l in &word.literals {
+        form[index[&variable(l)]] += if l.is_inverted { -BigRational::one() } else { BigRational::one() };
+    }
+    form
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_negative_somewhere(s: &str) -> bool {
+        let term = LGroupTerm::from(s);
+        match negative_assignment(&term, &[], &term.variables()) {
+            Some(assignment) => {
+                assert!(evaluate(&term, &assignment) < BigRational::zero());
+                true
+            },
+            None => false
+        }
+    }
+
+    #[test]
+    fn test_negative_assignment() {
+        assert!(is_negative_somewhere("x v y"));
+        assert!(is_negative_somewhere("xY v yZ"));
+        assert!(!is_negative_somewhere("x v X"));
+        assert!(!is_negative_somewhere("xY v yZ v zX"));
+        assert!(!is_negative_somewhere("xyXY"));
+        assert!(is_negative_somewhere("(x v y)(X ^ Y) ^ (x ^ e)"));
+        assert!(!is_negative_somewhere("(x v y)(X ^ Y) v (x ^ y)Y"));
+        // products of joins, whose CNF has new variables
+        assert!(!is_negative_somewhere("(x v Y)(y v X)"));
+        assert!(!is_negative_somewhere("z(x v y)(X v Y)Z"));
+        assert!(is_negative_somewhere("(x v y)z(Z ^ w)"));
+    }
+}
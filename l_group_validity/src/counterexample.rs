@@ -0,0 +1,340 @@
+use l_group_formulas::formula::LGroupFormula;
+use l_group_formulas::free_group_term::FreeGroupTerm;
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::literal::Literal;
+use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
+use l_group_formulas::Term;
+use l_group_cnf::sup_inf_normal_form::SupInfNormalForm;
+use num_rational::BigRational;
+use num_traits::FromPrimitive;
+use std::collections::{BTreeMap, BTreeSet};
+use super::piecewise_linear::PiecewiseLinear;
+use super::Countermodel;
+use super::options::Options;
+use super::resources::Resources;
+use super::validity_result::Limit;
+
+/// An assignment of the variables of an `LGroupFormula` to order-preserving
+/// bijections of the rationals, under which the formula fails at `point`.
+///
+/// Bijections act on the right, i.e., the product `xy` first applies `x`
+/// and then `y`, and joins and meets are taken pointwise. If the formula is
+/// the inequation `s <= t`, then `lhs_value > rhs_value`, where these are the
+/// images of `point` under `s` and `t`, respectively. If it is an equation,
+/// then they are different.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::find_countermodel;
+/// use l_group_validity::counterexample::find_counterexample;
+/// let formula = LGroupFormula::from("xy <= yx");
+/// let countermodel = find_countermodel(formula.clone()).unwrap();
+/// let counterexample = find_counterexample(&formula, &countermodel).unwrap();
+/// assert!(counterexample.lhs_value > counterexample.rhs_value);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample {
+    pub assignment: BTreeMap<Literal, PiecewiseLinear>,
+    pub point:      BigRational,
+    pub lhs_value:  BigRational,
+    pub rhs_value:  BigRational
+}
+
+impl std::fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (x, map) in &self.assignment {
+            writeln!(f, "{} is {}", x, map)?;
+        }
+        write!(f, "At {}, the left hand side is {} and the right hand side is {}.",
+               self.point, self.lhs_value, self.rhs_value)
+    }
+}
+
+/// Returns a `Counterexample` to the `LGroupFormula`, built from the
+/// `Countermodel` a check found for it, e.g., in `ValidityResult::Invalid`,
+/// or `None` if the search for the order of the points finds none.
+///
+/// The search may take exponentially many steps in the length of the
+/// meetand. Use `find_counterexample_with` to limit it.
+pub fn find_counterexample(eq: &LGroupFormula, countermodel: &Countermodel) -> Option<Counterexample> {
+    find_counterexample_with(eq, countermodel, &Options::default())
+        .map(|counterexample| counterexample.expect("There are no limits by default."))
+}
+
+/// Returns a `Counterexample` as `find_counterexample` does, or the limit of
+/// `options` hit on the way. Every order of points tried counts as a search node.
+pub fn find_counterexample_with(
+        eq: &LGroupFormula,
+        countermodel: &Countermodel,
+        options: &Options) -> Option<Result<Counterexample, Limit>> {
+    let (lhs, rhs) = match eq {
+        LGroupFormula::LGroupEquation(lhs, rhs) | LGroupFormula::LGroupInequation(lhs, rhs) => (lhs, rhs)
+    };
+
+    // e <= meetand fails, and meetand has no meets, so it is the join of these words
+    let words: BTreeSet<FreeGroupTerm> = SupInfNormalForm::from(&countermodel.meetand).joinands
+        .into_iter()
+        .flatten()
+        .collect();
+    let chain = Chain::new(&words);
+    let resources = Resources::new(options, &());
+    let positions = match chain.order(&words, &countermodel.negative_cone, &resources) {
+        Ok(positions) => positions?,
+        Err(limit) => return Some(Err(limit))
+    };
+
+    let mut assignment = BTreeMap::new();
+    for x in lhs.variables().union(&rhs.variables()) {
+        let mut points = Vec::new();
+        if let Some(edges) = chain.edges.get(x) {
+            for (u, v) in edges {
+                points.push((positions[*u].clone(), positions[*v].clone()));
+            }
+        }
+        assignment.insert(*x, PiecewiseLinear::interpolating(points));
+    }
+
+    let point = positions[chain.index[&FreeGroupTerm::new(Vec::new())]].clone();
+    let lhs_value = evaluate(lhs, &assignment, &point);
+    let rhs_value = evaluate(rhs, &assignment, &point);
+    Some(Ok(Counterexample { assignment, point, lhs_value, rhs_value }))
+}
+
+/// Returns the image of `point` under `term`, where the variables are
+/// interpreted by `assignment`, and act on the right.
+///
+/// Panics if a variable of `term` is not assigned.
+pub fn evaluate(
+        term: &LGroupTerm,
+        assignment: &BTreeMap<Literal, PiecewiseLinear>,
+        point: &BigRational) -> BigRational {
+    match term {
+        LGroupTerm::Atom(x) => {
+            let mut value = point.clone();
+            for literal in &x.literals {
+                let map = &assignment[&Literal::new(literal.character, literal.id, false)];
+                value = match literal.is_inverted {
+                    false => map.apply(&value),
+                    true  => map.inverse().apply(&value)
+                };
+            }
+            value
+        },
+        LGroupTerm::Meet(xs) => xs.iter()
+            .map(|x| evaluate(x, assignment, point))
+            .min()
+            .expect("Empty meet"),
+        LGroupTerm::Join(xs) => xs.iter()
+            .map(|x| evaluate(x, assignment, point))
+            .max()
+            .expect("Empty join"),
+        LGroupTerm::Prod(xs) => {
+            let mut value = point.clone();
+            for x in xs {
+                value = evaluate(x, assignment, &value);
+            }
+            value
+        }
+    }
+}
+
+/// The prefixes of some words, as elements of the free group,
+/// together with the partial maps given by right multiplication by variables.
+struct Chain {
+    points:    Vec<FreeGroupTerm>,
+    index:     BTreeMap<FreeGroupTerm, usize>,
+    /// For each variable `x`, the pairs `(u, v)` of points with `ux = v`.
+    edges:     BTreeMap<Literal, BTreeSet<(usize, usize)>>
+}
+
+/// Whether, for points `i` and `j`, it is known that `i < j`, `j < i`, or neither.
+type Relation = Vec<Vec<Option<bool>>>;
+
+impl Chain {
+    fn new(words: &BTreeSet<FreeGroupTerm>) -> Chain {
+        let mut chain = Chain {
+            points: Vec::new(),
+            index:  BTreeMap::new(),
+            edges:  BTreeMap::new()
+        };
+        for w in words {
+            let mut previous = chain.point(FreeGroupTerm::new(Vec::new()));
+            for k in 0 .. w.literals.len() {
+                let current = chain.point(FreeGroupTerm::new(w.literals[0 ..= k].to_vec()));
+                let literal = w.literals[k];
+                let variable = Literal::new(literal.character, literal.id, false);
+                let edge = match literal.is_inverted {
+                    false => (previous, current),
+                    true  => (current, previous)
+                };
+                chain.edges.entry(variable).or_default().insert(edge);
+                previous = current;
+            }
+        }
+        chain
+    }
+
+    fn point(&mut self, u: FreeGroupTerm) -> usize {
+        if let Some(i) = self.index.get(&u) {
+            return *i;
+        }
+        self.points.push(u.clone());
+        self.index.insert(u, self.points.len() - 1);
+        self.points.len() - 1
+    }
+
+    /// Finds positions of the points in the rationals, such that every variable
+    /// is order-preserving and every word sends `e` below itself. Whenever
+    /// there is a choice, the order on short elements given by `negative_cone`
+    /// is tried first. Gives up and returns the limit as soon as `resources`
+    /// hits one.
+    fn order(
+            &self,
+            words: &BTreeSet<FreeGroupTerm>,
+            negative_cone: &BTreeSet<ShortFreeGroupTerm>,
+            resources: &Resources) -> Result<Option<Vec<BigRational>>, Limit> {
+        let n = self.points.len();
+        let identity = self.index[&FreeGroupTerm::new(Vec::new())];
+        let mut relation = vec![vec![None; n]; n];
+        let facts = words.iter().map(|w| (self.index[w], identity)).collect();
+        if !self.propagate(&mut relation, facts) {
+            return Ok(None);
+        }
+        let relation = match self.search(relation, negative_cone, resources, 0)? {
+            Some(relation) => relation,
+            None => return Ok(None)
+        };
+        Ok(Some((0 .. n)
+            .map(|i| (0 .. n).filter(|k| relation[*k][i] == Some(true)).count())
+            .map(|rank| BigRational::from_usize(rank).unwrap())
+            .collect()))
+    }
+
+    fn search(
+            &self,
+            relation: Relation,
+            negative_cone: &BTreeSet<ShortFreeGroupTerm>,
+            resources: &Resources,
+            depth: usize) -> Result<Option<Relation>, Limit> {
+        resources.visit(depth)?;
+        let n = self.points.len();
+        let undecided = (0 .. n)
+            .flat_map(|i| (i + 1 .. n).map(move |j| (i, j)))
+            .find(|(i, j)| relation[*i][*j].is_none());
+        let (i, j) = match undecided {
+            None => return Ok(Some(relation)),
+            Some(pair) => pair
+        };
+
+        // u < v iff u * v^-1 < e
+        let quotient = self.points[i].clone() * self.points[j].inverse();
+        let preferred = match quotient.literals.len() <= 3 {
+            true  => !negative_cone.contains(&ShortFreeGroupTerm::from(quotient.inverse())),
+            false => true
+        };
+        for i_is_smaller in &[preferred, !preferred] {
+            let mut attempt = relation.clone();
+            let fact = match i_is_smaller {
+                true  => (i, j),
+                false => (j, i)
+            };
+            if self.propagate(&mut attempt, vec![fact]) {
+                if let Some(result) = self.search(attempt, negative_cone, resources, depth + 1)? {
+                    return Ok(Some(result));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Adds the facts `i < j` and their consequences under transitivity
+    /// and order-preservation of the variables. Returns `false` if this
+    /// leads to a contradiction.
+    #[allow(clippy::needless_range_loop)]
+    fn propagate(&self, relation: &mut Relation, mut facts: Vec<(usize, usize)>) -> bool {
+        let n = self.points.len();
+        while let Some((i, j)) = facts.pop() {
+            match relation[i][j] {
+                Some(true)  => continue,
+                Some(false) => return false,
+                None        => {}
+            };
+            if i == j {
+                return false;
+            }
+            relation[i][j] = Some(true);
+            relation[j][i] = Some(false);
+            for k in 0 .. n {
+                if relation[k][i] == Some(true) { facts.push((k, j)); }
+                if relation[j][k] == Some(true) { facts.push((i, k)); }
+            }
+            for edges in self.edges.values() {
+                let image_of = |u| edges.iter().find(|(a, _)| *a == u).map(|(_, b)| *b);
+                let preimage_of = |v| edges.iter().find(|(_, b)| *b == v).map(|(a, _)| *a);
+                if let (Some(k), Some(l)) = (image_of(i), image_of(j)) {
+                    facts.push((k, l));
+                }
+                if let (Some(k), Some(l)) = (preimage_of(i), preimage_of(j)) {
+                    facts.push((k, l));
+                }
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::find_countermodel;
+    use super::super::options::Limits;
+
+    fn check_counterexample(string: &str) {
+        let formula = LGroupFormula::from(string);
+        let countermodel = find_countermodel(formula.clone()).expect("no countermodel");
+        let counterexample = find_counterexample(&formula, &countermodel).expect("no counterexample");
+        match formula {
+            LGroupFormula::LGroupInequation(_, _) => assert!(counterexample.lhs_value > counterexample.rhs_value),
+            LGroupFormula::LGroupEquation(_, _) => assert!(counterexample.lhs_value != counterexample.rhs_value)
+        };
+    }
+
+    #[test]
+    fn test_commutativity() {
+        check_counterexample("xy = yx");
+        check_counterexample("xy <= yx");
+    }
+
+    #[test]
+    fn test_colacito_example_1point3point7() {
+        check_counterexample("e <= xx v xy v yX ");
+    }
+
+    #[test]
+    fn test_representable_l_groups() {
+        check_counterexample("e <= x v yXY");
+    }
+
+    #[test]
+    fn test_weakly_abelian() {
+        check_counterexample("(x ^ e)(x ^ e) <= Y(x ^ e)y");
+    }
+
+    #[test]
+    fn test_long_atoms() {
+        check_counterexample("e <= xyzw");
+        check_counterexample("e = e v xyzw");
+    }
+
+    #[test]
+    fn test_limits() {
+        let formula = LGroupFormula::from("e <= xx v xy v yX");
+        let countermodel = find_countermodel(formula.clone()).unwrap();
+        let options = Options { limits: Limits { search_nodes: Some(1), ..Limits::default() }, ..Options::default() };
+        assert_eq!(Some(Err(Limit::SearchNodes)), find_counterexample_with(&formula, &countermodel, &options));
+        let options = Options { limits: Limits { search_nodes: Some(1000), ..Limits::default() }, ..Options::default() };
+        assert_eq!(find_counterexample(&formula, &countermodel).map(Ok), find_counterexample_with(&formula, &countermodel, &options));
+    }
+}
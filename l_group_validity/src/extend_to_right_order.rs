@@ -1,23 +1,496 @@
-//use truncated_free_groups::truncated_group::TruncatedGroup;
+use truncated_free_groups::bit_set::BitSet;
 use truncated_free_groups::tiny_truncated_group::TinyTruncatedGroup;
-use truncated_free_groups::truncated_subgroup::TruncatedSubgroup;
+use truncated_free_groups::truncated_group::TruncatedGroup;
+use truncated_free_groups::truncated_subgroup::{Checkpoint, Justification, TruncatedSubgroup};
 use truncated_free_groups::tiny_truncated_group::ElementsExceptIdentity;
 use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
+use l_group_formulas::literal::Literal;
 use std::collections::BTreeSet;
+use tracing::{debug, Level};
+use certificate_checker::certificate::{Refutation, Step};
 // use l_group_formulas::short_free_group_term::Len;
 use l_group_formulas::Term;
 use truncated_free_groups::truncated_subgroup::Insert;
+use super::sat::{SatFormula, Interrupted};
+use super::options::{Heuristic, Options};
+use super::resources::Resources;
+use super::validity_result::{Statistics, Limit};
 
 
-pub (super) fn extend_to_right_order(elements: Box<BTreeSet<ShortFreeGroupTerm>>) -> bool {
-    if elements.len() == 0 {
-        return true;
+/// Returns a subset of the ball of radius 3 containing `elements`, which is
+/// closed under (short) products, does not contain `e`, and contains every
+/// element of the ball of radius 2 or its inverse. If there is no such set,
+/// i.e., if `elements` does not extend to a right order, returns `None`.
+/// Adds the subgroups visited and the restarts of the search to `statistics`.
+/// Gives up and returns `None` as soon as `stop` returns `true`, or the
+/// limit hit as soon as `resources` hits one. So once `stop` has returned
+/// `true`, `Ok(None)` does not say that `elements` do not extend, and callers
+/// have to ignore it.
+pub (super) fn extend_to_right_order(
+        elements: BTreeSet<ShortFreeGroupTerm>,
+        options: &Options,
+        statistics: &mut Statistics,
+        resources: &Resources,
+        stop: &dyn Fn() -> bool) -> Result<Option<BTreeSet<ShortFreeGroupTerm>>, Limit> {
+    extend(elements, options, statistics, resources, stop, false)
+}
+
+/// Returns a set as `extend_to_right_order` does, which is additionally closed
+/// under (short) conjugates by generators, i.e., the set of elements below `e`
+/// of a partial bi-order, or `None` if `elements` does not extend to one.
+/// `None` shows that `elements` does not extend to a bi-order, but a partial
+/// bi-order is not known to extend to a bi-order of the free group.
+pub (super) fn extend_to_bi_order(
+        elements: BTreeSet<ShortFreeGroupTerm>,
+        options: &Options,
+        statistics: &mut Statistics,
+        resources: &Resources,
+        stop: &dyn Fn() -> bool) -> Result<Option<BTreeSet<ShortFreeGroupTerm>>, Limit> {
+    extend(elements, options, statistics, resources, stop, true)
+}
+
+/// Searches for a set as `extend_to_right_order` does. With `options.restarts`,
+/// the search starts over whenever it visited that many subgroups, with ties
+/// of the heuristic broken at random, and with twice the limit. Once `stop`
+/// has returned `true`, callers have to ignore `Ok(None)`, as for
+/// `extend_to_right_order`.
+fn extend(
+        elements: BTreeSet<ShortFreeGroupTerm>,
+        options: &Options,
+        statistics: &mut Statistics,
+        resources: &Resources,
+        stop: &dyn Fn() -> bool,
+        normal: bool) -> Result<Option<BTreeSet<ShortFreeGroupTerm>>, Limit> {
+    if elements.is_empty() {
+        return Ok(Some(elements));
+    }
+
+    let all_literals = match literals(&elements) {
+        Some(all_literals) => all_literals,
+        None => return Ok(None)
+    };
+    let ball = resources.ball(&all_literals);
+    let ambient_group = TinyTruncatedGroup::within(ball.clone());
+    let mut subgroup = TruncatedSubgroup::within(ball.clone(), ball.set(&elements), false, true, normal);
+    if contains_identity(&subgroup) && tracing::enabled!(Level::DEBUG) {
+        // closing again, to tell why
+        let recording = TruncatedSubgroup::within_recording(ball.clone(), ball.set(&elements), true, normal);
+        if let Some(argument) = recording.argument(ball.identity()) {
+            debug!("The elements do not extend, as {}.", argument);
+        }
+    }
+    let mut strong_complement = Complement { elements: ambient_group.elements_except_identity(), trail: Vec::new() };
+    for x in subgroup.elements.iter() {
+        strong_complement.elements.remove(x);
+        strong_complement.elements.remove(ball.inverse(x));
+    }
+    let mut search = Search {
+        ambient_group: &ambient_group,
+        elements:      &elements,
+        options,
+        normal,
+        certify:       false,
+        nodes:         &mut statistics.search_nodes,
+        resources,
+        stop,
+        budget:        options.restarts,
+        random:        None
+    };
+    let mut random = Random(options.seed | 1);
+    let mut restarts = 0;
+    loop {
+        match search.run(&mut subgroup, &mut strong_complement) {
+            Outcome::Extends(negative_cone) => return Ok(Some(negative_cone)),
+            Outcome::Refuted(_) => return Ok(None),
+            Outcome::Interrupted => {
+                resources.check()?;
+                if stop() {
+                    return Ok(None);
+                }
+                restarts += 1;
+                statistics.restarts += 1;
+                search.budget = options.restarts.map(|limit| limit << restarts.min(32));
+                search.random = Some(random.next());
+                debug!(restarts, budget = ?search.budget, "Restarting the search.");
+            }
+        };
+    }
+}
+
+/// Whether elements extend to an order, as `refute` decides it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub (super) enum Decision {
+    /// They do not extend, as the refutation shows.
+    Refuted(Refutation),
+    /// They extend, with the negative cone `extend` would return.
+    Extends(BTreeSet<ShortFreeGroupTerm>)
+}
+
+/// Returns why `elements` do not extend to a right order, or to a bi-order
+/// if `normal` is set, as the tree of the choices `extend` makes without
+/// restarting, or the negative cone if they extend. Adds the subgroups
+/// visited to `nodes`. Gives up and returns the limit as soon as `resources`
+/// hits one.
+pub (super) fn refute(
+        elements: BTreeSet<ShortFreeGroupTerm>,
+        options: &Options,
+        nodes: &mut usize,
+        resources: &Resources,
+        normal: bool) -> Result<Decision, Limit> {
+    let all_literals = match literals(&elements) {
+        _ if elements.is_empty() => return Ok(Decision::Extends(elements)),
+        Some(all_literals) => all_literals,
+        // `e` is one of the elements
+        None => return Ok(Decision::Refuted(Refutation::Closure(Vec::new())))
+    };
+    let ball = resources.ball(&all_literals);
+    let ambient_group = TinyTruncatedGroup::within(ball.clone());
+    let mut subgroup = TruncatedSubgroup::within_recording(ball.clone(), ball.set(&elements), true, normal);
+    let mut complement = Complement { elements: ambient_group.elements_except_identity(), trail: Vec::new() };
+    complement.remove_with_inverses(&ball, &subgroup.elements.iter().collect::<Vec<_>>());
+    let mut search = Search {
+        ambient_group: &ambient_group,
+        elements:      &elements,
+        options,
+        normal,
+        certify:       true,
+        nodes,
+        resources,
+        stop:          &|| false,
+        budget:        None,
+        random:        None
+    };
+    match search.run(&mut subgroup, &mut complement) {
+        Outcome::Extends(negative_cone) => Ok(Decision::Extends(negative_cone)),
+        Outcome::Refuted(refutation) => Ok(Decision::Refuted(refutation.expect("The search certifies."))),
+        // without a budget or `stop`, the search is only interrupted by a limit
+        Outcome::Interrupted => Err(resources.check().expect_err("The search stops at limits only."))
+    }
+}
+
+/// The outcome of a (partial) search.
+enum Outcome {
+    Extends(BTreeSet<ShortFreeGroupTerm>),
+    /// With a refutation if the search certifies.
+    Refuted(Option<Refutation>),
+    /// The budget of subgroups ran out, the search was stopped, or it hit a limit.
+    Interrupted
+}
+
+/// The elements of the ball of radius 2 such that neither they nor their
+/// inverses are in the subgroup searched, with a trail to roll back as
+/// `TruncatedSubgroup` has.
+struct Complement {
+    elements: BitSet,
+    trail:    Vec<usize>
+}
+
+impl Complement {
+    fn remove_with_inverses(&mut self, ball: &TruncatedGroup, added: &[usize]) {
+        for x in added {
+            for y in &[*x, ball.inverse(*x)] {
+                if self.elements.remove(*y) {
+                    self.trail.push(*y);
+                }
+            }
+        }
+    }
+
+    fn checkpoint(&self) -> usize {
+        self.trail.len()
+    }
+
+    fn rollback(&mut self, checkpoint: usize) {
+        for x in self.trail.drain(checkpoint ..) {
+            self.elements.insert(x);
+        }
+    }
+}
+
+/// The elements propagating added to a subgroup, in order, each with the
+/// refutation of its inverse if the search certifies.
+type Forced = Vec<(usize, Refutation)>;
+
+/// A choice of the search, to roll back to when it is refuted.
+struct Choice {
+    element:    usize,
+    /// Whether the inverse of `element` was chosen already.
+    inverted:   bool,
+    /// The elements propagating added to the subgroup the choice is made in.
+    forced:     Forced,
+    /// The refutation of `element`, once its inverse is chosen.
+    refuted:    Option<Refutation>,
+    subgroup:   Checkpoint,
+    complement: usize
+}
+
+/// A xorshift generator, so that restarts are reproducible from the seed.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+struct Search<'a> {
+    ambient_group: &'a TinyTruncatedGroup,
+    /// The elements to extend, to which `Heuristic::MostFrequent` refers.
+    elements:      &'a BTreeSet<ShortFreeGroupTerm>,
+    options:       &'a Options,
+    normal:        bool,
+    /// Whether refuted subgroups come with a `Refutation`, for which the
+    /// subgroup searched has to record why it contains its elements.
+    certify:       bool,
+    nodes:         &'a mut usize,
+    resources:     &'a Resources<'a>,
+    stop:          &'a dyn Fn() -> bool,
+    /// The number of subgroups left to visit before restarting.
+    budget:        Option<usize>,
+    /// The state of the generator breaking ties, if they are broken at random.
+    random:        Option<u64>
+}
+
+impl<'a> Search<'a> {
+    /// Returns the number of elements one step of closing `subgroup` under
+    /// `x` adds, or `None` if this already adds `e` or both an element and its
+    /// inverse. This is cheaper than inserting `x`, which closes completely.
+    fn probe(&self, subgroup: &TruncatedSubgroup, x: usize) -> Option<usize> {
+        let ball = &self.ambient_group.ball;
+        let elements = &subgroup.elements;
+        let mut added = BitSet::new(ball.len());
+        added.insert(x);
+        for (y, product) in ball.right_products(x).chain(ball.left_products(x)) {
+            if elements.contains(y) {
+                added.insert(product);
+            }
+        }
+        if let Some(square) = ball.product(x, x) {
+            added.insert(square);
+        }
+        if self.normal {
+            for (_, conjugate) in ball.conjugates(x) {
+                added.insert(*conjugate);
+            }
+        }
+        added.difference_with(elements);
+        let conflicts = added.contains(ball.identity())
+            || added.iter().any(|z| elements.contains(ball.inverse(z)) || added.contains(ball.inverse(z)));
+        match conflicts {
+            true  => None,
+            false => Some(added.len())
+        }
+    }
+
+    /// Returns how many input elements contain `x` or its inverse as a subword.
+    fn frequency(&self, x: ShortFreeGroupTerm) -> usize {
+        let occurs_in = |x: ShortFreeGroupTerm, y: &ShortFreeGroupTerm| {
+            let (x, y) = (word(&x), word(y));
+            x.len() <= y.len() && y.windows(x.len()).any(|w| w == x.as_slice())
+        };
+        self.elements.iter().filter(|y| occurs_in(x, y) || occurs_in(x.inverse(), y)).count()
+    }
+
+    /// Searches from `subgroup` depth first, choosing an element or its
+    /// inverse at every step, and rolls back to `subgroup` and `complement`
+    /// when done. The choices are kept on a stack rather than recursing, so
+    /// deep searches do not overflow. If the search certifies, the refutation
+    /// of a choice is put together from those of the element and its inverse
+    /// once both are refuted.
+    fn run(&mut self, subgroup: &mut TruncatedSubgroup, complement: &mut Complement) -> Outcome {
+        let ball = self.ambient_group.ball.clone();
+        let root = (subgroup.checkpoint(), complement.checkpoint());
+        let mut choices: Vec<Choice> = Vec::new();
+        let outcome = 'search: loop {
+            let next = match self.visit(subgroup, complement, choices.len()) {
+                Ok((minimal, forced)) => {
+                    choices.push(Choice {
+                        element:    minimal,
+                        inverted:   false,
+                        forced,
+                        refuted:    None,
+                        subgroup:   subgroup.checkpoint(),
+                        complement: complement.checkpoint()
+                    });
+                    minimal
+                },
+                // backtrack to the last choice whose inverse is left to try
+                Err(Outcome::Refuted(mut refutation)) => loop {
+                    let choice = match choices.last_mut() {
+                        Some(choice) => choice,
+                        None => break 'search Outcome::Refuted(refutation)
+                    };
+                    subgroup.rollback(choice.subgroup);
+                    complement.rollback(choice.complement);
+                    if !choice.inverted {
+                        choice.inverted = true;
+                        choice.refuted = refutation;
+                        break ball.inverse(choice.element);
+                    }
+                    let Choice { element, refuted, forced, .. } = choices.pop().expect("The choice is on the stack.");
+                    let branch = refuted.zip(refutation).map(|(with, with_inverse)| self.branch(element, with, with_inverse));
+                    refutation = self.after(forced, branch);
+                },
+                Err(outcome) => break outcome
+            };
+            let added = subgroup.insert(next);
+            complement.remove_with_inverses(&ball, added);
+        };
+        subgroup.rollback(root.0);
+        complement.rollback(root.1);
+        outcome
+    }
+
+    /// Visits `subgroup`, reached after `depth` choices, and returns the
+    /// element to choose next, or the outcome if there is nothing to choose.
+    /// Propagating adds the forced elements to `subgroup`, which are returned
+    /// with the element.
+    fn visit(
+            &mut self,
+            subgroup: &mut TruncatedSubgroup,
+            complement: &mut Complement,
+            depth: usize) -> Result<(usize, Forced), Outcome> {
+        *self.nodes += 1;
+        if (self.stop)() || self.resources.visit(depth).is_err() {
+            return Err(Outcome::Interrupted);
+        }
+        if let Some(budget) = &mut self.budget {
+            if *budget == 0 {
+                return Err(Outcome::Interrupted);
+            }
+            *budget -= 1;
+        }
+        if contains_identity(subgroup) {
+            return Err(Outcome::Refuted(self.closure(subgroup)));
+        }
+
+        let ball = self.ambient_group.ball.clone();
+        let mut scores = Vec::new();
+        let mut all_forced = Vec::new();
+        if self.options.propagate || self.options.heuristic == Heuristic::LargestClosure {
+            // an element is forced if one step with its inverse contradicts itself
+            loop {
+                scores.clear();
+                let mut forced = Vec::new();
+                for x in complement.elements.iter().filter(|x| *x < ball.inverse(*x)) {
+                    match (self.probe(subgroup, x), self.probe(subgroup, ball.inverse(x))) {
+                        (None, None) => {
+                            let branch = self.refutation(subgroup, x).zip(self.refutation(subgroup, ball.inverse(x)))
+                                .map(|(with, with_inverse)| self.branch(x, with, with_inverse));
+                            return Err(Outcome::Refuted(self.after(all_forced, branch)));
+                        },
+                        (Some(added), Some(added_inverse)) => match added >= added_inverse {
+                            true  => scores.push((added, x)),
+                            false => scores.push((added_inverse, ball.inverse(x)))
+                        },
+                        (Some(_), None) => forced.push(x),
+                        (None, Some(_)) => forced.push(ball.inverse(x))
+                    };
+                }
+                if forced.is_empty() || !self.options.propagate {
+                    break;
+                }
+                for x in forced {
+                    if let Some(refutation) = self.refutation(subgroup, ball.inverse(x)) {
+                        all_forced.push((x, refutation));
+                    }
+                    let added = subgroup.insert(x);
+                    complement.remove_with_inverses(&ball, added);
+                }
+                if contains_identity(subgroup) {
+                    let closure = self.closure(subgroup);
+                    return Err(Outcome::Refuted(self.after(all_forced, closure)));
+                }
+            }
+        }
+
+        if contains_all_terms_or_inverses(self.ambient_group, subgroup) {
+            return Err(Outcome::Extends(subgroup.terms()));
+        }
+
+        let minimal = match self.options.heuristic {
+            Heuristic::LargestClosure if !scores.is_empty() => self.choose(scores),
+            Heuristic::First | Heuristic::LargestClosure => self.choose(complement.elements.iter().map(|x| (0, x)).collect()),
+            Heuristic::MostFrequent => self.choose(complement.elements.iter().map(|x| (self.frequency(ball.elements[x]), x)).collect())
+        };
+        Ok((minimal, all_forced))
+    }
+
+    /// Returns why `subgroup`, which contains `e`, is refuted, if the search
+    /// certifies.
+    fn closure(&self, subgroup: &TruncatedSubgroup) -> Option<Refutation> {
+        if !self.certify {
+            return None;
+        }
+        let ball = &self.ambient_group.ball;
+        let derivation = subgroup.derivation(ball.identity()).expect("The subgroup records why it contains e.");
+        Some(Refutation::Closure(derivation.into_iter().filter_map(|(y, justification)| match justification {
+            Justification::Given => None,
+            Justification::Product(a, b) => Some(Step::Product(ball.elements[a], ball.elements[b], ball.elements[y])),
+            Justification::Conjugate(a, g) => {
+                let g = ball.elements[g].left.expect("Conjugates are by letters.");
+                Some(Step::Conjugate(ball.elements[a], g, ball.elements[y]))
+            }
+        }).collect()))
     }
 
+    /// Returns why adding `x` to `subgroup` gives `e`, if the search
+    /// certifies, which it does if `probe` finds a conflict.
+    fn refutation(&self, subgroup: &mut TruncatedSubgroup, x: usize) -> Option<Refutation> {
+        if !self.certify {
+            return None;
+        }
+        let checkpoint = subgroup.checkpoint();
+        subgroup.insert(x);
+        let refutation = self.closure(subgroup);
+        subgroup.rollback(checkpoint);
+        refutation
+    }
+
+    fn branch(&self, x: usize, with: Refutation, with_inverse: Refutation) -> Refutation {
+        Refutation::Branch(self.ambient_group.ball.elements[x], Box::new(with), Box::new(with_inverse))
+    }
+
+    /// Returns `refutation` preceded by branches on the elements `forced`,
+    /// where the refutation of the inverse of each is known.
+    fn after(&self, forced: Forced, refutation: Option<Refutation>) -> Option<Refutation> {
+        forced.into_iter().rev().fold(refutation, |refutation, (x, with_inverse)| {
+            refutation.map(|with| self.branch(x, with, with_inverse))
+        })
+    }
+
+    /// Returns the element with the highest score, where ties go to the least
+    /// element, or to a random one after restarting.
+    fn choose(&mut self, scores: Vec<(usize, usize)>) -> usize {
+        let best = scores.iter().map(|(score, _)| *score).max().expect("Something is left to choose.");
+        let mut ties: Vec<_> = scores.into_iter().filter(|(score, _)| *score == best).map(|(_, x)| x).collect();
+        // indices are ordered as the elements are
+        ties.sort();
+        match &mut self.random {
+            None => ties[0],
+            Some(state) => {
+                let mut random = Random(*state);
+                let index = (random.next() % ties.len() as u64) as usize;
+                *state = random.0;
+                ties[index]
+            }
+        }
+    }
+}
+
+fn word(x: &ShortFreeGroupTerm) -> Vec<Literal> {
+    [x.left, x.mid, x.right].iter().flatten().copied().collect()
+}
+
+/// Returns the literals occurring in `elements`, or `None` if one of them is `e`.
+fn literals(elements: &BTreeSet<ShortFreeGroupTerm>) -> Option<BTreeSet<Literal>> {
     let mut all_literals = BTreeSet::new();
-    for x in &*elements {
+    for x in elements {
         match (x.left, x.mid, x.right) {
-            (None, None, None) => { return false; }
+            (None, None, None) => { return None; }
             (Some(a), None, None) => { all_literals.insert(a); }
             (Some(a), Some(b), None) => {
                 all_literals.insert(a);
@@ -31,94 +504,294 @@ pub (super) fn extend_to_right_order(elements: Box<BTreeSet<ShortFreeGroupTerm>>
             _ => {}
         };
     }
+    Some(all_literals)
+}
 
-    let ambient_group = TinyTruncatedGroup::new(all_literals.clone());
-    let subgroup = TruncatedSubgroup::new(elements, all_literals, false, true);
+/// The question whether a set of elements extends to a right order, or to a
+/// bi-order, as a propositional formula. Its variable `i + 1` says that the
+/// element `ball[i]` is below `e`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub (super) struct OrderEncoding {
+    /// The ball of radius 3 without `e`, in the letters of the elements.
+    pub ball:    Vec<ShortFreeGroupTerm>,
+    pub formula: SatFormula
+}
 
-   
-    let mut terms_and_inverses = subgroup.elements.clone();
-    for x in &*subgroup.elements {
-        terms_and_inverses.insert(x.inverse());
-    }
-    
-    let mut strong_complement = ambient_group.elements_except_identity();
-    for x in *terms_and_inverses {
-        strong_complement.remove(&x);
+impl OrderEncoding {
+    /// Encodes the conditions on the sets `extend` looks for: they contain
+    /// `elements`, they are closed under short products and, if `normal` is
+    /// set, under short conjugates by generators, they do not contain `e`, and
+    /// they contain every element of the ball of radius 2 or its inverse.
+    pub (super) fn new(elements: &BTreeSet<ShortFreeGroupTerm>, normal: bool) -> OrderEncoding {
+        let mut letters = BTreeSet::new();
+        for a in literals(elements).expect("The elements do not contain e.") {
+            letters.insert(a);
+            letters.insert(a.inverse());
+        }
+        // words are vectors of indices of letters, numbered in base `m + 1`
+        let letters: Vec<Literal> = letters.into_iter().collect();
+        let m = letters.len();
+        let inverse: Vec<usize> = letters.iter().map(|a| letters.binary_search(&a.inverse()).unwrap()).collect();
+        let code = |w: &[usize]| w.iter().fold(0, |code, a| code * (m + 1) + a + 1);
+
+        // the reduced words of length at most 3, by length
+        let mut words: Vec<Vec<Vec<usize>>> = vec![vec![Vec::new()]];
+        for length in 1 ..= 3 {
+            let mut longer = Vec::new();
+            for w in &words[length - 1] {
+                for a in (0 .. m).filter(|a| w.last() != Some(&inverse[*a])) {
+                    longer.push([w.as_slice(), &[a]].concat());
+                }
+            }
+            words.push(longer);
+        }
+        let mut variable = vec![0; (m + 1).pow(3)];
+        let mut ball = Vec::new();
+        for w in words[1 ..].iter().flatten() {
+            let letter = |k: usize| w.get(k).map(|a| letters[*a]);
+            ball.push(ShortFreeGroupTerm::new(letter(0), letter(1), letter(2)));
+            variable[code(w)] = ball.len() as i32;
+        }
+        let var = |w: &[usize]| variable[code(w)];
+
+        let mut formula = SatFormula::new(ball.len());
+        for x in elements {
+            let w: Vec<usize> = [x.left, x.mid, x.right].iter().flatten()
+                .map(|a| letters.binary_search(a).unwrap())
+                .collect();
+            formula.add_clause(vec![var(&w)]);
+        }
+        for x in words[1 ..= 2].iter().flatten() {
+            let x_inverse: Vec<usize> = x.iter().rev().map(|a| inverse[*a]).collect();
+            formula.add_clause(vec![var(x), var(&x_inverse)]);
+        }
+        let mut y = Vec::with_capacity(3);
+        let mut product = Vec::with_capacity(3);
+        for x in words[1 ..].iter().flatten() {
+            // the products `xy` of length at most 3 where `y` cancels exactly the
+            // last `c` letters of `x`, i.e., `x = ps` and `y = s⁻¹w` with `xy = pw`
+            for c in 0 ..= x.len() {
+                let (p, s) = x.split_at(x.len() - c);
+                let longest = 3 - std::cmp::max(p.len(), c);
+                for w in words[.. longest + 1].iter().flatten() {
+                    let cancels_more = matches!((p.last(), w.first()), (Some(a), Some(b)) if *b == inverse[*a]);
+                    if cancels_more || (!w.is_empty() && w.first() == s.first()) || c + w.len() == 0 {
+                        continue;
+                    }
+                    y.clear();
+                    y.extend(s.iter().rev().map(|a| inverse[*a]));
+                    y.extend(w);
+                    product.clear();
+                    product.extend(p);
+                    product.extend(w);
+                    match product.len() {
+                        0 => formula.add_clause(vec![-var(x), -var(&y)]),
+                        _ => formula.add_clause(vec![-var(x), -var(&y), var(&product)])
+                    };
+                }
+            }
+            if normal {
+                for (g, g_inverse) in inverse.iter().enumerate() {
+                    // `g⁻¹xg`, which is short if something cancels
+                    let conjugate = match (x.first() == Some(&g), x.last() == Some(g_inverse)) {
+                        (true, true) => x[1 .. x.len() - 1].to_vec(),
+                        (true, _) => [&x[1 ..], &[g]].concat(),
+                        (_, true) => [&[*g_inverse], &x[.. x.len() - 1]].concat(),
+                        _ if x.len() == 1 => vec![*g_inverse, x[0], g],
+                        _ => continue
+                    };
+                    formula.add_clause(vec![-var(x), var(&conjugate)]);
+                }
+            }
+        }
+        OrderEncoding { ball, formula }
     }
 
-    extends_helper(&ambient_group, &subgroup, &mut strong_complement, 1)
+    /// Returns the elements whose variables are true in `model`.
+    pub (super) fn decode(&self, model: &[bool]) -> BTreeSet<ShortFreeGroupTerm> {
+        self.ball.iter().zip(model).filter(|(_, value)| **value).map(|(x, _)| *x).collect()
+    }
 }
 
-fn extends_helper(
-        ambient_group: &TinyTruncatedGroup, 
-        subgroup: &TruncatedSubgroup,
-        complement: &mut BTreeSet<ShortFreeGroupTerm>,
-        recursion_depth: usize) -> bool {
-    
-    
-    if contains_identity(&subgroup) { 
-        return false;
+/// Returns a set as `extend` does with the given `normal`, found by solving
+/// the `OrderEncoding` of `elements`. Increases `conflicts` by the number of
+/// conflicts of the SAT solver. Once `stop` has returned `true`, callers have
+/// to ignore `Ok(None)`, as for `extend_to_right_order`.
+pub (super) fn extend_by_sat(
+        elements: BTreeSet<ShortFreeGroupTerm>,
+        conflicts: &mut usize,
+        resources: &Resources,
+        stop: &dyn Fn() -> bool,
+        normal: bool) -> Result<Option<BTreeSet<ShortFreeGroupTerm>>, Limit> {
+    if elements.is_empty() {
+        return Ok(Some(elements));
+    }
+    if literals(&elements).is_none() {
+        return Ok(None);
     }
-    if contains_all_terms_or_inverses(&ambient_group, &subgroup) { 
-        return true;
+    let encoding = OrderEncoding::new(&elements, normal);
+    match encoding.formula.solve_until(conflicts, &|| stop() || resources.check().is_err()) {
+        Ok(model) => Ok(model.map(|model| encoding.decode(&model))),
+        Err(Interrupted) => {
+            resources.check()?;
+            Ok(None)
+        }
     }
+}
 
-    // let complement = strong_complement(&subgroup, &ambient_group);
-    // let minimal = complement.iter().min_by_key(|x| x.len()).unwrap();
-    let mut minimal: ShortFreeGroupTerm;
-    
-    let mut complement_iter = complement.iter();
-    minimal = *complement_iter.next().unwrap();
-    minimal = minimal.clone();
-    
+fn contains_all_terms_or_inverses(
+        ambient_group: &TinyTruncatedGroup, 
+        subgroup: &TruncatedSubgroup) -> bool {
+    let mut missing = ambient_group.elements_except_identity();
+    missing.difference_with(&subgroup.elements);
+    missing.iter().all(|x| subgroup.elements.contains(ambient_group.ball.inverse(x)))
+}
 
-    let mut new_subgroup = TruncatedSubgroup::new(subgroup.elements.clone(), ambient_group.generators.clone(), true, true);
-    let newly_added = new_subgroup.insert(minimal);
+fn contains_identity(subgroup: &TruncatedSubgroup) -> bool {
+    subgroup.elements.contains(subgroup.ball.identity())
+}
 
-    for t in &newly_added {
-        complement.remove(&t);
-        complement.remove(&t.inverse());
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::options::{CancellationToken, Limits};
+    use certificate_checker::check_refutation;
 
-    if extends_helper(&ambient_group, &new_subgroup, complement, recursion_depth + 1) {
-        return true;
+    fn set(elements: &[&str]) -> BTreeSet<ShortFreeGroupTerm> {
+        elements.iter().map(|s| ShortFreeGroupTerm::from(*s)).collect()
     }
 
-    for t in &newly_added {
-        complement.insert(*t);
-        complement.insert(t.inverse());
+    fn search(elements: &[&str], options: &Options, statistics: &mut Statistics, normal: bool) -> Option<BTreeSet<ShortFreeGroupTerm>> {
+        extend(set(elements), options, statistics, &Resources::new(options, &()), &|| false, normal).unwrap()
     }
 
-    let mut new_subgroup = TruncatedSubgroup::new(subgroup.elements.clone(), ambient_group.generators.clone(), true, true);
-    let newly_added = new_subgroup.insert(minimal.inverse());
+    #[test]
+    fn test_sat_agrees_with_search() {
+        let sets = [vec!["x", "X"], vec!["x", "y"], vec!["xY", "yZ", "zX"], vec!["xy", "YX"], vec!["x", "yXY"],
+                    vec!["xx", "xy", "yX"], vec!["xyX", "Y"]];
+        for elements in &sets {
+            for normal in &[false, true] {
+                let by_search = search(elements, &Options::default(), &mut Statistics::default(), *normal);
+                let by_sat = extend_by_sat(set(elements), &mut 0, &Resources::new(&Options::default(), &()), &|| false, *normal).unwrap();
+                assert_eq!(by_search.is_some(), by_sat.is_some(), "{:?} with normal {}", elements, normal);
+                if let Some(negative_cone) = by_sat {
+                    assert!(set(elements).is_subset(&negative_cone));
+                    let subgroup = TruncatedSubgroup::new(negative_cone.clone(), literals(&negative_cone).unwrap(), false, false, *normal);
+                    assert_eq!(negative_cone, subgroup.terms());
+                }
+            }
+        }
+    }
 
-    for t in &newly_added {
-        complement.remove(&t);
-        complement.remove(&t.inverse());
+    #[test]
+    fn test_heuristics_agree() {
+        let sets = [vec!["x", "X"], vec!["xY", "yZ", "zX"], vec!["x", "yXY"], vec!["xx", "xy", "yX"], vec!["xyX", "Y"]];
+        for heuristic in &[Heuristic::First, Heuristic::LargestClosure, Heuristic::MostFrequent] {
+            for (propagate, restarts) in &[(false, None), (true, None), (true, Some(1)), (false, Some(2))] {
+                let options = Options { heuristic: *heuristic, propagate: *propagate, restarts: *restarts, ..Options::default() };
+                for elements in &sets {
+                    for normal in &[false, true] {
+                        let expected = search(elements, &Options::default(), &mut Statistics::default(), *normal);
+                        let mut statistics = Statistics::default();
+                        let result = search(elements, &options, &mut statistics, *normal);
+                        assert_eq!(expected.is_some(), result.is_some(), "{:?} with {:?}", elements, options);
+                        if let Some(negative_cone) = result {
+                            assert!(set(elements).is_subset(&negative_cone));
+                            let ambient_group = TinyTruncatedGroup::new(literals(&set(elements)).unwrap());
+                            let ball = ambient_group.ball.clone();
+                            assert!(contains_all_terms_or_inverses(&ambient_group,
+                                &TruncatedSubgroup::within(ball.clone(), ball.set(&negative_cone), false, true, *normal)));
+                        }
+                        if restarts == &Some(1) && statistics.search_nodes > 1 {
+                            assert!(statistics.restarts > 0);
+                        }
+                    }
+                }
+            }
+        }
     }
 
-    if extends_helper(&ambient_group, &new_subgroup, complement, recursion_depth + 1) {
-        return true;
+    #[test]
+    fn test_refutations_check() {
+        let sets = [vec!["x", "X"], vec!["xY", "yZ", "zX"], vec!["x", "yXY"], vec!["xx", "xy", "yX"], vec!["xyX", "Y"], vec!["e", "x"]];
+        for heuristic in &[Heuristic::First, Heuristic::LargestClosure] {
+            for propagate in &[false, true] {
+                let options = Options { heuristic: *heuristic, propagate: *propagate, ..Options::default() };
+                let resources = Resources::new(&options, &());
+                for elements in &sets {
+                    for normal in &[false, true] {
+                        let decision = refute(set(elements), &options, &mut 0, &resources, *normal).unwrap();
+                        match (decision, search(elements, &options, &mut Statistics::default(), *normal)) {
+                            (Decision::Refuted(refutation), None) =>
+                                assert_eq!(Ok(()), check_refutation(&set(elements), &refutation, *normal)),
+                            (Decision::Extends(negative_cone), Some(expected)) => assert_eq!(expected, negative_cone),
+                            (decision, _) => panic!("{:?} with {:?} gives {:?}", elements, options, decision)
+                        };
+                    }
+                }
+            }
+        }
     }
 
-    for t in &newly_added {
-        complement.insert(*t);
-        complement.insert(t.inverse());
+    #[test]
+    fn test_limits() {
+        let elements = set(&["xx", "xy", "yX"]);
+        let options = Options { limits: Limits { search_nodes: Some(2), ..Limits::default() }, ..Options::default() };
+        let mut statistics = Statistics::default();
+        let result = extend(elements.clone(), &options, &mut statistics, &Resources::new(&options, &()), &|| false, false);
+        assert_eq!(Err(Limit::SearchNodes), result);
+        assert_eq!(3, statistics.search_nodes);
+
+        let token = CancellationToken::new();
+        let options = Options { cancellation: Some(token.clone()), ..Options::default() };
+        token.cancel();
+        for normal in &[false, true] {
+            let resources = Resources::new(&options, &());
+            assert_eq!(Err(Limit::Cancelled), extend(elements.clone(), &options, &mut Statistics::default(), &resources, &|| false, *normal));
+            assert_eq!(Err(Limit::Cancelled), extend_by_sat(elements.clone(), &mut 0, &resources, &|| false, *normal));
+        }
+
+        // stopping is not hitting a limit
+        assert_eq!(Ok(None), extend(elements, &Options::default(), &mut Statistics::default(), &Resources::new(&Options::default(), &()), &|| true, false));
     }
-    return false;
-}
 
-fn contains_all_terms_or_inverses(
-        ambient_group: &TinyTruncatedGroup, 
-        subgroup: &TruncatedSubgroup) -> bool {
-    for x in &ambient_group.elements_except_identity() {
-        if !subgroup.elements.contains(x) && !subgroup.elements.contains(&x.inverse()) {
-            return false;
+    #[test]
+    fn test_search_rolls_back() {
+        for propagate in &[false, true] {
+            let options = Options { propagate: *propagate, ..Options::default() };
+            let resources = Resources::new(&options, &());
+            for elements in &[vec!["xx", "xy", "yX"], vec!["xY", "yZ", "zX"], vec!["xyX", "Y"]] {
+                let elements = set(elements);
+                let ball = resources.ball(&literals(&elements).unwrap());
+                let ambient_group = TinyTruncatedGroup::within(ball.clone());
+                let mut subgroup = TruncatedSubgroup::within(ball.clone(), ball.set(&elements), false, true, false);
+                let mut complement = Complement { elements: ambient_group.elements_except_identity(), trail: Vec::new() };
+                complement.remove_with_inverses(&ball, &subgroup.elements.iter().collect::<Vec<_>>());
+                let (before, complement_before) = (subgroup.elements.clone(), complement.elements.clone());
+                let mut nodes = 0;
+                let mut search = Search { ambient_group: &ambient_group, elements: &elements, options: &options, normal: false,
+                                          certify: false, nodes: &mut nodes, resources: &resources, stop: &|| false, budget: None, random: None };
+                search.run(&mut subgroup, &mut complement);
+                assert_eq!(before, subgroup.elements);
+                assert_eq!(complement_before, complement.elements);
+            }
         }
     }
-    return true;
-}
 
-fn contains_identity(subgroup: &TruncatedSubgroup) -> bool {
-    return subgroup.elements.contains(&ShortFreeGroupTerm::new(None, None, None));
+    #[test]
+    fn test_balls_are_shared() {
+        let options = Options::default();
+        let resources = Resources::new(&options, &());
+        let ball = resources.ball(&literals(&set(&["xY"])).unwrap());
+        assert!(std::sync::Arc::ptr_eq(&ball, &resources.ball(&literals(&set(&["Xy", "yx"])).unwrap())));
+        assert!(!std::sync::Arc::ptr_eq(&ball, &resources.ball(&literals(&set(&["xz"])).unwrap())));
+    }
+
+    #[test]
+    fn test_encoding_size() {
+        // with the letters x and X, the ball is x, xx, xxx and their inverses
+        let encoding = OrderEncoding::new(&set(&["x"]), false);
+        assert_eq!(6, encoding.ball.len());
+        assert_eq!(Some(set(&["x", "xx", "xxx"])), encoding.formula.solve(&mut 0).map(|model| encoding.decode(&model)));
+    }
 }
@@ -1,87 +1,641 @@
 use l_group_formulas::formula::LGroupFormula;
-use std::collections::BTreeSet;
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::free_group_term::FreeGroupTerm;
+use l_group_formulas::literal::Literal;
+use std::collections::{BTreeMap, BTreeSet};
 use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
 use l_group_cnf::three_cnf::ThreeCNF;
-use extend_to_right_order::extend_to_right_order;
+use extend_to_right_order::{extend_to_right_order, extend_to_bi_order, extend_by_sat, refute, Decision, OrderEncoding};
 use l_group_formulas::Term;
-use split_at_meets::split_at_meets;
+use l_group_formulas::parsing_error::ParsingError;
+use l_group_formulas::mv_formula::MVFormula;
+use certificate_checker::certificate::{Certificate, Meetand};
+use split_at_meets::{split_at_meets, split_at_meets_within};
+use resources::Resources;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, info_span};
 
 mod extend_to_right_order;
+mod linear_program;
 mod split_at_meets;
+mod resources;
 
-/// Returns whether an `LGroupFormula` holds in all l-groups.
-pub fn is_valid(eq: LGroupFormula) -> bool {
-    let mut meetands: BTreeSet<BTreeSet<ShortFreeGroupTerm>> = BTreeSet::new();
-    match eq {
-        LGroupFormula::LGroupInequation(lhs, rhs) => {
-            let split = split_at_meets(rhs.clone() * lhs.inverse());
-            println!("Split up the inequation e <= {} to form {} meetands.", (rhs.clone() * lhs.inverse()).to_string(), split.len());
-            for x in split {
-                println!("Computing the short normal form of {}", x.to_string());
-                let three_cnf = ThreeCNF::from(x);
-                println!("The three-normal form is {}.\n", three_cnf.to_string());
-
-                if three_cnf.meetands.len() == 0 {
-                    return false
-                }
+/// Validity in abelian l-groups, with countermodels in the rationals.
+pub mod abelian;
+use abelian::{AbelianValidityResult, AbelianCountermodel};
+
+/// Validity in MV-algebras, with countermodels in the rational unit interval.
+pub mod mv;
+use mv::MVValidityResult;
+
+/// Validity in distributive l-monoids, with countermodels on finite chains.
+pub mod monoid;
+use monoid::{MonoidComparison, MonoidValidityResult, InverseError};
+
+/// Counterexamples in the l-group of order-preserving bijections of the rationals.
+pub mod counterexample;
+pub mod piecewise_linear;
+
+pub mod validity_result;
+use validity_result::{ValidityResult, Statistics, Direction, Limit};
+
+pub mod options;
+use options::{Backend, Options};
+
+/// A CDCL SAT solver, with export to the DIMACS format.
+pub mod sat;
+
+/// Progress reports of checks, which may abort them.
+pub mod observer;
+use observer::Observer;
+
+#[cfg(feature = "parallel")]
+mod parallel;
+
+/// A witness for an `LGroupFormula` failing in some l-group.
+/// 
+/// `meetand` is one of the terms obtained by splitting the formula at meets,
+/// such that `e <= meetand` is not valid. The witness for this is `clause`, a
+/// meetand of `ThreeCNF::from(meetand)`, together with `negative_cone`. 
+/// The latter contains `clause`, does not contain `e`, is closed under
+/// products staying in the ball of radius 3, and contains every element of
+/// the ball of radius 2 or its inverse. That is, it is the set of elements
+/// below `e` of a partial right order, in which every element of `clause`
+/// is below `e`. For `is_valid_representable`, it is a partial bi-order,
+/// which need not extend to a bi-order of the free group.
+/// 
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::find_countermodel;
+/// let countermodel = find_countermodel(LGroupFormula::from("xy = yx")).unwrap();
+/// assert!(countermodel.clause.is_subset(&countermodel.negative_cone));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Countermodel {
+    pub meetand:       LGroupTerm,
+    pub clause:        BTreeSet<ShortFreeGroupTerm>,
+    pub negative_cone: BTreeSet<ShortFreeGroupTerm>
+}
+
+/// Checks whether an `LGroupFormula` holds in all l-groups.
+pub fn is_valid(eq: LGroupFormula) -> ValidityResult {
+    is_valid_with(eq, &Options::default())
+}
+
+/// Checks whether an `LGroupFormula` holds in all l-groups, as set in `options`.
+pub fn is_valid_with(eq: LGroupFormula, options: &Options) -> ValidityResult {
+    is_valid_observed(eq, options, &())
+}
+
+/// Checks whether an `LGroupFormula` holds in all l-groups, as set in `options`,
+/// telling `observer` about the progress of the check.
+pub fn is_valid_observed(eq: LGroupFormula, options: &Options, observer: &dyn Observer) -> ValidityResult {
+    check(eq, Order::Right, options, observer)
+}
+
+/// Checks whether an `LGroupFormula` holds in all representable l-groups,
+/// i.e., in all totally ordered groups.
+///
+/// This works as `is_valid` does, except that the clauses have to extend to
+/// bi-orders, so the `negative_cone` of a countermodel is additionally closed
+/// under conjugation, as far as the conjugates stay in the ball of radius 3.
+///
+/// Only `ValidityResult::Valid` is a proof: a clause which does not extend to
+/// a partial bi-order on the ball of radius 3 does not extend to a bi-order.
+/// Unlike for right orders, it is not known that every such partial bi-order
+/// extends to a bi-order of the free group, so `ValidityResult::Invalid` is
+/// only a candidate answer, whose countermodel may not come from a totally
+/// ordered group.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::{is_valid, is_valid_representable};
+/// let formula = LGroupFormula::from("e <= x v yXY");
+/// assert!(is_valid(formula.clone()).is_invalid());
+/// assert!(is_valid_representable(formula).is_valid());
+/// ```
+pub fn is_valid_representable(eq: LGroupFormula) -> ValidityResult {
+    is_valid_representable_with(eq, &Options::default())
+}
+
+/// Checks whether an `LGroupFormula` holds in all representable l-groups, as
+/// set in `options`.
+pub fn is_valid_representable_with(eq: LGroupFormula, options: &Options) -> ValidityResult {
+    is_valid_representable_observed(eq, options, &())
+}
+
+/// Checks whether an `LGroupFormula` holds in all representable l-groups, as
+/// set in `options`, telling `observer` about the progress of the check.
+pub fn is_valid_representable_observed(eq: LGroupFormula, options: &Options, observer: &dyn Observer) -> ValidityResult {
+    check(eq, Order::Bi, options, observer)
+}
+
+/// The orders of free groups whose existence refutes formulas in a variety.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Order {
+    Right,
+    Bi
+}
+
+impl Order {
+    /// Returns the negative cone of an order of `self` extending `clause`, as
+    /// `extend_to_right_order` does. Once `stop` has returned `true`, callers
+    /// have to ignore `Ok(None)`, which does not say that `clause` does not extend.
+    fn extend(
+            self,
+            clause: BTreeSet<ShortFreeGroupTerm>,
+            options: &Options,
+            statistics: &mut Statistics,
+            resources: &Resources,
+            stop: &dyn Fn() -> bool) -> Result<Option<BTreeSet<ShortFreeGroupTerm>>, Limit> {
+        let _span = info_span!("extend", size = clause.len()).entered();
+        resources.observe(resources.observer.clause(&clause))?;
+        info!("Checking whether {} extends to a {}.", join(&clause), self);
+        let (search_nodes, conflicts) = (statistics.search_nodes, statistics.conflicts);
+        let result = match (options.backend, self) {
+            (Backend::Search, Order::Right) => extend_to_right_order(clause, options, statistics, resources, stop),
+            (Backend::Search, Order::Bi)    => extend_to_bi_order(clause, options, statistics, resources, stop),
+            (Backend::Sat, _)               => extend_by_sat(clause, &mut statistics.conflicts, resources, stop, self == Order::Bi)
+        };
+        debug!(search_nodes = statistics.search_nodes - search_nodes,
+               conflicts = statistics.conflicts - conflicts,
+               extends = ?result.as_ref().map(|negative_cone| negative_cone.is_some()));
+        result
+    }
+}
+
+impl std::fmt::Display for Order {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Order::Right => write!(f, "right order"),
+            Order::Bi    => write!(f, "bi-order")
+        }
+    }
+}
+
+fn check(eq: LGroupFormula, order: Order, options: &Options, observer: &dyn Observer) -> ValidityResult {
+    let _span = info_span!("check", %order).entered();
+    #[cfg(not(feature = "parallel"))]
+    let result = check_sequential(eq, order, options, observer);
+    #[cfg(feature = "parallel")]
+    let result = parallel::check(eq, order, options, observer);
+    debug!(statistics = ?result.statistics(), "The formula is {}.", result);
+    result
+}
+
+/// Checks the clauses one after another, computing the `ThreeCNF` of each
+/// meetand only once the clauses of the previous ones do not extend.
+#[cfg_attr(feature = "parallel", allow(dead_code))]
+fn check_sequential(eq: LGroupFormula, order: Order, options: &Options, observer: &dyn Observer) -> ValidityResult {
+    let mut statistics = Statistics::default();
+    let resources = Resources::new(options, observer);
+    match find_extension(eq, order, options, &resources, &mut statistics) {
+        Ok(Some((direction, countermodel))) => ValidityResult::Invalid { direction, countermodel, statistics },
+        Ok(None) => ValidityResult::Valid(statistics),
+        Err(limit) => ValidityResult::Unknown { limit, statistics }
+    }
+}
 
-                for meetand in three_cnf.meetands {
-                    meetands.insert(meetand);
+/// Returns the first clause of `eq` extending to `order`, as a countermodel of
+/// the inequation in the returned direction, or the limit hit on the way.
+fn find_extension(
+        eq: LGroupFormula,
+        order: Order,
+        options: &Options,
+        resources: &Resources,
+        statistics: &mut Statistics) -> Result<Option<(Direction, Countermodel)>, Limit> {
+    // clauses which are already known not to extend to an order
+    let mut checked = BTreeSet::new();
+    for (direction, term) in inequations(eq) {
+        let split = info_span!("split", ?direction).in_scope(|| split_at_meets_within(term.clone(), resources))?;
+        info!(meetands = split.len(), "Split up the inequation e <= {} to form {} meetands.", term, split.len());
+        statistics.meetands += split.len();
+        check_meetands(statistics.meetands, resources)?;
+        resources.observe(resources.observer.split(direction, split.len()))?;
+
+        for meetand in split {
+            let clauses = clauses_within(&meetand, resources)?;
+
+            for clause in clauses {
+                if checked.contains(&clause) {
+                    continue;
                 }
+                statistics.clauses += 1;
+                match order.extend(clause.clone(), options, statistics, resources, &|| false)? {
+                    Some(negative_cone) => return Ok(Some((direction, Countermodel { meetand, clause, negative_cone }))),
+                    None => { checked.insert(clause); }
+                };
             }
-        },
-        LGroupFormula::LGroupEquation(lhs, rhs) => {
-            let split1 = split_at_meets(rhs.clone() * lhs.inverse());
-            let split2 = split_at_meets(lhs.clone() * rhs.inverse());
-            println!("Split up the two inequations into {} meetands.", split1.len() + split2.len());
-            for x in split1.union(&split2) {
-                let three_cnf = ThreeCNF::from(x.clone());
-                println!("Constructed the formula {}.", three_cnf.to_string());
-                for meetand in three_cnf.meetands {
-                    meetands.insert(meetand);
+        }
+    }
+    Ok(None)
+}
+
+/// Returns an error if there are more than `resources.limits.meetands` meetands.
+fn check_meetands(meetands: usize, resources: &Resources) -> Result<(), Limit> {
+    match resources.limits.meetands {
+        Some(limit) if meetands > limit => Err(Limit::Meetands),
+        _ => Ok(())
+    }
+}
+
+/// The elements of `clause`, separated by commas.
+fn join(clause: &BTreeSet<ShortFreeGroupTerm>) -> String {
+    clause.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(", ")
+}
+
+/// Checks whether an `LGroupFormula` holds in all abelian l-groups.
+///
+/// These are exactly the formulas holding in the rationals. The inequations
+/// are split at meets and brought into their `CNF`, and each clause is
+/// checked by an exact linear program on the exponent vectors of its words.
+pub fn is_valid_abelian(eq: LGroupFormula) -> AbelianValidityResult {
+    check_abelian(eq, &[])
+}
+
+/// Checks whether an `LGroupFormula` holds in all abelian l-groups, whenever
+/// all of `positive` are positive.
+fn check_abelian(eq: LGroupFormula, positive: &[FreeGroupTerm]) -> AbelianValidityResult {
+    let (lhs, rhs) = match &eq {
+        LGroupFormula::LGroupEquation(lhs, rhs) | LGroupFormula::LGroupInequation(lhs, rhs) => (lhs.clone(), rhs.clone())
+    };
+    let variables: BTreeSet<_> = positive.iter().map(|p| LGroupTerm::Atom(p.clone()))
+        .chain(vec![lhs.clone(), rhs.clone()])
+        .flat_map(|term| term.variables())
+        .collect();
+    for (direction, term) in inequations(eq) {
+        if let Some(assignment) = abelian::negative_assignment(&term, positive, &variables) {
+            let lhs_value = abelian::evaluate(&lhs, &assignment);
+            let rhs_value = abelian::evaluate(&rhs, &assignment);
+            return AbelianValidityResult::Invalid {
+                direction,
+                countermodel: AbelianCountermodel { assignment, lhs_value, rhs_value }
+            };
+        }
+    }
+    AbelianValidityResult::Valid
+}
+
+/// Checks whether an `MVFormula` holds in all MV-algebras, i.e., whether its
+/// translation holds in all abelian l-groups.
+///
+/// Rather than `formula`, this checks `bounded`, where it suffices that the
+/// variables lie strictly between `e` and the unit, as the terms are continuous.
+pub fn is_valid_mv(formula: MVFormula) -> MVValidityResult {
+    let unit = FreeGroupTerm::from(formula.unit);
+    let mut positive = vec![unit.clone()];
+    for x in formula.variables.keys() {
+        positive.push(FreeGroupTerm::from(*x));
+        positive.push(unit.clone() * FreeGroupTerm::from(x.inverse()));
+    }
+    match check_abelian(formula.bounded.clone(), &positive) {
+        AbelianValidityResult::Valid => MVValidityResult::Valid,
+        AbelianValidityResult::Invalid { direction, countermodel } => MVValidityResult::Invalid {
+            direction,
+            countermodel: mv::scaled(&formula, &countermodel)
+        }
+    }
+}
+
+/// Checks whether an inverse-free `LGroupFormula` holds in all distributive
+/// l-monoids, i.e., for all order-preserving maps of chains, or returns an
+/// error if the formula has inverses.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::is_valid_monoid;
+/// assert!(is_valid_monoid(LGroupFormula::from("x(y v z)w = xyw v xzw")).unwrap().is_valid());
+/// assert!(is_valid_monoid(LGroupFormula::from("xy = yx")).unwrap().is_invalid());
+/// assert!(is_valid_monoid(LGroupFormula::from("X = e")).is_err());
+/// ```
+pub fn is_valid_monoid(eq: LGroupFormula) -> Result<MonoidValidityResult, InverseError> {
+    is_valid_monoid_with(eq, &Options::default())
+}
+
+/// Checks whether an inverse-free `LGroupFormula` holds in all distributive
+/// l-monoids, as set in `options`, or returns an error if the formula has
+/// inverses. Only the limits and the cancellation in `options` apply.
+pub fn is_valid_monoid_with(eq: LGroupFormula, options: &Options) -> Result<MonoidValidityResult, InverseError> {
+    let (lhs, rhs) = match &eq {
+        LGroupFormula::LGroupEquation(lhs, rhs) | LGroupFormula::LGroupInequation(lhs, rhs) => (lhs, rhs)
+    };
+    if let Some(literal) = monoid::inverted_literal(lhs).or_else(|| monoid::inverted_literal(rhs)) {
+        return Err(InverseError { literal });
+    }
+    let inequations = match &eq {
+        LGroupFormula::LGroupInequation(_, _) => vec![(Direction::LeftBelowRight, lhs, rhs)],
+        LGroupFormula::LGroupEquation(_, _) => vec![
+            (Direction::LeftBelowRight, lhs, rhs),
+            (Direction::RightBelowLeft, rhs, lhs)
+        ]
+    };
+    let resources = Resources::new(options, &());
+    for (direction, smaller, larger) in inequations {
+        match monoid::find_countermodel(smaller, larger, &resources) {
+            Ok(Some(mut countermodel)) => {
+                if direction == Direction::RightBelowLeft {
+                    std::mem::swap(&mut countermodel.lhs_value, &mut countermodel.rhs_value);
                 }
+                return Ok(MonoidValidityResult::Invalid { direction, countermodel });
+            },
+            Ok(None) => {},
+            Err(limit) => return Ok(MonoidValidityResult::Unknown { limit })
+        }
+    }
+    Ok(MonoidValidityResult::Valid)
+}
+
+/// Checks an inverse-free `LGroupFormula` both in all distributive l-monoids
+/// and in all l-groups, each as set in `options`, to report whether it holds
+/// in l-groups, but fails in l-monoids. Returns an error if the formula has
+/// inverses.
+///
+/// Colacito, Galatos, Metcalfe, and Santschi prove in "From distributive
+/// ℓ-monoids to ℓ-groups, and back again" (J. Algebra, 2022) that "an
+/// inverse-free equation is valid in the variety LG of lattice-ordered groups
+/// (ℓ-groups) if and only if it is valid in the variety DLM of distributive
+/// lattice-ordered monoids (distributive ℓ-monoids)", so such a report points
+/// to a bug in one of the two procedures.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::compare_monoid_and_l_groups;
+/// use l_group_validity::options::Options;
+/// let comparison = compare_monoid_and_l_groups(LGroupFormula::from("xy ^ yx <= xx v yy"), &Options::default()).unwrap();
+/// assert!(comparison.monoid.is_valid() && comparison.l_group.is_valid());
+/// assert!(!comparison.holds_only_in_l_groups());
+/// ```
+pub fn compare_monoid_and_l_groups(eq: LGroupFormula, options: &Options) -> Result<MonoidComparison, InverseError> {
+    let monoid = is_valid_monoid_with(eq.clone(), options)?;
+    let l_group = is_valid_with(eq, options);
+    Ok(MonoidComparison { monoid, l_group })
+}
+
+/// Returns the terms `t` such that the `LGroupFormula` holds if, and only if,
+/// `e <= t` holds for all of them, together with the `Direction` they stand for.
+fn inequations(eq: LGroupFormula) -> Vec<(Direction, LGroupTerm)> {
+    match eq {
+        LGroupFormula::LGroupInequation(lhs, rhs) => vec![
+            (Direction::LeftBelowRight, rhs * lhs.inverse())
+        ],
+        LGroupFormula::LGroupEquation(lhs, rhs) => vec![
+            (Direction::LeftBelowRight, rhs.clone() * lhs.inverse()),
+            (Direction::RightBelowLeft, lhs * rhs.inverse())
+        ]
+    }
+}
+
+/// Returns a `Countermodel` if the `LGroupFormula` does not hold in all l-groups,
+/// and `None` if it does.
+pub fn find_countermodel(eq: LGroupFormula) -> Option<Countermodel> {
+    match is_valid(eq) {
+        ValidityResult::Invalid { countermodel, .. } => Some(countermodel),
+        _ => None
+    }
+}
+
+/// Returns a `Certificate` of an `LGroupFormula` holding in all l-groups,
+/// which `certificate_checker::check` accepts, or `None` if it does not hold.
+///
+/// The refutation of each clause is the tree of the choices the search makes
+/// with the default `Options`, where every subgroup containing `e` comes with
+/// the products giving `e`. The text format of certificates cannot write the
+/// variables `e` and `v`, `v1`, ..., so they are renamed first, as
+/// `SymbolTable` does. Parsed formulas never have them.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::certify;
+/// use certificate_checker::check;
+/// let certificate = certify(LGroupFormula::from("e <= xx v yy v XY")).unwrap();
+/// assert_eq!(Ok(()), check(&certificate));
+/// assert!(certify(LGroupFormula::from("xy = yx")).is_none());
+/// ```
+pub fn certify(eq: LGroupFormula) -> Option<Certificate> {
+    certify_with(eq, &Options::default()).map(|certificate| certificate.expect("There are no limits by default."))
+}
+
+/// Returns a `Certificate` as `certify` does, searching as set in `options`,
+/// or the limit hit on the way. The backend is always `Backend::Search`.
+pub fn certify_with(eq: LGroupFormula, options: &Options) -> Option<Result<Certificate, Limit>> {
+    certificate_of(check_certified(eq, Order::Right, options))
+}
+
+/// Returns a `Certificate` of an `LGroupFormula` holding in all representable
+/// l-groups, as `certify` does, whose refutations may conjugate.
+pub fn certify_representable(eq: LGroupFormula) -> Option<Certificate> {
+    certify_representable_with(eq, &Options::default()).map(|certificate| certificate.expect("There are no limits by default."))
+}
+
+/// Returns a `Certificate` as `certify_representable` does, searching as set
+/// in `options`, or the limit hit on the way.
+pub fn certify_representable_with(eq: LGroupFormula, options: &Options) -> Option<Result<Certificate, Limit>> {
+    certificate_of(check_certified(eq, Order::Bi, options))
+}
+
+/// Checks whether an `LGroupFormula` holds in all l-groups as `is_valid_with`
+/// does, and returns a `Certificate` as `certify_with` does if it holds, both
+/// from the same search. The backend is always `Backend::Search`, without
+/// restarts. As for `certify`, the variables `e` and `v`, `v1`, ... are
+/// renamed first, also in the countermodel of an invalid formula.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::is_valid_certified;
+/// use l_group_validity::options::Options;
+/// let (result, certificate) = is_valid_certified(LGroupFormula::from("e <= x v X"), &Options::default());
+/// assert!(result.is_valid());
+/// assert_eq!(Ok(()), certificate_checker::check(&certificate.unwrap()));
+/// ```
+pub fn is_valid_certified(eq: LGroupFormula, options: &Options) -> (ValidityResult, Option<Certificate>) {
+    check_certified(eq, Order::Right, options)
+}
+
+fn certificate_of((result, certificate): (ValidityResult, Option<Certificate>)) -> Option<Result<Certificate, Limit>> {
+    match (result, certificate) {
+        (ValidityResult::Unknown { limit, .. }, _) => Some(Err(limit)),
+        (_, certificate) => certificate.map(Ok)
+    }
+}
+
+fn check_certified(eq: LGroupFormula, order: Order, options: &Options) -> (ValidityResult, Option<Certificate>) {
+    let _span = info_span!("certify", %order).entered();
+    let eq = writable(eq);
+    let mut statistics = Statistics::default();
+    let resources = Resources::new(options, &());
+    let mut certificate = Certificate {
+        formula:       eq.clone(),
+        representable: order == Order::Bi,
+        meetands:      Vec::new(),
+        refutations:   BTreeMap::new()
+    };
+    match find_refutations(eq, order, options, &resources, &mut statistics, &mut certificate) {
+        Ok(Some((direction, countermodel))) => (ValidityResult::Invalid { direction, countermodel, statistics }, None),
+        Ok(None) => (ValidityResult::Valid(statistics), Some(certificate)),
+        Err(limit) => (ValidityResult::Unknown { limit, statistics }, None)
+    }
+}
+
+/// Checks the clauses of `eq` as `find_extension` does, but by `refute`, and
+/// adds the meetands and refutations to `certificate` on the way.
+fn find_refutations(
+        eq: LGroupFormula,
+        order: Order,
+        options: &Options,
+        resources: &Resources,
+        statistics: &mut Statistics,
+        certificate: &mut Certificate) -> Result<Option<(Direction, Countermodel)>, Limit> {
+    for (direction, term) in inequations(eq) {
+        let split = split_at_meets_within(term, resources)?;
+        statistics.meetands += split.len();
+        check_meetands(statistics.meetands, resources)?;
+        for meetand in split {
+            let clauses = clauses_within(&meetand, resources)?;
+            for clause in &clauses {
+                if certificate.refutations.contains_key(clause) {
+                    continue;
+                }
+                statistics.clauses += 1;
+                info!("Refuting that {} extends to a {}.", join(clause), order);
+                match refute(clause.clone(), options, &mut statistics.search_nodes, resources, order == Order::Bi)? {
+                    Decision::Refuted(refutation) => { certificate.refutations.insert(clause.clone(), refutation); },
+                    Decision::Extends(negative_cone) => {
+                        let countermodel = Countermodel { meetand, clause: clause.clone(), negative_cone };
+                        return Ok(Some((direction, countermodel)));
+                    }
+                };
             }
+            certificate.meetands.push(Meetand { term: meetand, clauses: clauses.into_iter().collect() });
         }
+    }
+    Ok(None)
+}
+
+/// Renames the variables `e` and `v`, `v1`, ..., which `Display` prints as
+/// the identity and joins, as `SymbolTable` does, i.e., to fresh variables
+/// `e1`, `e2`, ... and `w1`, `w2`, ..., respectively.
+fn writable(eq: LGroupFormula) -> LGroupFormula {
+    let (lhs, rhs) = match &eq {
+        LGroupFormula::LGroupEquation(lhs, rhs) | LGroupFormula::LGroupInequation(lhs, rhs) => (lhs, rhs)
     };
+    let mut used = lhs.variables();
+    used.append(&mut rhs.variables());
+    let reserved: Vec<Literal> = used.iter().copied().filter(|x| *x == Literal::from('e') || x.character == 'v').collect();
+    if reserved.is_empty() {
+        return eq;
+    }
+    let mut renaming = BTreeMap::new();
+    for x in reserved {
+        let character = match x.character {
+            'v' => 'w',
+            character => character
+        };
+        let fresh = (1 ..).map(|id| Literal::new(character, id, false)).find(|y| !used.contains(y)).unwrap();
+        used.insert(fresh);
+        renaming.insert(x, LGroupTerm::from(fresh));
+    }
+    match eq {
+        LGroupFormula::LGroupEquation(lhs, rhs) =>
+            LGroupFormula::LGroupEquation(lhs.substituted(&renaming), rhs.substituted(&renaming)),
+        LGroupFormula::LGroupInequation(lhs, rhs) =>
+            LGroupFormula::LGroupInequation(lhs.substituted(&renaming), rhs.substituted(&renaming))
+    }
+}
 
-    if meetands.len() == 0 {
-        return false;
+/// Returns the clauses of the `ThreeCNF` of `meetand`, such that `e <= meetand`
+/// is valid if, and only if, none of them extends to a right order. Unlike
+/// `ThreeCNF::from`, this keeps the clauses consisting of a single atom.
+fn clauses(meetand: &LGroupTerm) -> BTreeSet<BTreeSet<ShortFreeGroupTerm>> {
+    info!("Computing the short normal form of {}", meetand);
+    let three_cnf = ThreeCNF::with_atoms(meetand.clone());
+    info!(clauses = three_cnf.meetands.len(), "The three-normal form is {}.", three_cnf);
+    three_cnf.meetands
+}
+
+/// Returns the clauses of `meetand` as `clauses` does, or an error if they
+/// have more than `resources.limits.cnf_size` elements altogether, or if
+/// `resources` has hit another limit before.
+fn clauses_within(meetand: &LGroupTerm, resources: &Resources) -> Result<BTreeSet<BTreeSet<ShortFreeGroupTerm>>, Limit> {
+    resources.check()?;
+    let clauses = clauses(meetand);
+    let size = clauses.iter().map(|clause| clause.len()).sum();
+    match resources.limits.cnf_size {
+        Some(limit) if size > limit => Err(Limit::CnfSize),
+        _ => {
+            resources.observe(resources.observer.three_cnf(meetand, clauses.len(), size))?;
+            Ok(clauses)
+        }
     }
-    
-    println!("Checking all meetands.");
-    for meetand in meetands {
-        // verbosity
-        let mut print_string = String::new();
-        for t in &meetand {
-            print_string.push_str(t.to_string().as_str());
-            print_string.push_str(", ");
+}
+
+/// Writes the question whether each clause of an `LGroupFormula` extends to a
+/// right order, or to a bi-order if `representable` is set, as a file in the
+/// DIMACS format into `directory`, and returns the paths of the files.
+///
+/// The formula holds if, and only if, all of them are unsatisfiable. The
+/// comments of a file list the clause and the element of the ball of radius
+/// 3 that each variable stands for, which is below `e` if it is true.
+pub fn write_dimacs(eq: LGroupFormula, representable: bool, directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut all_clauses = BTreeSet::new();
+    for (_, term) in inequations(eq) {
+        for meetand in split_at_meets(term) {
+            all_clauses.extend(clauses(&meetand));
         }
-        print_string.pop();
-        print_string.pop();
-        println!("Checking whether {} extends to a right order.", print_string);
-        // end verbosity
-       
-        if extend_to_right_order(Box::new(meetand)) {
-            return false;
+    }
+    let mut paths = Vec::new();
+    for (i, clause) in all_clauses.iter().enumerate() {
+        let path = directory.join(format!("clause_{}.cnf", i + 1));
+        let mut file = io::BufWriter::new(std::fs::File::create(&path)?);
+        let elements: Vec<String> = clause.iter().map(|x| x.to_string()).collect();
+        writeln!(file, "c clause {}", elements.join(", "))?;
+        if clause.contains(&ShortFreeGroupTerm::new(None, None, None)) {
+            // no set of elements below `e` contains `e`
+            writeln!(file, "p cnf 0 1\n0")?;
+        } else {
+            let encoding = OrderEncoding::new(clause, representable);
+            for (j, x) in encoding.ball.iter().enumerate() {
+                writeln!(file, "c {} {}", j + 1, x)?;
+            }
+            encoding.formula.write_dimacs(&mut file)?;
         }
+        paths.push(path);
     }
-    return true;
+    Ok(paths)
 }
 
-pub fn is_valid_from_string(s: &str) -> bool {
-    is_valid(LGroupFormula::from(s))
+/// Parses an `LGroupFormula` and checks whether it holds in all l-groups.
+pub fn is_valid_from_string(s: &str) -> Result<ValidityResult, ParsingError> {
+    Ok(is_valid(s.parse()?))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use l_group_formulas::free_group_term::FreeGroupTerm;
+    use num_rational::BigRational;
+    use num_traits::{One, Zero};
+    use options::{CancellationToken, Limits};
+    use std::time::Duration;
     
     fn check_valid(string: &str) {
-        assert_eq!(true, is_valid(LGroupFormula::from(string)));
+        assert!(is_valid(LGroupFormula::from(string)).is_valid());
     }
 
     fn check_invalid(string: &str) {
-        assert_eq!(false, is_valid(LGroupFormula::from(string)));
+        assert!(is_valid(LGroupFormula::from(string)).is_invalid());
     }
 
     #[test]
@@ -131,6 +685,10 @@ mod tests {
     #[test]
     fn test_representable_l_groups() {
         check_invalid("e <= x v yXY");
+        assert!(is_valid_representable(LGroupFormula::from("e <= x v yXY")).is_valid());
+        assert!(is_valid_representable(LGroupFormula::from("x ^ y <= x")).is_valid());
+        assert!(is_valid_representable(LGroupFormula::from("xy = yx")).is_invalid());
+        assert!(is_valid_representable(LGroupFormula::from("x <= y")).is_invalid());
     }
     
     #[test]
@@ -143,6 +701,43 @@ mod tests {
         check_invalid("xyz ^ rst <= xsz v ryt");
     }
         
+    #[test]
+    fn test_mv_algebras() {
+        let is_valid_mv_string = |string: &str| is_valid_mv(string.parse().unwrap()).is_valid();
+        assert!(is_valid_mv_string("x → (y → x)"));
+        assert!(is_valid_mv_string("(x → y) → ((y → z) → (x → z))"));
+        assert!(is_valid_mv_string("((x → y) → y) = ((y → x) → x)"));
+        assert!(is_valid_mv_string("(x → y) ⊕ (y → x)"));
+        assert!(is_valid_mv_string("x ⊙ y <= x"));
+        assert!(!is_valid_mv_string("x ⊕ x = x"));
+        assert!(!is_valid_mv_string("(x → (x → y)) → (x → y)"));
+        match is_valid_mv("x <= x ⊙ x".parse().unwrap()) {
+            MVValidityResult::Invalid { countermodel, .. } => {
+                let x = &countermodel.assignment[&Literal::from('x')];
+                assert!(*x > BigRational::zero() && *x < BigRational::one());
+                assert!(countermodel.lhs_value > countermodel.rhs_value);
+            },
+            MVValidityResult::Valid => panic!("x <= x ⊙ x fails for x = 1/2")
+        };
+    }
+
+    #[test]
+    fn test_l_monoids() {
+        let is_valid_monoid_string = |string| is_valid_monoid(LGroupFormula::from(string)).unwrap().is_valid();
+        assert!(is_valid_monoid_string("xy ^ yx <= xx v yy"));
+        assert!(is_valid_monoid_string("xx ^ yy <= xy v yx"));
+        assert!(!is_valid_monoid_string("x ^ y ^ e <= xy"));
+        for string in ["xy ^ yx <= xx v yy", "x ^ y ^ e <= xy", "xyz ^ rst <= xsz v ryt", "x(y v z) = xy v xz"] {
+            let comparison = compare_monoid_and_l_groups(LGroupFormula::from(string), &Options::default()).unwrap();
+            assert!(!comparison.holds_only_in_l_groups());
+            assert_eq!(comparison.l_group.is_valid(), comparison.monoid.is_valid());
+        }
+        let options = Options { limits: Limits { search_nodes: Some(1), ..Limits::default() }, ..Options::default() };
+        let result = is_valid_monoid_with(LGroupFormula::from("xyz ^ zyx <= xzy v yxz"), &options).unwrap();
+        assert_eq!(MonoidValidityResult::Unknown { limit: Limit::SearchNodes }, result);
+        assert_eq!(Err(InverseError { literal: Literal::new('x', 0, true) }), is_valid_monoid(LGroupFormula::from("X <= y")));
+    }
+
     #[test]
     fn test_pyvalidity_bug() {
         check_valid("e <= xY v yZ v zX");
@@ -157,4 +752,284 @@ mod tests {
     fn test_initial_split_bug() {
         check_invalid("e <= e ^ x");
     }
+
+    #[test]
+    fn test_long_atoms() {
+        check_invalid("e <= xyzw");
+        check_invalid("e = e v xyzw");
+        // the clause of a single long atom is kept, shortened as the others are
+        let expected: BTreeSet<_> = vec!["xyv1", "V1zw"].into_iter().map(ShortFreeGroupTerm::from).collect();
+        assert!(clauses(&LGroupTerm::from("xyzw")).contains(&expected));
+        // also next to other clauses, as for the meetand `xyzw` of this term
+        let clauses = clauses(&LGroupTerm::from("(xyzw ^ x) v (xyzw ^ y)"));
+        let expected: BTreeSet<_> = vec!["xyv2", "V2zw"].into_iter().map(ShortFreeGroupTerm::from).collect();
+        assert_eq!(4, clauses.len());
+        assert!(clauses.contains(&expected));
+    }
+
+    #[test]
+    fn test_countermodel_commutativity() {
+        let countermodel = find_countermodel(LGroupFormula::from("xy = yx")).expect("no countermodel");
+        let cone = &countermodel.negative_cone;
+        assert!(countermodel.clause.is_subset(cone));
+        assert!(!cone.contains(&ShortFreeGroupTerm::new(None, None, None)));
+        for x in cone {
+            for y in cone {
+                let product = FreeGroupTerm::from(*x) * FreeGroupTerm::from(*y);
+                if product.literals.len() <= 3 {
+                    assert!(cone.contains(&ShortFreeGroupTerm::from(product)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sat_backend() {
+        let options = Options { backend: Backend::Sat, ..Options::default() };
+        let valid = ["x ^ (y v z) = (x ^ y) v (x ^ z)", "x(y ^ z)w = xyw ^ xzw", "e <= xx v yy v XY",
+                     "(Xy ^ e) v (Yx ^ e) = e", "e <= xY v yZ v zX", "e=e"];
+        for s in &valid {
+            assert!(is_valid_with(LGroupFormula::from(*s), &options).is_valid(), "{} is valid", s);
+        }
+        let invalid = ["xy = yx", "e <= xx v xy v yX", "e <= x v yXY", "e <= e ^ x", "e = e v xyzw"];
+        for s in &invalid {
+            match is_valid_with(LGroupFormula::from(*s), &options) {
+                ValidityResult::Invalid { countermodel, .. } => {
+                    assert!(countermodel.clause.is_subset(&countermodel.negative_cone));
+                    assert!(!countermodel.negative_cone.contains(&ShortFreeGroupTerm::new(None, None, None)));
+                },
+                _ => panic!("{} is invalid", s)
+            };
+        }
+        assert!(is_valid_representable_with(LGroupFormula::from("e <= x v yXY"), &options).is_valid());
+        assert!(is_valid_representable_with(LGroupFormula::from("(x ^ e)(x ^ e) <= Y(x ^ e)y"), &options).is_invalid());
+    }
+
+    #[test]
+    fn test_limits() {
+        let formula = LGroupFormula::from("x ^ (y v z) = (x ^ y) v (x ^ z)");
+        let unknown = |limits: Limits| match is_valid_with(formula.clone(), &Options { limits, ..Options::default() }) {
+            ValidityResult::Unknown { limit, statistics } => Some((limit, statistics)),
+            _ => None
+        };
+        assert_eq!(None, unknown(Limits::default()));
+        let (limit, statistics) = unknown(Limits { time: Some(Duration::from_secs(0)), ..Limits::default() }).unwrap();
+        assert_eq!(Limit::Time, limit);
+        assert_eq!(Statistics::default(), statistics);
+        assert_eq!(Limit::CnfSize, unknown(Limits { cnf_size: Some(2), ..Limits::default() }).unwrap().0);
+        assert_eq!(Limit::Meetands, unknown(Limits { meetands: Some(2), ..Limits::default() }).unwrap().0);
+        let (limit, statistics) = unknown(Limits { search_nodes: Some(3), ..Limits::default() }).unwrap();
+        assert_eq!(Limit::SearchNodes, limit);
+        assert_eq!(4, statistics.search_nodes);
+        assert!(statistics.clauses > 0);
+
+        for backend in &[Backend::Search, Backend::Sat] {
+            let options = Options { backend: *backend, cancellation: Some(CancellationToken::new()), ..Options::default() };
+            assert!(is_valid_with(formula.clone(), &options).is_valid());
+            options.cancellation.as_ref().unwrap().cancel();
+            assert!(is_valid_with(formula.clone(), &options).is_unknown());
+        }
+    }
+
+    /// Counts the calls of each method, and aborts at the first call of `abort_at`.
+    #[derive(Default)]
+    struct Counting {
+        calls:    std::sync::Mutex<[usize; 4]>,
+        depth:    std::sync::atomic::AtomicUsize,
+        abort_at: Option<usize>
+    }
+
+    impl Counting {
+        fn count(&self, method: usize) -> observer::Control {
+            self.calls.lock().unwrap()[method] += 1;
+            match self.abort_at == Some(method) {
+                true  => observer::Control::Abort,
+                false => observer::Control::Continue
+            }
+        }
+    }
+
+    impl Observer for Counting {
+        fn split(&self, _direction: Direction, _meetands: usize) -> observer::Control {
+            self.count(0)
+        }
+
+        fn three_cnf(&self, _meetand: &LGroupTerm, clauses: usize, size: usize) -> observer::Control {
+            assert!(clauses <= size);
+            self.count(1)
+        }
+
+        fn clause(&self, _clause: &BTreeSet<ShortFreeGroupTerm>) -> observer::Control {
+            self.count(2)
+        }
+
+        fn node(&self, depth: usize, _nodes: usize) -> observer::Control {
+            self.depth.fetch_max(depth, std::sync::atomic::Ordering::Relaxed);
+            self.count(3)
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let formula = LGroupFormula::from("x ^ (y v z) = (x ^ y) v (x ^ z)");
+        let observer = Counting::default();
+        let statistics = *is_valid_observed(formula.clone(), &Options::default(), &observer).statistics();
+        let calls = *observer.calls.lock().unwrap();
+        assert_eq!([2, statistics.meetands, statistics.clauses, statistics.search_nodes], calls);
+        let observer = Counting::default();
+        assert!(is_valid_observed(LGroupFormula::from("e <= xx v xy v yX"), &Options::default(), &observer).is_invalid());
+        assert!(observer.depth.into_inner() > 0);
+
+        for method in 0 .. 4 {
+            let observer = Counting { abort_at: Some(method), ..Counting::default() };
+            match is_valid_observed(formula.clone(), &Options::default(), &observer) {
+                ValidityResult::Unknown { limit, .. } => assert_eq!(Limit::Aborted, limit),
+                result => panic!("The check is {} after aborting.", result)
+            };
+            // in parallel, other threads may call the observer before they see the abort
+            let calls = observer.calls.lock().unwrap()[method];
+            match cfg!(feature = "parallel") {
+                true  => assert!(calls >= 1),
+                false => assert_eq!(1, calls)
+            };
+        }
+    }
+
+    #[test]
+    fn test_write_dimacs() {
+        let directory = std::env::temp_dir().join(format!("l_group_validity_dimacs_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let paths = write_dimacs(LGroupFormula::from("e <= x v X"), false, &directory).unwrap();
+        assert_eq!(1, paths.len());
+        let dimacs = std::fs::read_to_string(&paths[0]).unwrap();
+        assert!(dimacs.starts_with("c clause "));
+        let header = dimacs.lines().find(|line| line.starts_with("p cnf ")).unwrap();
+        let variables: usize = header.split(' ').nth(2).unwrap().parse().unwrap();
+        assert_eq!(variables, dimacs.lines().filter(|line| line.starts_with("c ") && !line.starts_with("c clause")).count());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_certificates() {
+        for s in &["x ^ (y v z) = (x ^ y) v (x ^ z)", "x(y v z)w = xyw v xzw", "e <= xY v yZ v zX", "(Xy ^ e) v (Yx ^ e) = e", "e = e"] {
+            let certificate = certify(LGroupFormula::from(*s)).unwrap();
+            assert_eq!(Ok(()), certificate_checker::check(&certificate), "{}", s);
+            assert_eq!(Ok(certificate.clone()), certificate.to_string().parse());
+        }
+        let certificate = certify_representable(LGroupFormula::from("e <= x v yXY")).unwrap();
+        assert_eq!(Ok(()), certificate_checker::check(&certificate));
+        assert!(certify(LGroupFormula::from("e <= x v yXY")).is_none());
+        assert!(certify(LGroupFormula::from("e <= xyzw")).is_none());
+        assert!(certify(LGroupFormula::from("e <= xx v xy v yX")).is_none());
+
+        let formula = LGroupFormula::from("x ^ (y v z) = (x ^ y) v (x ^ z)");
+        for (limits, limit) in [(Limits { time: Some(Duration::from_secs(0)), ..Limits::default() }, Limit::Time),
+                                (Limits { search_nodes: Some(1), ..Limits::default() }, Limit::SearchNodes),
+                                (Limits { meetands: Some(2), ..Limits::default() }, Limit::Meetands)] {
+            let options = Options { limits, ..Options::default() };
+            assert_eq!(Some(Err(limit)), certify_with(formula.clone(), &options));
+        }
+    }
+
+    #[test]
+    fn test_certificates_of_standard_formulas_round_trip() {
+        use l_group_formulas::parsing::{Dialect, ParseOptions};
+        let standard = ParseOptions { dialect: Dialect::Standard, ..ParseOptions::default() };
+        for s in &["v1 /\\ w <= v1", "e * v <= e * (v | 1)", "v^-1 e^-1 e v = 1"] {
+            let formula = LGroupFormula::parse_with(s, &standard).unwrap();
+            let certificate = certify(formula).unwrap();
+            assert_eq!(Ok(certificate.clone()), certificate.to_string().parse(), "{}", s);
+            assert_eq!(Ok(()), certificate_checker::check(&certificate.to_string().parse().unwrap()), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_certificates_rename_reserved_variables() {
+        let v1 = LGroupTerm::from(FreeGroupTerm::new(vec![Literal::new('v', 1, false)]));
+        let e = LGroupTerm::from(FreeGroupTerm::new(vec![Literal::from('e')]));
+        let meet = LGroupTerm::Meet(vec![v1.clone(), e.clone(), LGroupTerm::from("w1")].into_iter().collect());
+        let certificate = certify(LGroupFormula::LGroupInequation(meet, v1)).unwrap();
+        assert_eq!(LGroupFormula::from("w2 ^ e1 ^ w1 <= w2"), certificate.formula);
+        assert_eq!(Ok(certificate.clone()), certificate.to_string().parse());
+        assert_eq!(Ok(()), certificate_checker::check(&certificate));
+        assert!(certify(LGroupFormula::LGroupInequation(e.clone(), LGroupTerm::from("x"))).is_none());
+    }
+
+    #[test]
+    fn test_certified_check_agrees() {
+        for s in &["x ^ (y v z) = (x ^ y) v (x ^ z)", "xy = yx", "e <= xx v yy v XY", "e <= xyzw", "e <= x v yXY"] {
+            let formula = LGroupFormula::from(*s);
+            let (result, certificate) = is_valid_certified(formula.clone(), &Options::default());
+            match (is_valid(formula), result) {
+                (ValidityResult::Valid(_), ValidityResult::Valid(statistics)) => {
+                    assert!(statistics.clauses > 0 && statistics.search_nodes > 0, "{}", s);
+                    assert_eq!(Ok(()), certificate_checker::check(&certificate.unwrap()));
+                },
+                (ValidityResult::Invalid { direction: expected, .. }, ValidityResult::Invalid { direction, countermodel, .. }) => {
+                    assert_eq!(expected, direction);
+                    assert!(countermodel.clause.is_subset(&countermodel.negative_cone));
+                    assert!(certificate.is_none());
+                },
+                (expected, result) => panic!("{} is {}, but {} when certified", s, expected, result)
+            };
+        }
+    }
+
+    #[test]
+    fn test_direction() {
+        let direction_of = |string| match is_valid(LGroupFormula::from(string)) {
+            ValidityResult::Invalid { direction, .. } => direction,
+            _ => panic!("{} should be invalid", string)
+        };
+        assert_eq!(Direction::LeftBelowRight, direction_of("x <= x ^ y"));
+        assert_eq!(Direction::LeftBelowRight, direction_of("x = x ^ y"));
+        assert_eq!(Direction::RightBelowLeft, direction_of("x ^ y = x"));
+    }
+
+    #[test]
+    fn test_statistics() {
+        let result = is_valid(LGroupFormula::from("x ^ (y v z) = (x ^ y) v (x ^ z)"));
+        assert!(result.is_valid());
+        assert!(result.statistics().meetands >= 2);
+        assert!(result.statistics().clauses >= 1);
+    }
+
+    #[test]
+    fn test_sup_inf_normal_form_is_equal() {
+        use l_group_cnf::sup_inf_normal_form::SupInfNormalForm;
+        for s in &["x(y v z ^ w)X", "(x ^ e)(y v Z)(x ^ y)"] {
+            let term = LGroupTerm::from(*s);
+            let normal_form = SupInfNormalForm::from(&term).to_term();
+            assert!(is_valid(LGroupFormula::LGroupEquation(term, normal_form)).is_valid());
+        }
+    }
+
+    #[test]
+    fn test_abelian() {
+        let is_valid_abelian_string = |string| is_valid_abelian(LGroupFormula::from(string)).is_valid();
+        assert!(is_valid_abelian_string("xy = yx"));
+        assert!(is_valid_abelian_string("x(y ^ z) = xy ^ xz"));
+        assert!(is_valid_abelian_string("e <= xX v yY"));
+        assert!(is_valid_abelian_string("(xy v e) <= (x v e)(y v e)"));
+        assert!(!is_valid_abelian_string("x v y = x"));
+        assert!(!is_valid_abelian_string("x ^ e <= X ^ e"));
+        match is_valid_abelian(LGroupFormula::from("x ^ y = x")) {
+            AbelianValidityResult::Invalid { direction, countermodel } => {
+                assert_eq!(Direction::RightBelowLeft, direction);
+                assert!(countermodel.rhs_value > countermodel.lhs_value);
+            },
+            AbelianValidityResult::Valid => panic!("x <= x ^ y should fail")
+        };
+    }
+
+    #[test]
+    fn test_from_string() {
+        assert!(is_valid_from_string("e <= x v X").unwrap().is_valid());
+        assert!(is_valid_from_string("e <= x v (X").is_err());
+    }
+
+    #[test]
+    fn test_no_countermodel_for_valid_formula() {
+        assert_eq!(None, find_countermodel(LGroupFormula::from("e <= x v X")));
+    }
 }
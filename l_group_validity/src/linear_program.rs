@@ -0,0 +1,129 @@
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+/// Returns a point `x` with `a * x <= b` for every constraint `(a, b)`, where all
+/// `a` have length `dimension`, or `None` if there is no such point.
+///
+/// This is the first phase of the simplex method with Bland's rule, computed
+/// exactly. The free variables `x` are split into `x⁺ - x⁻`, every constraint
+/// gets a slack variable, and, after making the right hand sides non-negative,
+/// an artificial variable, whose sum is minimized.
+pub (super) fn feasible_point(
+        constraints: &[(Vec<BigRational>, BigRational)],
+        dimension: usize) -> Option<Vec<BigRational>> {
+    let m = constraints.len();
+    // columns: x⁺, x⁻, slack, artificial
+    let columns = 2 * dimension + 2 * m;
+    let artificial = 2 * dimension + m;
+    let mut rows = Vec::new();
+    let mut rhs = Vec::new();
+    for (i, (a, b)) in constraints.iter().enumerate() {
+        let sign = if b.is_negative() { -BigRational::one() } else { BigRational::one() };
+        let mut row = vec![BigRational::zero(); columns];
+        for (j, a_j) in a.iter().enumerate() {
+            row[j] = &sign * a_j;
+            row[dimension + j] = -(&sign * a_j);
+        }
+        row[2 * dimension + i] = sign.clone();
+        row[artificial + i] = BigRational::one();
+        rows.push(row);
+        rhs.push(sign * b);
+    }
+    let mut basis: Vec<usize> = (artificial .. columns).collect();
+
+    loop {
+        // the reduced costs of minimizing the sum of the artificial variables,
+        // which never enter the basis again once they have left it
+        let entering = (0 .. artificial).find(|j| {
+            let mut cost = BigRational::zero();
+            for (row, k) in rows.iter().zip(&basis) {
+                if *k >= artificial {
+                    cost -= &row[*j];
+                }
+            }
+            cost.is_negative()
+        });
+        let entering = match entering {
+            Some(j) => j,
+            None => break
+        };
+        let mut leaving: Option<(usize, BigRational)> = None;
+        for (i, row) in rows.iter().enumerate() {
+            if row[entering].is_positive() {
+                let ratio = &rhs[i] / &row[entering];
+                let is_better = match &leaving {
+                    None => true,
+                    Some((k, best)) => ratio < *best || (ratio == *best && basis[i] < basis[*k])
+                };
+                if is_better {
+                    leaving = Some((i, ratio));
+                }
+            }
+        }
+        let pivot_row = leaving.expect("The artificial objective is bounded below by zero.").0;
+        pivot(&mut rows, &mut rhs, pivot_row, entering);
+        basis[pivot_row] = entering;
+    }
+
+    let mut values = vec![BigRational::zero(); columns];
+    for (i, j) in basis.iter().enumerate() {
+        values[*j] = rhs[i].clone();
+    }
+    if values[artificial ..].iter().any(|v| !v.is_zero()) {
+        return None;
+    }
+    Some((0 .. dimension).map(|j| &values[j] - &values[dimension + j]).collect())
+}
+
+fn pivot(rows: &mut [Vec<BigRational>], rhs: &mut [BigRational], pivot_row: usize, column: usize) {
+    let factor = rows[pivot_row][column].clone();
+    for x in rows[pivot_row].iter_mut() {
+        *x /= &factor;
+    }
+    rhs[pivot_row] /= &factor;
+    let row = rows[pivot_row].clone();
+    let value = rhs[pivot_row].clone();
+    for i in 0 .. rows.len() {
+        if i == pivot_row || rows[i][column].is_zero() {
+            continue;
+        }
+        let multiple = rows[i][column].clone();
+        for (x, y) in rows[i].iter_mut().zip(&row) {
+            *x -= &multiple * y;
+        }
+        rhs[i] -= &multiple * &value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_traits::FromPrimitive;
+
+    fn q(n: i64) -> BigRational {
+        BigRational::from_i64(n).unwrap()
+    }
+
+    fn satisfies(point: &[BigRational], constraints: &[(Vec<BigRational>, BigRational)]) -> bool {
+        constraints.iter().all(|(a, b)| {
+            let value = a.iter().zip(point).fold(BigRational::zero(), |sum, (a_j, x_j)| sum + a_j * x_j);
+            value <= *b
+        })
+    }
+
+    #[test]
+    fn test_feasible() {
+        // x - y <= -1, y <= 3, -x <= -2
+        let constraints = vec![(vec![q(1), q(-1)], q(-1)), (vec![q(0), q(1)], q(3)), (vec![q(-1), q(0)], q(-2))];
+        let point = feasible_point(&constraints, 2).unwrap();
+        assert!(satisfies(&point, &constraints));
+    }
+
+    #[test]
+    fn test_infeasible() {
+        // x <= -1 and -x <= -1
+        let constraints = vec![(vec![q(1)], q(-1)), (vec![q(-1)], q(-1))];
+        assert_eq!(None, feasible_point(&constraints, 1));
+        assert_eq!(None, feasible_point(&[(Vec::new(), q(-1))], 0));
+    }
+}
@@ -0,0 +1,382 @@
+use l_group_cnf::sup_inf_normal_form::SupInfNormalForm;
+use l_group_formulas::free_group_term::FreeGroupTerm;
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::literal::Literal;
+use std::collections::{BTreeMap, BTreeSet};
+use super::resources::Resources;
+use super::split_at_meets::split_at_meets_within;
+use super::validity_result::{Direction, Limit, ValidityResult};
+
+/// An assignment of the variables of an inverse-free `LGroupFormula` to
+/// order-preserving maps of the chain `0 < 1 < ... < n - 1`, under which the
+/// formula fails at `point`.
+///
+/// `assignment[x][i]` is the image of `i` under `x`. As for `Counterexample`,
+/// the maps act on the right, and joins and meets are taken pointwise.
+/// If the formula is the inequation `s <= t`, then `lhs_value > rhs_value`,
+/// where these are the images of `point` under `s` and `t`, respectively.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::is_valid_monoid;
+/// use l_group_validity::monoid::MonoidValidityResult;
+/// match is_valid_monoid(LGroupFormula::from("xy <= yx v x")).unwrap() {
+///     MonoidValidityResult::Invalid { countermodel, .. } => {
+///         assert!(countermodel.lhs_value > countermodel.rhs_value);
+///     },
+///     result => panic!("xy <= yx v x fails in l-monoids, but is {:?}", result)
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonoidCountermodel {
+    pub assignment: BTreeMap<Literal, Vec<usize>>,
+    pub point:      usize,
+    pub lhs_value:  usize,
+    pub rhs_value:  usize
+}
+
+impl std::fmt::Display for MonoidCountermodel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (x, map) in &self.assignment {
+            let images: Vec<String> = map.iter().enumerate().map(|(i, j)| format!("{} -> {}", i, j)).collect();
+            writeln!(f, "{} is {}", x, images.join(", "))?;
+        }
+        write!(f, "At {}, the left hand side is {} and the right hand side is {}.",
+               self.point, self.lhs_value, self.rhs_value)
+    }
+}
+
+/// The outcome of checking an inverse-free `LGroupFormula` for validity in all
+/// distributive l-monoids.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonoidValidityResult {
+    Valid,
+    /// The inequation in `direction` fails, as witnessed by `countermodel`.
+    Invalid {
+        direction:    Direction,
+        countermodel: MonoidCountermodel
+    },
+    /// The check gave up at `limit`.
+    Unknown {
+        limit: Limit
+    }
+}
+
+impl MonoidValidityResult {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, MonoidValidityResult::Valid)
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, MonoidValidityResult::Invalid { .. })
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, MonoidValidityResult::Unknown { .. })
+    }
+}
+
+/// The outcomes of checking an inverse-free `LGroupFormula` in all distributive
+/// l-monoids and in all l-groups, as `compare_monoid_and_l_groups` returns them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonoidComparison {
+    pub monoid:  MonoidValidityResult,
+    pub l_group: ValidityResult
+}
+
+impl MonoidComparison {
+    /// Whether the formula holds in all l-groups, but fails in some
+    /// distributive l-monoid.
+    pub fn holds_only_in_l_groups(&self) -> bool {
+        self.l_group.is_valid() && self.monoid.is_invalid()
+    }
+}
+
+/// The error of passing a formula with inverses to `is_valid_monoid`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InverseError {
+    /// An inverted literal of the formula.
+    pub literal: Literal
+}
+
+impl std::fmt::Display for InverseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "l-monoid terms have no inverses, but {} is inverted", self.literal)
+    }
+}
+
+impl std::error::Error for InverseError {}
+
+/// Returns an inverted literal of `term`, if there is one.
+pub (super) fn inverted_literal(term: &LGroupTerm) -> Option<Literal> {
+    match term {
+        LGroupTerm::Atom(x) => x.literals.iter().find(|l| l.is_inverted).cloned(),
+        LGroupTerm::Meet(xs) | LGroupTerm::Join(xs) => xs.iter().find_map(inverted_literal),
+        LGroupTerm::Prod(xs) => xs.iter().find_map(inverted_literal)
+    }
+}
+
+/// Returns a countermodel to the inequation `lhs <= rhs` of inverse-free terms,
+/// or `None` if it holds in all distributive l-monoids. Gives up and returns
+/// the limit as soon as `resources` hits one.
+///
+/// Since products distribute over meets and joins, `lhs` is a join of meets
+/// and `rhs` a meet of joins of words. The inequation fails if, and only if,
+/// some meet of words `s_j` is not below some join of words `t_l`, i.e., if
+/// there is a right preorder of the free monoid, in which every `t_l` is
+/// strictly below every `s_j`. As `extend_to_right_order` does for right
+/// orders of free groups, this searches for a `TruncatedPreorder` on the
+/// prefixes of these words, which is total.
+pub (super) fn find_countermodel(
+        lhs: &LGroupTerm,
+        rhs: &LGroupTerm,
+        resources: &Resources) -> Result<Option<MonoidCountermodel>, Limit> {
+    let meets = SupInfNormalForm::from(lhs).joinands;
+    let joins: Vec<BTreeSet<FreeGroupTerm>> = split_at_meets_within(rhs.clone(), resources)?.iter()
+        .map(|meetand| SupInfNormalForm::from(meetand).joinands.into_iter().flatten().collect())
+        .collect();
+    let mut variables = lhs.variables();
+    variables.append(&mut rhs.variables());
+    for meetands in &meets {
+        for joinands in &joins {
+            resources.check()?;
+            let prefixes = Prefixes::new(meetands.iter().chain(joinands));
+            let mut facts = Vec::new();
+            for s in meetands {
+                for t in joinands {
+                    facts.push((prefixes.index[s], prefixes.index[t], false));
+                }
+            }
+            let mut preorder = TruncatedPreorder::new(&prefixes);
+            if preorder.insert(facts) {
+                if let Some(preorder) = preorder.extend(resources, 0)? {
+                    return Ok(Some(prefixes.countermodel(&preorder.relation, &variables, lhs, rhs)));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The prefixes of some words, together with the partial maps
+/// given by appending letters.
+struct Prefixes {
+    words: Vec<FreeGroupTerm>,
+    index: BTreeMap<FreeGroupTerm, usize>,
+    /// For each variable `x`, the pairs `(u, v)` of prefixes with `ux = v`.
+    edges: BTreeMap<Literal, BTreeMap<usize, usize>>
+}
+
+/// Whether, for prefixes `i` and `j`, it is known that `i <= j`, `j < i`, or neither.
+type Relation = Vec<Vec<Option<bool>>>;
+
+impl Prefixes {
+    fn new<'a>(words: impl Iterator<Item = &'a FreeGroupTerm>) -> Prefixes {
+        let mut prefixes = Prefixes { words: Vec::new(), index: BTreeMap::new(), edges: BTreeMap::new() };
+        for w in words {
+            let mut previous = prefixes.prefix(FreeGroupTerm::new(Vec::new()));
+            for k in 0 .. w.literals.len() {
+                let current = prefixes.prefix(FreeGroupTerm::new(w.literals[0 ..= k].to_vec()));
+                prefixes.edges.entry(w.literals[k]).or_default().insert(previous, current);
+                previous = current;
+            }
+        }
+        prefixes
+    }
+
+    fn prefix(&mut self, u: FreeGroupTerm) -> usize {
+        if let Some(i) = self.index.get(&u) {
+            return *i;
+        }
+        self.words.push(u.clone());
+        self.index.insert(u, self.words.len() - 1);
+        self.words.len() - 1
+    }
+
+    /// Turns a total preorder on the prefixes into maps of a chain, where each
+    /// prefix is sent to the number of prefixes strictly below it.
+    fn countermodel(
+            &self,
+            relation: &Relation,
+            variables: &BTreeSet<Literal>,
+            lhs: &LGroupTerm,
+            rhs: &LGroupTerm) -> MonoidCountermodel {
+        let n = self.words.len();
+        let rank: Vec<usize> = (0 .. n)
+            .map(|i| (0 .. n).filter(|k| relation[i][*k] == Some(false)).count())
+            .collect();
+        let mut assignment = BTreeMap::new();
+        for x in variables {
+            let mut points: BTreeMap<usize, usize> = BTreeMap::new();
+            if let Some(edges) = self.edges.get(x) {
+                for (u, v) in edges {
+                    points.insert(rank[*u], rank[*v]);
+                }
+            }
+            // below the defined points, take the image of the smallest one, and
+            // otherwise the image of the largest defined point below
+            let map = (0 .. n)
+                .map(|i| match points.range(..= i).next_back() {
+                    Some((_, j)) => *j,
+                    None => points.values().next().cloned().unwrap_or(i)
+                })
+                .collect();
+            assignment.insert(*x, map);
+        }
+        let point = rank[self.index[&FreeGroupTerm::new(Vec::new())]];
+        let lhs_value = evaluate(lhs, &assignment, point);
+        let rhs_value = evaluate(rhs, &assignment, point);
+        MonoidCountermodel { assignment, point, lhs_value, rhs_value }
+    }
+}
+
+/// The part of a right preorder of the free monoid on some prefixes which is
+/// known, closed under the consequences of totality, transitivity, and the
+/// compatibility with appending letters, as far as they stay in the prefixes.
+/// This is the analogue of a `TruncatedSubgroup` for l-monoids, where the
+/// search chooses between `u <= v` and `v < u` instead of between an element
+/// and its inverse, as there are no inverses.
+#[derive(Clone)]
+struct TruncatedPreorder<'a> {
+    prefixes: &'a Prefixes,
+    relation: Relation
+}
+
+impl<'a> TruncatedPreorder<'a> {
+    fn new(prefixes: &'a Prefixes) -> TruncatedPreorder<'a> {
+        let n = prefixes.words.len();
+        TruncatedPreorder { prefixes, relation: vec![vec![None; n]; n] }
+    }
+
+    /// Returns a total preorder containing this one, or `None` if there is
+    /// none, after choosing for every pair of prefixes in turn, and counts
+    /// the preorders visited in `resources`. Gives up and returns the limit
+    /// as soon as `resources` hits one.
+    fn extend(self, resources: &Resources, depth: usize) -> Result<Option<TruncatedPreorder<'a>>, Limit> {
+        resources.visit(depth)?;
+        let n = self.prefixes.words.len();
+        let undecided = (0 .. n)
+            .flat_map(|i| (0 .. n).map(move |j| (i, j)))
+            .find(|(i, j)| self.relation[*i][*j].is_none());
+        let (i, j) = match undecided {
+            None => return Ok(Some(self)),
+            Some(pair) => pair
+        };
+        for i_is_below in &[true, false] {
+            let mut attempt = self.clone();
+            if attempt.insert(vec![(i, j, *i_is_below)]) {
+                if let Some(result) = attempt.extend(resources, depth + 1)? {
+                    return Ok(Some(result));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Adds the facts `(i, j, b)`, meaning that `i <= j` if `b`, and `j < i`
+    /// otherwise, together with their consequences under totality, transitivity,
+    /// and the compatibility with appending letters. Returns `false` if this
+    /// leads to a contradiction.
+    #[allow(clippy::needless_range_loop)]
+    fn insert(&mut self, mut facts: Vec<(usize, usize, bool)>) -> bool {
+        let n = self.prefixes.words.len();
+        let relation = &mut self.relation;
+        while let Some((i, j, below)) = facts.pop() {
+            match relation[i][j] {
+                Some(known) if known == below => continue,
+                Some(_) => return false,
+                None    => {}
+            };
+            relation[i][j] = Some(below);
+            if below {
+                for k in 0 .. n {
+                    if relation[k][i] == Some(true) { facts.push((k, j, true)); }
+                    if relation[j][k] == Some(true) { facts.push((i, k, true)); }
+                }
+                // u <= v implies ux <= vx
+                for edges in self.prefixes.edges.values() {
+                    if let (Some(k), Some(l)) = (edges.get(&i), edges.get(&j)) {
+                        facts.push((*k, *l, true));
+                    }
+                }
+            } else {
+                facts.push((j, i, true));
+                for k in 0 .. n {
+                    // j < i <= k and k <= j < i
+                    if relation[i][k] == Some(true) { facts.push((k, j, false)); }
+                    if relation[k][j] == Some(true) { facts.push((i, k, false)); }
+                }
+                // vx < ux implies v < u
+                for edges in self.prefixes.edges.values() {
+                    let preimage_of = |v| edges.iter().find(|(_, b)| **b == v).map(|(a, _)| *a);
+                    if let (Some(k), Some(l)) = (preimage_of(i), preimage_of(j)) {
+                        facts.push((k, l, false));
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Returns the image of `point` under the inverse-free `term`, where the
+/// variables are interpreted by `assignment`, and act on the right.
+///
+/// Panics if a variable of `term` is not assigned.
+pub fn evaluate(term: &LGroupTerm, assignment: &BTreeMap<Literal, Vec<usize>>, point: usize) -> usize {
+    match term {
+        LGroupTerm::Atom(x) => x.literals.iter().fold(point, |value, l| assignment[l][value]),
+        LGroupTerm::Meet(xs) => xs.iter().map(|x| evaluate(x, assignment, point)).min().expect("Empty meet"),
+        LGroupTerm::Join(xs) => xs.iter().map(|x| evaluate(x, assignment, point)).max().expect("Empty join"),
+        LGroupTerm::Prod(xs) => xs.iter().fold(point, |value, x| evaluate(x, assignment, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::options::{Limits, Options};
+
+    fn countermodel(lhs: &str, rhs: &str) -> Option<MonoidCountermodel> {
+        let (lhs, rhs) = (LGroupTerm::from(lhs), LGroupTerm::from(rhs));
+        let countermodel = find_countermodel(&lhs, &rhs, &Resources::new(&Options::default(), &())).unwrap();
+        if let Some(countermodel) = &countermodel {
+            assert!(countermodel.lhs_value > countermodel.rhs_value);
+            for map in countermodel.assignment.values() {
+                assert!(map.windows(2).all(|pair| pair[0] <= pair[1]));
+            }
+        }
+        countermodel
+    }
+
+    #[test]
+    fn test_valid() {
+        assert_eq!(None, countermodel("x ^ y", "x"));
+        assert_eq!(None, countermodel("x(y v z)", "xy v xz"));
+        assert_eq!(None, countermodel("(x ^ y)z", "xz"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(countermodel("xy", "yx").is_some());
+        assert!(countermodel("x", "e").is_some());
+        assert!(countermodel("e", "x v xx").is_some());
+    }
+
+    #[test]
+    fn test_limits() {
+        let (lhs, rhs) = (LGroupTerm::from("xyz ^ zyx"), LGroupTerm::from("xzy v yxz"));
+        let options = Options { limits: Limits { search_nodes: Some(1), ..Limits::default() }, ..Options::default() };
+        assert_eq!(Err(Limit::SearchNodes), find_countermodel(&lhs, &rhs, &Resources::new(&options, &())));
+        let options = Options { limits: Limits { search_nodes: Some(10000), ..Limits::default() }, ..Options::default() };
+        assert!(find_countermodel(&lhs, &rhs, &Resources::new(&options, &())).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_inverted_literal() {
+        assert_eq!(None, inverted_literal(&LGroupTerm::from("x(y ^ z)")));
+        assert_eq!(Some(Literal::new('y', 0, true)), inverted_literal(&LGroupTerm::from("x(Y ^ z)")));
+    }
+}
@@ -0,0 +1,78 @@
+use l_group_formulas::literal::Literal;
+use l_group_formulas::mv_formula::MVFormula;
+use num_rational::BigRational;
+use num_traits::{Signed, Zero};
+use std::collections::BTreeMap;
+use super::abelian::{evaluate, AbelianCountermodel};
+use super::validity_result::Direction;
+
+/// An assignment of the variables of an `MVFormula` to the rational unit
+/// interval, under which it fails in the standard MV-algebra `[0, 1]`.
+///
+/// If the formula is the inequation `s <= t`, then `lhs_value > rhs_value`,
+/// where these are the values of `s` and `t`. If it is an equation, then they
+/// are different.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::mv_formula::MVFormula;
+/// use l_group_validity::is_valid_mv;
+/// use l_group_validity::mv::MVValidityResult;
+/// assert!(is_valid_mv("x ⊕ ¬x".parse().unwrap()).is_valid());
+/// match is_valid_mv("x ⊙ x = x".parse().unwrap()) {
+///     MVValidityResult::Invalid { countermodel, .. } => assert!(countermodel.lhs_value != countermodel.rhs_value),
+///     MVValidityResult::Valid => panic!("x ⊙ x = x fails for x = 1/2")
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MVCountermodel {
+    pub assignment: BTreeMap<Literal, BigRational>,
+    pub lhs_value:  BigRational,
+    pub rhs_value:  BigRational
+}
+
+impl std::fmt::Display for MVCountermodel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (x, value) in &self.assignment {
+            writeln!(f, "{} is {}", x, value)?;
+        }
+        write!(f, "The left hand side is {} and the right hand side is {}.", self.lhs_value, self.rhs_value)
+    }
+}
+
+/// The outcome of checking an `MVFormula` for validity in all MV-algebras.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MVValidityResult {
+    Valid,
+    /// The inequation in `direction` fails, as witnessed by `countermodel`.
+    Invalid {
+        direction:    Direction,
+        countermodel: MVCountermodel
+    }
+}
+
+impl MVValidityResult {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, MVValidityResult::Valid)
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, MVValidityResult::Invalid { .. })
+    }
+}
+
+/// Scales a countermodel of the translation of `formula` to the unit
+/// interval, dividing by the value of the unit.
+pub (super) fn scaled(formula: &MVFormula, countermodel: &AbelianCountermodel) -> MVCountermodel {
+    let unit = countermodel.assignment[&formula.unit].abs();
+    assert!(!unit.is_zero(), "In the trivial MV-algebra, every formula holds.");
+    let assignment = formula.variables.iter()
+        .map(|(x, translation)| (*x, evaluate(translation, &countermodel.assignment) / &unit))
+        .collect();
+    MVCountermodel {
+        assignment,
+        lhs_value: &countermodel.lhs_value / &unit,
+        rhs_value: &countermodel.rhs_value / &unit
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeSet;
+use l_group_formulas::l_group_term::LGroupTerm;
+use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
+use super::validity_result::Direction;
+
+/// Whether an `Observer` lets a check go on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    /// Stops the check, which then returns `ValidityResult::Unknown` with
+    /// `Limit::Aborted`.
+    Abort
+}
+
+/// Receives the progress of a check by `is_valid_observed`, e.g., to drive a
+/// progress bar. Each method is called at a step of the check, and by default
+/// does nothing and lets the check go on.
+///
+/// With the `parallel` feature, the methods are called from several threads,
+/// so an observer keeps its state in atomics or behind locks.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::is_valid_observed;
+/// use l_group_validity::observer::{Control, Observer};
+/// use l_group_validity::options::Options;
+///
+/// struct AtMostTenNodes(AtomicUsize);
+///
+/// impl Observer for AtMostTenNodes {
+///     fn node(&self, _depth: usize, nodes: usize) -> Control {
+///         self.0.fetch_max(nodes, Ordering::Relaxed);
+///         match nodes < 10 {
+///             true  => Control::Continue,
+///             false => Control::Abort
+///         }
+///     }
+/// }
+///
+/// let observer = AtMostTenNodes(AtomicUsize::new(0));
+/// let result = is_valid_observed(LGroupFormula::from("e <= xx v xy v yX"), &Options::default(), &observer);
+/// assert!(result.is_unknown());
+/// // in parallel, other threads may visit nodes before they see the abort
+/// assert!(observer.0.load(Ordering::Relaxed) >= 10);
+/// ```
+pub trait Observer: Sync {
+    /// The inequation in `direction` was split into `meetands` meetands.
+    fn split(&self, _direction: Direction, _meetands: usize) -> Control {
+        Control::Continue
+    }
+
+    /// The `ThreeCNF` of `meetand` has `clauses` clauses, with `size`
+    /// joinands altogether.
+    fn three_cnf(&self, _meetand: &LGroupTerm, _clauses: usize, _size: usize) -> Control {
+        Control::Continue
+    }
+
+    /// The check starts on whether `clause` extends to an order.
+    fn clause(&self, _clause: &BTreeSet<ShortFreeGroupTerm>) -> Control {
+        Control::Continue
+    }
+
+    /// The search of `Backend::Search` visits a subgroup after `depth`
+    /// choices, as the `nodes`-th subgroup in the whole check.
+    fn node(&self, _depth: usize, _nodes: usize) -> Control {
+        Control::Continue
+    }
+}
+
+/// Observes nothing.
+impl Observer for () {}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// How the clauses of a formula are checked for extending to orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// A search through the subgroups generated by a clause, together with
+    /// a choice of an element or its inverse for each short element.
+    #[default]
+    Search,
+    /// The CDCL solver of `sat`, on a propositional encoding of the search.
+    Sat
+}
+
+/// How `Backend::Search` chooses the element of the ball of radius 2 to branch
+/// on, i.e., to add to the set of elements below `e`, or else its inverse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Heuristic {
+    /// The least undecided element.
+    #[default]
+    First,
+    /// The undecided element or inverse adding the most elements in one step
+    /// of the closure with the current set, as it is the most likely to run
+    /// into `e`.
+    LargestClosure,
+    /// The undecided element which occurs most often in the clause, together
+    /// with its inverse.
+    MostFrequent
+}
+
+/// Settings of `is_valid_with` and `is_valid_representable_with`.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::is_valid_with;
+/// use l_group_validity::options::{Backend, Options};
+/// let options = Options { backend: Backend::Sat, ..Options::default() };
+/// assert!(is_valid_with(LGroupFormula::from("x ^ (y v z) = (x ^ y) v (x ^ z)"), &options).is_valid());
+/// assert!(is_valid_with(LGroupFormula::from("xy = yx"), &options).is_invalid());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    pub backend:   Backend,
+    pub heuristic: Heuristic,
+    /// Whether `Backend::Search` adds the elements whose inverses run into
+    /// `e` within one step of the closure before branching, and gives up as
+    /// soon as an element and its inverse both do.
+    pub propagate: bool,
+    /// If set, `Backend::Search` restarts after visiting this many subgroups,
+    /// breaking ties of the heuristic at random, and doubles the limit.
+    pub restarts:  Option<usize>,
+    /// The seed of the random choices after restarts.
+    pub seed:      u64,
+    pub limits:    Limits,
+    /// If set, the check gives up once this is cancelled.
+    pub cancellation: Option<CancellationToken>
+}
+
+/// Bounds on the resources of a check, beyond which it gives up and returns
+/// `ValidityResult::Unknown`. By default, there are none.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::is_valid_with;
+/// use l_group_validity::options::{Limits, Options};
+/// use l_group_validity::validity_result::{Limit, ValidityResult};
+/// let options = Options { limits: Limits { search_nodes: Some(1), ..Limits::default() }, ..Options::default() };
+/// match is_valid_with(LGroupFormula::from("e <= xx v xy v yX"), &options) {
+///     ValidityResult::Unknown { limit, .. } => assert_eq!(Limit::SearchNodes, limit),
+///     _ => panic!("The search needs more than one subgroup.")
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// The time from the start of the check.
+    pub time:         Option<Duration>,
+    /// The number of subgroups visited by `Backend::Search`, in all clauses.
+    pub search_nodes: Option<usize>,
+    /// The number of joinands of the `ThreeCNF` of a meetand.
+    pub cnf_size:     Option<usize>,
+    /// The number of meetands obtained by splitting at meets, which also
+    /// bounds the sets of terms while splitting.
+    pub meetands:     Option<usize>
+}
+
+/// A flag with which a check running on another thread can be cancelled.
+/// Clones share the flag, and are equal to each other.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_formulas::formula::LGroupFormula;
+/// use l_group_validity::is_valid_with;
+/// use l_group_validity::options::{CancellationToken, Options};
+/// let token = CancellationToken::new();
+/// let options = Options { cancellation: Some(token.clone()), ..Options::default() };
+/// token.cancel();
+/// assert_eq!("unknown", is_valid_with(LGroupFormula::from("xy = yx"), &options).to_string());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &CancellationToken) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationToken {}
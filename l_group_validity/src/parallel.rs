@@ -0,0 +1,149 @@
+use rayon::prelude::*;
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use l_group_formulas::formula::LGroupFormula;
+use super::{Countermodel, Order, check_meetands, clauses_within, inequations};
+use super::observer::Observer;
+use super::options::Options;
+use super::resources::Resources;
+use super::split_at_meets::split_at_meets_within;
+use super::validity_result::{ValidityResult, Statistics, Direction, Limit};
+use tracing::{Span, info, info_span};
+
+/// Checks as `check_sequential` does, but computes the `ThreeCNF` of all
+/// meetands of both inequations, and then checks their clauses, on the thread
+/// pool of `rayon`.
+///
+/// Once a clause extends to an order, the clauses after it are skipped or
+/// stopped, while the ones before it are still checked. So the countermodel
+/// is the one of the first clause extending, as in `check_sequential`, but
+/// the statistics count the clauses checked in the meantime as well. A clause
+/// extending decides the check even if another one hits a limit.
+pub (super) fn check(eq: LGroupFormula, order: Order, options: &Options, observer: &dyn Observer) -> ValidityResult {
+    let mut statistics = Statistics::default();
+    let resources = Resources::new(options, observer);
+    match find_extension(eq, order, options, &resources, &mut statistics) {
+        Ok(Some((direction, countermodel))) => ValidityResult::Invalid { direction, countermodel, statistics },
+        Ok(None) => ValidityResult::Valid(statistics),
+        Err(limit) => ValidityResult::Unknown { limit, statistics }
+    }
+}
+
+fn find_extension(
+        eq: LGroupFormula,
+        order: Order,
+        options: &Options,
+        resources: &Resources,
+        statistics: &mut Statistics) -> Result<Option<(Direction, Countermodel)>, Limit> {
+    let mut meetands = Vec::new();
+    for (direction, term) in inequations(eq) {
+        let split = info_span!("split", ?direction).in_scope(|| split_at_meets_within(term.clone(), resources))?;
+        info!(meetands = split.len(), "Split up the inequation e <= {} to form {} meetands.", term, split.len());
+        statistics.meetands += split.len();
+        check_meetands(statistics.meetands, resources)?;
+        resources.observe(resources.observer.split(direction, split.len()))?;
+        meetands.extend(split.into_iter().map(|meetand| (direction, meetand)));
+    }
+
+    // the threads of the pool log within the span of the check
+    let span = Span::current();
+    let all_clauses: Vec<_> = meetands.par_iter()
+        .map(|(_, meetand)| span.in_scope(|| clauses_within(meetand, resources)))
+        .collect::<Result<_, _>>()?;
+
+    // the clauses in the order `check_sequential` checks them, without repetitions
+    let mut seen = BTreeSet::new();
+    let mut jobs = Vec::new();
+    for ((direction, meetand), clauses) in meetands.iter().zip(all_clauses) {
+        for clause in clauses {
+            if seen.insert(clause.clone()) {
+                jobs.push((*direction, meetand, clause));
+            }
+        }
+    }
+
+    // the first job whose clause is known to extend
+    let first = AtomicUsize::new(usize::MAX);
+    // the results of the jobs, or `None` for the ones skipped or stopped
+    let results: Vec<_> = jobs.par_iter()
+        .enumerate()
+        .map(|(i, (_, _, clause))| {
+            let mut statistics = Statistics::default();
+            let stop = || first.load(Ordering::Relaxed) < i;
+            if stop() {
+                return (None, statistics);
+            }
+            statistics.clauses += 1;
+            let result = span.in_scope(|| order.extend(clause.clone(), options, &mut statistics, resources, &stop));
+            if let Ok(Some(_)) = result {
+                first.fetch_min(i, Ordering::Relaxed);
+            }
+            // once stopped, `Ok(None)` does not say that the clause does not
+            // extend, but an earlier clause does anyway
+            match stop() {
+                true  => (None, statistics),
+                false => (Some(result), statistics)
+            }
+        })
+        .collect();
+
+    let mut countermodel = None;
+    let mut hit = None;
+    for ((direction, meetand, clause), (result, job_statistics)) in jobs.into_iter().zip(results) {
+        *statistics += job_statistics;
+        match result {
+            Some(Ok(Some(negative_cone))) if countermodel.is_none() => {
+                countermodel = Some((direction, Countermodel { meetand: meetand.clone(), clause, negative_cone }));
+            },
+            Some(Err(limit)) if hit.is_none() => hit = Some(limit),
+            _ => {}
+        };
+    }
+    match (countermodel, hit) {
+        (Some(countermodel), _) => Ok(Some(countermodel)),
+        (None, Some(limit)) => Err(limit),
+        (None, None) => Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::check_sequential;
+    use super::super::options::Limits;
+
+    #[test]
+    fn test_agrees_with_sequential() {
+        let formulas = ["x ^ (y v z) = (x ^ y) v (x ^ z)", "xy = yx", "e <= xx v yy v XY", "x ^ y = x",
+                        "e <= x v yXY", "(Xy ^ e) v (Yx ^ e) = e", "xyz ^ xzy <= e v x"];
+        for formula in &formulas {
+            for order in &[Order::Right, Order::Bi] {
+                let parallel = check(LGroupFormula::from(*formula), *order, &Options::default(), &());
+                let sequential = check_sequential(LGroupFormula::from(*formula), *order, &Options::default(), &());
+                match (&parallel, &sequential) {
+                    (ValidityResult::Valid(statistics), ValidityResult::Valid(expected)) => {
+                        assert_eq!(expected.meetands, statistics.meetands);
+                        assert_eq!(expected.clauses, statistics.clauses);
+                    },
+                    (ValidityResult::Invalid { direction, countermodel, .. },
+                     ValidityResult::Invalid { direction: expected_direction, countermodel: expected, .. }) => {
+                        assert_eq!(expected_direction, direction);
+                        assert_eq!(expected, countermodel);
+                    },
+                    _ => panic!("{} is {} in parallel, but {} sequentially", formula, parallel, sequential)
+                };
+            }
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let options = Options { limits: Limits { search_nodes: Some(1), ..Limits::default() }, ..Options::default() };
+        match check(LGroupFormula::from("e <= xx v xy v yX"), Order::Right, &options, &()) {
+            ValidityResult::Unknown { limit, .. } => assert_eq!(Limit::SearchNodes, limit),
+            result => panic!("The search needs more than one subgroup, but is {}.", result)
+        };
+        let options = Options { limits: Limits { meetands: Some(1), ..Limits::default() }, ..Options::default() };
+        assert!(check(LGroupFormula::from("x ^ (y v z) = (x ^ y) v (x ^ z)"), Order::Right, &options, &()).is_unknown());
+    }
+}
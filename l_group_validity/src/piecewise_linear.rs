@@ -0,0 +1,85 @@
+use num_rational::BigRational;
+
+/// An order-preserving bijection of the rationals, which is piecewise linear
+/// with finitely many rational breakpoints, and has slope 1 outside of them.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_validity::piecewise_linear::PiecewiseLinear;
+/// use num_rational::BigRational;
+/// use num_traits::FromPrimitive;
+/// let q = |n: i64| BigRational::from_i64(n).unwrap();
+/// let f = PiecewiseLinear::interpolating(vec![(q(0), q(1)), (q(2), q(5))]);
+/// assert_eq!(q(3), f.apply(&q(1)));
+/// assert_eq!(q(0), f.apply(&q(-1)));
+/// assert_eq!(q(6), f.apply(&q(3)));
+/// assert_eq!(q(1), f.inverse().apply(&q(3)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseLinear {
+    breakpoints: Vec<(BigRational, BigRational)>
+}
+
+impl PiecewiseLinear {
+    pub fn identity() -> PiecewiseLinear {
+        PiecewiseLinear { breakpoints: Vec::new() }
+    }
+
+    /// Returns the map sending each `a` to `b` for `(a, b)` in `points`,
+    /// which is linear in between.
+    ///
+    /// Panics if the points do not describe an order-preserving injection.
+    pub fn interpolating(mut points: Vec<(BigRational, BigRational)>) -> PiecewiseLinear {
+        points.sort();
+        points.dedup();
+        for pair in points.windows(2) {
+            if pair[0].0 >= pair[1].0 || pair[0].1 >= pair[1].1 {
+                panic!("The points do not describe an order-preserving injection.");
+            }
+        }
+        PiecewiseLinear { breakpoints: points }
+    }
+
+    pub fn breakpoints(&self) -> &[(BigRational, BigRational)] {
+        &self.breakpoints
+    }
+
+    pub fn inverse(&self) -> PiecewiseLinear {
+        PiecewiseLinear {
+            breakpoints: self.breakpoints.iter().map(|(a, b)| (b.clone(), a.clone())).collect()
+        }
+    }
+
+    pub fn apply(&self, x: &BigRational) -> BigRational {
+        let (first, last) = match (self.breakpoints.first(), self.breakpoints.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return x.clone()
+        };
+        if x <= &first.0 {
+            return x - &first.0 + &first.1;
+        }
+        if x >= &last.0 {
+            return x - &last.0 + &last.1;
+        }
+        for pair in self.breakpoints.windows(2) {
+            let ((a0, b0), (a1, b1)) = (&pair[0], &pair[1]);
+            if x <= a1 {
+                return b0 + (x - a0) * (b1 - b0) / (a1 - a0);
+            }
+        }
+        unreachable!()
+    }
+}
+
+impl std::fmt::Display for PiecewiseLinear {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.breakpoints.is_empty() {
+            return write!(f, "the identity");
+        }
+        let points: Vec<String> = self.breakpoints.iter()
+            .map(|(a, b)| format!("{} -> {}", a, b))
+            .collect();
+        write!(f, "the piecewise linear map with {}, and slope 1 outside", points.join(", "))
+    }
+}
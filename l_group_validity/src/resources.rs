@@ -0,0 +1,85 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Instant;
+use l_group_formulas::literal::Literal;
+use l_group_formulas::Term;
+use truncated_free_groups::truncated_group::TruncatedGroup;
+use super::observer::{Control, Observer};
+use super::options::{CancellationToken, Limits, Options};
+use super::validity_result::Limit;
+
+/// The resources a check has used, shared by the threads checking clauses,
+/// against the limits in `Options`, together with the `Observer` of the check
+/// and the balls of radius 3 built so far.
+pub (super) struct Resources<'a> {
+    pub (super) limits:   Limits,
+    pub (super) observer: &'a dyn Observer,
+    deadline:             Option<Instant>,
+    cancellation:         Option<&'a CancellationToken>,
+    /// The subgroups visited by `Backend::Search` so far.
+    nodes:                AtomicUsize,
+    /// Whether the observer asked to abort.
+    aborted:              AtomicBool,
+    /// By their letters, i.e., their generators and the inverses.
+    balls:                Mutex<BTreeMap<BTreeSet<Literal>, Arc<TruncatedGroup>>>
+}
+
+impl<'a> Resources<'a> {
+    /// Starts the clock of `options.limits.time`.
+    pub (super) fn new(options: &'a Options, observer: &'a dyn Observer) -> Resources<'a> {
+        Resources {
+            limits:       options.limits,
+            observer,
+            deadline:     options.limits.time.map(|time| Instant::now() + time),
+            cancellation: options.cancellation.as_ref(),
+            nodes:        AtomicUsize::new(0),
+            aborted:      AtomicBool::new(false),
+            balls:        Mutex::new(BTreeMap::new())
+        }
+    }
+
+    /// Returns the limit the check has hit, if any.
+    pub (super) fn check(&self) -> Result<(), Limit> {
+        if self.aborted.load(Ordering::Relaxed) {
+            return Err(Limit::Aborted);
+        }
+        if self.cancellation.is_some_and(|token| token.is_cancelled()) {
+            return Err(Limit::Cancelled);
+        }
+        if self.limits.search_nodes.is_some_and(|limit| self.nodes.load(Ordering::Relaxed) > limit) {
+            return Err(Limit::SearchNodes);
+        }
+        // not reading the clock without a time limit, as it is missing on some targets
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(Limit::Time);
+        }
+        Ok(())
+    }
+
+    /// Counts a subgroup visited after `depth` choices, tells the observer,
+    /// and returns the limit the check has hit, if any.
+    pub (super) fn visit(&self, depth: usize) -> Result<(), Limit> {
+        let nodes = self.nodes.fetch_add(1, Ordering::Relaxed) + 1;
+        self.observe(self.observer.node(depth, nodes))
+    }
+
+    /// Remembers if the observer asked to abort, and returns the limit the
+    /// check has hit, if any.
+    pub (super) fn observe(&self, control: Control) -> Result<(), Limit> {
+        if control == Control::Abort {
+            self.aborted.store(true, Ordering::Relaxed);
+        }
+        self.check()
+    }
+
+    /// Returns the ball of radius 3 with the given generators, built only
+    /// once for all clauses with the same letters.
+    pub (super) fn ball(&self, generators: &BTreeSet<Literal>) -> Arc<TruncatedGroup> {
+        let letters: BTreeSet<Literal> = generators.iter().flat_map(|g| vec![*g, g.inverse()]).collect();
+        let mut balls = self.balls.lock().unwrap();
+        balls.entry(letters.clone())
+            .or_insert_with(|| Arc::new(TruncatedGroup::new(letters)))
+            .clone()
+    }
+}
@@ -0,0 +1,389 @@
+use std::io::{self, Write};
+
+/// A propositional formula in conjunctive normal form, numbered as in the
+/// DIMACS format: the variables are `1, ..., variables`, and a literal is a
+/// variable `v` or its negation `-v`.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_validity::sat::SatFormula;
+/// let mut formula = SatFormula::new(2);
+/// formula.add_clause(vec![1, 2]);
+/// formula.add_clause(vec![-1]);
+/// assert_eq!(Some(vec![false, true]), formula.solve(&mut 0));
+/// formula.add_clause(vec![-2]);
+/// assert_eq!(None, formula.solve(&mut 0));
+///
+/// let mut dimacs = Vec::new();
+/// formula.write_dimacs(&mut dimacs).unwrap();
+/// assert_eq!("p cnf 2 3\n1 2 0\n-1 0\n-2 0\n", String::from_utf8(dimacs).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SatFormula {
+    pub variables: usize,
+    pub clauses:   Vec<Vec<i32>>
+}
+
+impl SatFormula {
+    pub fn new(variables: usize) -> SatFormula {
+        SatFormula { variables, clauses: Vec::new() }
+    }
+
+    /// Adds the disjunction of `clause`.
+    ///
+    /// Panics if a literal is `0` or its variable is out of range.
+    pub fn add_clause(&mut self, clause: Vec<i32>) {
+        assert!(clause.iter().all(|l| *l != 0 && l.unsigned_abs() as usize <= self.variables),
+                "Literals are nonzero and at most the number of variables.");
+        self.clauses.push(clause);
+    }
+
+    /// Writes the formula in the DIMACS format, without comments.
+    pub fn write_dimacs<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "p cnf {} {}", self.variables, self.clauses.len())?;
+        for clause in &self.clauses {
+            for l in clause {
+                write!(w, "{} ", l)?;
+            }
+            writeln!(w, "0")?;
+        }
+        Ok(())
+    }
+
+    /// Returns a satisfying assignment, whose `i`-th entry is the value of the
+    /// variable `i + 1`, or `None` if the formula is unsatisfiable. Increases
+    /// `conflicts` by the number of conflicts the solver ran into.
+    pub fn solve(&self, conflicts: &mut usize) -> Option<Vec<bool>> {
+        match self.solve_until(conflicts, &|| false) {
+            Ok(result) => result,
+            Err(Interrupted) => unreachable!("The solver is never stopped.")
+        }
+    }
+
+    /// Solves as `solve` does, but gives up as soon as `stop` returns `true`,
+    /// which is asked at every decision and conflict.
+    pub fn solve_until(&self, conflicts: &mut usize, stop: &dyn Fn() -> bool) -> Result<Option<Vec<bool>>, Interrupted> {
+        let mut solver = Solver::new(self.variables);
+        for clause in &self.clauses {
+            if !solver.add_clause(clause.iter().map(|l| literal(*l)).collect()) {
+                return Ok(None);
+            }
+        }
+        let result = solver.solve(stop);
+        *conflicts += solver.conflicts;
+        result
+    }
+}
+
+/// The solver was stopped before deciding a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interrupted;
+
+/// The literal `v` is `2 * (v - 1)`, and its negation is `2 * (v - 1) + 1`.
+fn literal(l: i32) -> usize {
+    2 * (l.unsigned_abs() as usize - 1) + (l < 0) as usize
+}
+
+fn negation(l: usize) -> usize {
+    l ^ 1
+}
+
+fn variable(l: usize) -> usize {
+    l >> 1
+}
+
+/// A conflict-driven clause-learning solver, which watches two literals per
+/// clause, learns the first unique implication point of each conflict, and
+/// jumps back to the second highest level of the learnt clause.
+struct Solver {
+    clauses:     Vec<Vec<usize>>,
+    /// The clauses whose first or second literal is the given one.
+    watches:     Vec<Vec<usize>>,
+    assignment:  Vec<Option<bool>>,
+    level:       Vec<usize>,
+    /// The clause which implied the value of a variable, if it was not decided.
+    reason:      Vec<Option<usize>>,
+    /// The true literals, in the order they were assigned.
+    trail:       Vec<usize>,
+    /// The lengths of the trail before each decision.
+    decisions:   Vec<usize>,
+    /// The number of literals on the trail whose consequences are propagated.
+    propagated:  usize,
+    conflicts:   usize
+}
+
+impl Solver {
+    fn new(variables: usize) -> Solver {
+        Solver {
+            clauses:    Vec::new(),
+            watches:    vec![Vec::new(); 2 * variables],
+            assignment: vec![None; variables],
+            level:      vec![0; variables],
+            reason:     vec![None; variables],
+            trail:      Vec::new(),
+            decisions:  Vec::new(),
+            propagated: 0,
+            conflicts:  0
+        }
+    }
+
+    fn value(&self, l: usize) -> Option<bool> {
+        self.assignment[variable(l)].map(|value| value != (l & 1 == 1))
+    }
+
+    /// Adds a clause before solving, and returns `false` if the clauses are
+    /// unsatisfiable already.
+    fn add_clause(&mut self, mut clause: Vec<usize>) -> bool {
+        clause.sort_unstable();
+        clause.dedup();
+        if clause.windows(2).any(|w| w[1] == negation(w[0])) {
+            return true;
+        }
+        match clause.len() {
+            0 => false,
+            1 => match self.value(clause[0]) {
+                Some(value) => value,
+                None => {
+                    self.assign(clause[0], None);
+                    true
+                }
+            },
+            _ => {
+                self.watch(clause);
+                true
+            }
+        }
+    }
+
+    fn watch(&mut self, clause: Vec<usize>) -> usize {
+        let index = self.clauses.len();
+        self.watches[clause[0]].push(index);
+        self.watches[clause[1]].push(index);
+        self.clauses.push(clause);
+        index
+    }
+
+    fn assign(&mut self, l: usize, reason: Option<usize>) {
+        let x = variable(l);
+        self.assignment[x] = Some(l & 1 == 0);
+        self.level[x] = self.decisions.len();
+        self.reason[x] = reason;
+        self.trail.push(l);
+    }
+
+    /// Assigns the literals implied by unit propagation, and returns a clause
+    /// all of whose literals are false, if there is one.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_literal = negation(self.trail[self.propagated]);
+            self.propagated += 1;
+            let mut watchers = std::mem::take(&mut self.watches[false_literal]);
+            let mut i = 0;
+            while i < watchers.len() {
+                let c = watchers[i];
+                if self.clauses[c][0] == false_literal {
+                    self.clauses[c].swap(0, 1);
+                }
+                let first = self.clauses[c][0];
+                if self.value(first) == Some(true) {
+                    i += 1;
+                    continue;
+                }
+                let replacement = (2 .. self.clauses[c].len())
+                    .find(|k| self.value(self.clauses[c][*k]) != Some(false));
+                if let Some(k) = replacement {
+                    self.clauses[c].swap(1, k);
+                    self.watches[self.clauses[c][1]].push(c);
+                    watchers.swap_remove(i);
+                    continue;
+                }
+                if self.value(first) == Some(false) {
+                    self.watches[false_literal] = watchers;
+                    return Some(c);
+                }
+                self.assign(first, Some(c));
+                i += 1;
+            }
+            self.watches[false_literal] = watchers;
+        }
+        None
+    }
+
+    /// Returns the clause learnt from `conflict`, whose first literal is the
+    /// negation of the first unique implication point, and whose second
+    /// literal, if any, has the highest level among the others.
+    fn analyze(&self, conflict: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.assignment.len()];
+        let mut learnt = vec![0];
+        let mut current = 0;
+        let mut clause = conflict;
+        let mut implied = None;
+        let mut index = self.trail.len();
+        loop {
+            for &l in &self.clauses[clause] {
+                let x = variable(l);
+                if Some(l) == implied || seen[x] || self.level[x] == 0 {
+                    continue;
+                }
+                seen[x] = true;
+                if self.level[x] == self.decisions.len() {
+                    current += 1;
+                } else {
+                    learnt.push(l);
+                }
+            }
+            let l = loop {
+                index -= 1;
+                if seen[variable(self.trail[index])] {
+                    break self.trail[index];
+                }
+            };
+            current -= 1;
+            if current == 0 {
+                learnt[0] = negation(l);
+                break;
+            }
+            implied = Some(l);
+            clause = self.reason[variable(l)].expect("Literals of the current level after the decision are implied.");
+        }
+        if let Some(k) = (1 .. learnt.len()).max_by_key(|k| self.level[variable(learnt[*k])]) {
+            learnt.swap(1, k);
+        }
+        learnt
+    }
+
+    /// Undoes all assignments above `level`.
+    fn backtrack(&mut self, level: usize) {
+        if level >= self.decisions.len() {
+            return;
+        }
+        for l in self.trail.drain(self.decisions[level] ..) {
+            self.assignment[variable(l)] = None;
+            self.reason[variable(l)] = None;
+        }
+        self.decisions.truncate(level);
+        self.propagated = self.trail.len();
+    }
+
+    fn solve(&mut self, stop: &dyn Fn() -> bool) -> Result<Option<Vec<bool>>, Interrupted> {
+        loop {
+            if stop() {
+                return Err(Interrupted);
+            }
+            if let Some(conflict) = self.propagate() {
+                self.conflicts += 1;
+                if self.decisions.is_empty() {
+                    return Ok(None);
+                }
+                let learnt = self.analyze(conflict);
+                let level = learnt.get(1).map_or(0, |l| self.level[variable(*l)]);
+                self.backtrack(level);
+                let first = learnt[0];
+                match learnt.len() {
+                    1 => self.assign(first, None),
+                    _ => {
+                        let c = self.watch(learnt);
+                        self.assign(first, Some(c));
+                    }
+                };
+            } else {
+                match (0 .. self.assignment.len()).find(|x| self.assignment[*x].is_none()) {
+                    Some(x) => {
+                        self.decisions.push(self.trail.len());
+                        self.assign(2 * x + 1, None);
+                    },
+                    None => return Ok(Some(self.assignment.iter().map(|value| value == &Some(true)).collect()))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn satisfies(model: &[bool], formula: &SatFormula) -> bool {
+        formula.clauses.iter().all(|clause| clause.iter().any(|l| model[l.unsigned_abs() as usize - 1] == (*l > 0)))
+    }
+
+    /// `n + 1` pigeons do not fit into `n` holes, where the variable
+    /// `i * n + j + 1` says that pigeon `i` sits in hole `j`.
+    fn pigeonhole(n: usize) -> SatFormula {
+        let mut formula = SatFormula::new((n + 1) * n);
+        let v = |i: usize, j: usize| (i * n + j + 1) as i32;
+        for i in 0 ..= n {
+            formula.add_clause((0 .. n).map(|j| v(i, j)).collect());
+        }
+        for j in 0 .. n {
+            for i in 0 ..= n {
+                for k in 0 .. i {
+                    formula.add_clause(vec![-v(i, j), -v(k, j)]);
+                }
+            }
+        }
+        formula
+    }
+
+    #[test]
+    fn test_satisfiable() {
+        let mut formula = SatFormula::new(4);
+        formula.add_clause(vec![1, 2, 3]);
+        formula.add_clause(vec![-1, -2]);
+        formula.add_clause(vec![-1, -3]);
+        formula.add_clause(vec![-2, -3]);
+        formula.add_clause(vec![-3, 4]);
+        formula.add_clause(vec![-4, 1, 2]);
+        formula.add_clause(vec![1, 1, -1]);
+        let model = formula.solve(&mut 0).unwrap();
+        assert!(satisfies(&model, &formula));
+    }
+
+    #[test]
+    fn test_unsatisfiable() {
+        let mut conflicts = 0;
+        assert_eq!(None, pigeonhole(4).solve(&mut conflicts));
+        assert!(conflicts > 0);
+        let mut formula = SatFormula::new(1);
+        formula.add_clause(Vec::new());
+        assert_eq!(None, formula.solve(&mut 0));
+    }
+
+    #[test]
+    fn test_interrupted() {
+        let mut conflicts = 0;
+        assert_eq!(Err(Interrupted), pigeonhole(4).solve_until(&mut conflicts, &|| true));
+        assert_eq!(0, conflicts);
+        let countdown = std::cell::Cell::new(10);
+        let stop = || {
+            countdown.set(countdown.get() - 1);
+            countdown.get() == 0
+        };
+        assert_eq!(Err(Interrupted), pigeonhole(4).solve_until(&mut conflicts, &stop));
+        assert!(conflicts < 10);
+    }
+
+    #[test]
+    fn test_learning_across_levels() {
+        // an exactly-one constraint on 0, ..., 5 with 0 and 1 excluded
+        // and 2, 3, 4 each forcing a contradiction, so only 5 is left
+        let mut formula = SatFormula::new(8);
+        formula.add_clause((1 ..= 6).collect());
+        for i in 1 ..= 6 {
+            for j in 1 .. i {
+                formula.add_clause(vec![-i, -j]);
+            }
+        }
+        formula.add_clause(vec![-1]);
+        formula.add_clause(vec![-2]);
+        for i in 3 ..= 5 {
+            formula.add_clause(vec![-i, 7]);
+            formula.add_clause(vec![-i, 8]);
+            formula.add_clause(vec![-7, -8, -i]);
+        }
+        let model = formula.solve(&mut 0).unwrap();
+        assert!(satisfies(&model, &formula));
+        assert!(model[5]);
+    }
+}
@@ -1,89 +1,25 @@
 use l_group_formulas::l_group_term::LGroupTerm;
 use std::collections::BTreeSet;
-use l_group_formulas::Reducable;
-
-pub (super) fn split_at_meets(term: LGroupTerm) -> BTreeSet<LGroupTerm> {
-    let mut terms : BTreeSet<LGroupTerm> = BTreeSet::new();
-    match term {
-        LGroupTerm::Meet(xs) => {
-            for x in xs {
-                for y in split_at_meets(x) {
-                    terms.insert(y);
-                }
-            }
-        },
-        LGroupTerm::Atom(x) => {
-            terms.insert(LGroupTerm::Atom(x));
-        },
-        LGroupTerm::Join(xs) => {
-            let mut splits = Vec::new();
-            for x in xs {
-                splits.push(split_at_meets(x));
-            }
-            for y in join_all_combinations(splits) {
-                terms.insert(y);
-            }
-        },
-        LGroupTerm::Prod(xs) => {
-            let mut splits = Vec::new();
-            for x in xs {
-                splits.push(split_at_meets(x));
-            }
-            for y in multiply_all_combinations(splits) {
-                terms.insert(y);
-            }
-        }
-    };
-    terms
-}
-
-fn join_all_combinations(sets_of_joinands: Vec<BTreeSet<LGroupTerm>>) -> BTreeSet<LGroupTerm> {
-    let mut result = BTreeSet::new();
-    let mut iterator = sets_of_joinands.iter();
-    if let Some(first_set) = iterator.next() {
-        for x in first_set {
-            result.insert(x.clone());
-        }
-    }
-    for joinands in iterator {
-        let mut tmp = BTreeSet::new();
-        for r in &result {
-            for j in joinands {
-                let mut tmp_joinands = BTreeSet::new();
-                tmp_joinands.insert(r.clone());
-                tmp_joinands.insert(j.clone());
-                let new_r = LGroupTerm::Join(tmp_joinands).reduced();
-                tmp.insert(new_r);
-            }
-        }
-        result = tmp;
-    }
-    result
-}
-
-fn multiply_all_combinations(sets_of_factors: Vec<BTreeSet<LGroupTerm>>) -> BTreeSet<LGroupTerm> {
-    let mut result = BTreeSet::new();
-    let mut iterator = sets_of_factors.iter();
-    if let Some(first_set) = iterator.next() {
-        for x in first_set {
-            result.insert(x.clone());
-        }
-    }
-    for factors in iterator {
-        let mut tmp = BTreeSet::new();
-        for r in &result {
-            for f in factors {
-                tmp.insert(r.clone() * f.clone());
-            }
-        }
-        result = tmp;
-    }
-    result
+use l_group_cnf::split_at_meets::split_at_meets_checked;
+use super::resources::Resources;
+use super::validity_result::Limit;
+
+pub (super) use l_group_cnf::split_at_meets::split_at_meets;
+
+/// Splits as `split_at_meets` does, but gives up as soon as a set of terms
+/// obtained on the way has more than `resources.limits.meetands` elements,
+/// or `resources` hits another limit.
+pub (super) fn split_at_meets_within(term: LGroupTerm, resources: &Resources) -> Result<BTreeSet<LGroupTerm>, Limit> {
+    split_at_meets_checked(term, &|terms| match resources.limits.meetands {
+        Some(limit) if terms.len() > limit => Err(Limit::Meetands),
+        _ => resources.check()
+    })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use super::super::options::{Limits, Options};
 
     #[test]
     fn test_split() {
@@ -108,9 +44,19 @@ mod test {
         expected.insert(LGroupTerm::Join(expected_joinands2));
 
         for x in split_at_meets(join.clone()) {
-            println!("{}", x.to_string())
+            println!("{}", x)
         }
         assert_eq!(expected, split_at_meets(join));
 
     }
+
+    #[test]
+    fn test_split_within_limits() {
+        // eight meetands, and four after joining the first two joinands
+        let term = LGroupTerm::from("(x ^ X) v (y ^ Y) v (z ^ Z)");
+        let options = Options { limits: Limits { meetands: Some(8), ..Limits::default() }, ..Options::default() };
+        assert_eq!(Ok(split_at_meets(term.clone())), split_at_meets_within(term.clone(), &Resources::new(&options, &())));
+        let options = Options { limits: Limits { meetands: Some(3), ..Limits::default() }, ..Options::default() };
+        assert_eq!(Err(Limit::Meetands), split_at_meets_within(term, &Resources::new(&options, &())));
+    }
 }
@@ -0,0 +1,120 @@
+use super::Countermodel;
+
+/// Counts of the work done by a validity check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Statistics {
+    /// The number of meetands obtained by splitting at meets.
+    pub meetands:     usize,
+    /// The number of `ThreeCNF` clauses checked for extending to a right order.
+    pub clauses:      usize,
+    /// The number of subgroups visited by the search for right orders.
+    pub search_nodes: usize,
+    /// The number of times this search started over.
+    pub restarts:     usize,
+    /// The number of conflicts of the SAT solver, if it is used instead.
+    pub conflicts:    usize
+}
+
+impl std::ops::AddAssign for Statistics {
+    fn add_assign(&mut self, other: Statistics) {
+        self.meetands += other.meetands;
+        self.clauses += other.clauses;
+        self.search_nodes += other.search_nodes;
+        self.restarts += other.restarts;
+        self.conflicts += other.conflicts;
+    }
+}
+
+/// Which of the inequations making up an `LGroupFormula` is meant.
+/// The inequation `lhs <= rhs` is checked first, and for equations,
+/// `rhs <= lhs` is checked second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Direction {
+    LeftBelowRight,
+    RightBelowLeft
+}
+
+/// The outcome of checking an `LGroupFormula` for validity in all l-groups.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use l_group_validity::is_valid_from_string;
+/// use l_group_validity::validity_result::{ValidityResult, Direction};
+/// match is_valid_from_string("x ^ y = x").unwrap() {
+///     ValidityResult::Invalid { direction, .. } => assert_eq!(Direction::RightBelowLeft, direction),
+///     _ => panic!("x <= x ^ y should fail")
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidityResult {
+    Valid(Statistics),
+    /// The inequation in `direction` fails, as witnessed by `countermodel`.
+    Invalid {
+        direction:    Direction,
+        countermodel: Countermodel,
+        statistics:   Statistics
+    },
+    /// The check gave up at `limit`, with the work done until then.
+    Unknown {
+        limit:      Limit,
+        statistics: Statistics
+    }
+}
+
+/// The resource a check ran out of, as set in `options::Limits`, or its
+/// cancellation, or the request of its `Observer` to abort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Time,
+    SearchNodes,
+    CnfSize,
+    Meetands,
+    Cancelled,
+    Aborted
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Limit::Time        => write!(f, "the time limit"),
+            Limit::SearchNodes => write!(f, "the limit on search nodes"),
+            Limit::CnfSize     => write!(f, "the limit on the size of normal forms"),
+            Limit::Meetands    => write!(f, "the limit on meetands"),
+            Limit::Cancelled   => write!(f, "cancellation"),
+            Limit::Aborted     => write!(f, "the request of the observer")
+        }
+    }
+}
+
+impl ValidityResult {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ValidityResult::Valid(_))
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, ValidityResult::Invalid { .. })
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, ValidityResult::Unknown { .. })
+    }
+
+    pub fn statistics(&self) -> &Statistics {
+        match self {
+            ValidityResult::Valid(statistics)
+            | ValidityResult::Invalid { statistics, .. }
+            | ValidityResult::Unknown { statistics, .. } => statistics
+        }
+    }
+}
+
+impl std::fmt::Display for ValidityResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ValidityResult::Valid(_) => write!(f, "valid"),
+            ValidityResult::Invalid { .. } => write!(f, "invalid"),
+            ValidityResult::Unknown { .. } => write!(f, "unknown")
+        }
+    }
+}
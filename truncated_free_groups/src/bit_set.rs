@@ -0,0 +1,99 @@
+/// A set of indices below a fixed bound, stored as one bit per index.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use truncated_free_groups::bit_set::BitSet;
+/// let mut set = BitSet::new(100);
+/// assert!(set.insert(3));
+/// assert!(set.insert(70));
+/// assert!(!set.insert(3));
+/// let mut other = BitSet::new(100);
+/// other.insert(70);
+/// assert!(other.is_subset(&set));
+/// set.difference_with(&other);
+/// assert_eq!(vec![3], set.iter().collect::<Vec<usize>>());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>
+}
+
+impl BitSet {
+    /// Returns the empty set of indices below `bound`.
+    pub fn new(bound: usize) -> BitSet {
+        BitSet { words: vec![0; bound.div_ceil(64)] }
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    /// Inserts `i` and returns whether it was new.
+    pub fn insert(&mut self, i: usize) -> bool {
+        let is_new = !self.contains(i);
+        self.words[i / 64] |= 1 << (i % 64);
+        is_new
+    }
+
+    /// Removes `i` and returns whether it was there.
+    pub fn remove(&mut self, i: usize) -> bool {
+        let was_there = self.contains(i);
+        self.words[i / 64] &= !(1 << (i % 64));
+        was_there
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        for w in &mut self.words { *w = 0; }
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        for (w, v) in self.words.iter_mut().zip(&other.words) { *w |= v; }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        for (w, v) in self.words.iter_mut().zip(&other.words) { *w &= v; }
+    }
+
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (w, v) in self.words.iter_mut().zip(&other.words) { *w &= !v; }
+    }
+
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words.iter().zip(&other.words).all(|(w, v)| w & !v == 0)
+    }
+
+    /// Returns the indices in increasing order.
+    pub fn iter(&self) -> Ones<'_> {
+        Ones { words: &self.words, offset: 0, word: self.words.first().copied().unwrap_or(0) }
+    }
+}
+
+/// The indices of a `BitSet`, in increasing order.
+pub struct Ones<'a> {
+    words:  &'a [u64],
+    offset: usize,
+    word:   u64
+}
+
+impl<'a> Iterator for Ones<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            self.offset += 1;
+            self.word = *self.words.get(self.offset)?;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some(self.offset * 64 + bit)
+    }
+}
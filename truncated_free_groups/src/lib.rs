@@ -1,3 +1,4 @@
+pub mod bit_set;
 pub mod truncated_group;
 pub mod truncated_subgroup;
 pub mod tiny_truncated_group;
@@ -1,51 +1,66 @@
 use std::collections::BTreeSet;
+use std::sync::Arc;
 use l_group_formulas::literal::Literal;
-use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
-use l_group_formulas::Term;
+use l_group_formulas::short_free_group_term::Len;
+use super::bit_set::BitSet;
+use super::truncated_group::TruncatedGroup;
 
 
+/// Represents the ball of radius 2, as the set of indices of its elements
+/// in `ball`, the ball of radius 3.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use std::collections::BTreeSet;
+/// use l_group_formulas::literal::Literal;
+/// use truncated_free_groups::tiny_truncated_group::{TinyTruncatedGroup, ElementsExceptIdentity};
+/// let mut generators = BTreeSet::new();
+/// generators.insert(Literal::from('x'));
+/// generators.insert(Literal::from('y'));
+/// let tiny = TinyTruncatedGroup::new(generators);
+/// assert_eq!(17, tiny.elements.len());
+/// assert_eq!(16, tiny.elements_except_identity().len());
+/// ```
 #[derive(Debug)]
 pub struct TinyTruncatedGroup {
     pub generators: BTreeSet<Literal>,
-    pub elements:   Box<BTreeSet<ShortFreeGroupTerm>>
+    pub ball:       Arc<TruncatedGroup>,
+    pub elements:   BitSet
 }
 
 
 impl TinyTruncatedGroup {
     pub fn new(generators: BTreeSet<Literal>) -> TinyTruncatedGroup {
-        let mut literals = BTreeSet::new();
-        let mut elements = BTreeSet::new();
-        for x in &generators {
-            literals.insert(ShortFreeGroupTerm::from(*x));
-            literals.insert(ShortFreeGroupTerm::from(x.inverse()));
-            elements.insert(ShortFreeGroupTerm::from(*x));
-            elements.insert(ShortFreeGroupTerm::from(x.inverse()));
-        }
+        TinyTruncatedGroup::within(Arc::new(TruncatedGroup::new(generators)))
+    }
 
-        let mut new_elements = BTreeSet::new();
-        for literal in &literals {
-            for t in &elements {
-                new_elements.insert(*t * *literal);
+    /// Returns the ball of radius 2 within `ball`, which can be shared.
+    pub fn within(ball: Arc<TruncatedGroup>) -> TinyTruncatedGroup {
+        let mut elements = BitSet::new(ball.len());
+        for (i, x) in ball.elements.iter().enumerate() {
+            if x.len() <= 2 {
+                elements.insert(i);
             }
         }
-        for x in &new_elements { elements.insert(*x); }
 
         TinyTruncatedGroup {
-            generators,
-            elements:   Box::new(elements)
+            generators: ball.generators.clone(),
+            ball,
+            elements
         }
     }
 }
 
 
 pub trait ElementsExceptIdentity {
-    fn elements_except_identity(&self) -> BTreeSet<ShortFreeGroupTerm>;
+    fn elements_except_identity(&self) -> BitSet;
 }
 
 impl ElementsExceptIdentity for TinyTruncatedGroup {
-    fn elements_except_identity(&self) -> BTreeSet<ShortFreeGroupTerm> {
+    fn elements_except_identity(&self) -> BitSet {
         let mut all_elements = self.elements.clone();
-        all_elements.remove(&ShortFreeGroupTerm::new(None, None, None));
-        return *all_elements;
+        all_elements.remove(self.ball.identity());
+        all_elements
     }
 }
@@ -1,11 +1,17 @@
 use std::collections::BTreeSet;
+use std::sync::OnceLock;
 use l_group_formulas::literal::Literal;
 use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
 use l_group_formulas::Term;
+use super::bit_set::BitSet;
 
 /// Represents the ball of radius 3 in the Cayley graph of the free
 /// group with respect to the standard generating set.
-/// 
+///
+/// The index of an element is its position in `elements`, which are in
+/// increasing order, so `e` has index 0 and comparing indices compares
+/// elements. Subsets of the ball are `BitSet`s of indices.
+///
 /// #Examples
 /// Basic usage:
 /// ```
@@ -17,39 +23,220 @@ use l_group_formulas::Term;
 /// generators.insert(Literal::from('x'));
 /// generators.insert(Literal::from('y'));
 /// let truncated_group = TruncatedGroup::new(generators);
-/// let mut expected: BTreeSet<ShortFreeGroupTerm> = BTreeSet::new();
 /// assert_eq!(53, truncated_group.elements.len());
+/// let xy = truncated_group.index(&ShortFreeGroupTerm::from("xy")).unwrap();
+/// let yx = truncated_group.index(&ShortFreeGroupTerm::from("YX")).unwrap();
+/// assert_eq!(yx, truncated_group.inverse(xy));
+/// assert_eq!(Some(truncated_group.identity()), truncated_group.product(xy, yx));
+/// assert_eq!(None, truncated_group.product(xy, xy));
+/// assert_eq!(None, truncated_group.index(&ShortFreeGroupTerm::from("z")));
+/// ```
+/// The products are looked up in a table computed once, and agree with the
+/// products of the elements as far as these are short.
+/// ```
+/// # use std::collections::BTreeSet;
+/// # use l_group_formulas::literal::Literal;
+/// # use l_group_formulas::free_group_term::FreeGroupTerm;
+/// # use truncated_free_groups::truncated_group::TruncatedGroup;
+/// let generators: BTreeSet<Literal> = "xy".chars().map(Literal::from).collect();
+/// let ball = TruncatedGroup::new(generators);
+/// for (i, x) in ball.elements.iter().enumerate() {
+///     for (j, y) in ball.elements.iter().enumerate() {
+///         let product = FreeGroupTerm::from(*x) * FreeGroupTerm::from(*y);
+///         assert_eq!(product.literals.len() <= 3, ball.product(i, j).is_some());
+///         assert_eq!(product.literals.len() <= 3, ball.left_products(j).any(|(k, _)| k == i));
+///     }
+/// }
+/// ```
 #[derive(Debug)]
 pub struct TruncatedGroup {
-    pub generators: BTreeSet<Literal>,
-    pub elements:   Box<BTreeSet<ShortFreeGroupTerm>>
+    pub generators:   BTreeSet<Literal>,
+    pub elements:     Vec<ShortFreeGroupTerm>,
+    inverses:         Vec<usize>,
+    /// The elements as words of indices of letters, i.e., of the generators
+    /// and their inverses in increasing order.
+    words:            Vec<Vec<usize>>,
+    inverse_letter:   Vec<usize>,
+    /// The indices of the words, numbered in base `letters + 1`.
+    index_of_code:    Vec<u32>,
+    /// The indices of the elements of each length.
+    by_length:        Vec<Vec<usize>>,
+    /// For every `x`, the pairs of the indices of `y` and `xy`, for the `y`
+    /// such that `xy` is in the ball, by increasing `y`. A row is computed
+    /// when it is first used, as searches reach only a small part of balls
+    /// with many letters.
+    products:         Vec<OnceLock<Vec<(u32, u32)>>>,
+    conjugates:       Vec<OnceLock<Vec<(usize, usize)>>>
 }
 
 
 impl TruncatedGroup {
     pub fn new(generators: BTreeSet<Literal>) -> TruncatedGroup {
-        let mut literals = BTreeSet::new();
-        let mut elements = BTreeSet::new();
+        let mut letters = BTreeSet::new();
         for x in &generators {
-            literals.insert(ShortFreeGroupTerm::from(*x));
-            literals.insert(ShortFreeGroupTerm::from(x.inverse()));
-            elements.insert(ShortFreeGroupTerm::from(*x));
-            elements.insert(ShortFreeGroupTerm::from(x.inverse()));
+            letters.insert(*x);
+            letters.insert(x.inverse());
         }
+        let letters: Vec<Literal> = letters.into_iter().collect();
+        let m = letters.len();
+        let inverse_letter: Vec<usize> = letters.iter()
+            .map(|a| letters.binary_search(&a.inverse()).unwrap())
+            .collect();
 
-        for _ in &[1, 2] {
-            let mut new_elements = BTreeSet::new();
-            for literal in &literals {
-                for t in &elements {
-                    new_elements.insert(*t * *literal);
+        // the reduced words of length at most 3
+        let mut words: Vec<Vec<usize>> = vec![Vec::new()];
+        let mut start = 0;
+        for _ in &[1, 2, 3] {
+            let end = words.len();
+            for i in start .. end {
+                for (a, a_inverse) in inverse_letter.iter().enumerate() {
+                    if words[i].last() != Some(a_inverse) {
+                        words.push([words[i].as_slice(), &[a]].concat());
+                    }
                 }
             }
-            for x in &new_elements { elements.insert(*x); }
+            start = end;
+        }
+        let term = |w: &[usize]| {
+            let letter = |k: usize| w.get(k).map(|a| letters[*a]);
+            ShortFreeGroupTerm::new(letter(0), letter(1), letter(2))
+        };
+        words.sort_by_key(|w| term(w));
+        let elements: Vec<ShortFreeGroupTerm> = words.iter().map(|w| term(w)).collect();
+
+        let mut index_of_code = vec![u32::MAX; (m + 1).pow(3)];
+        let mut by_length = vec![Vec::new(); 4];
+        for (i, w) in words.iter().enumerate() {
+            index_of_code[w.iter().fold(0, |code, a| code * (m + 1) + a + 1)] = i as u32;
+            by_length[w.len()].push(i);
         }
 
-        TruncatedGroup {
-            generators: generators,
-            elements:   Box::new(elements)
+        let mut ball = TruncatedGroup {
+            generators,
+            elements,
+            inverses:   Vec::new(),
+            words,
+            inverse_letter,
+            index_of_code,
+            by_length,
+            products:   Vec::new(),
+            conjugates: Vec::new()
+        };
+        ball.inverses = ball.words.iter().map(|w| ball.index_of(&ball.inverse_word(w))).collect();
+        ball.products = (0 .. ball.len()).map(|_| OnceLock::new()).collect();
+        ball.conjugates = (0 .. ball.len()).map(|_| OnceLock::new()).collect();
+        ball
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the index of `x`, or `None` if `x` is not in the ball.
+    pub fn index(&self, x: &ShortFreeGroupTerm) -> Option<usize> {
+        self.elements.binary_search(x).ok()
+    }
+
+    pub fn identity(&self) -> usize {
+        0
+    }
+
+    /// Returns the index of the inverse of the element with index `x`.
+    pub fn inverse(&self, x: usize) -> usize {
+        self.inverses[x]
+    }
+
+    /// Returns the set of the indices of `elements`.
+    ///
+    /// # Panics
+    /// Panics if one of `elements` is not in the ball.
+    pub fn set<'a>(&self, elements: impl IntoIterator<Item = &'a ShortFreeGroupTerm>) -> BitSet {
+        let mut set = BitSet::new(self.len());
+        for x in elements {
+            set.insert(self.index(x).expect("The element is in the ball."));
         }
+        set
+    }
+
+    /// Returns the elements whose indices are in `set`.
+    pub fn terms(&self, set: &BitSet) -> BTreeSet<ShortFreeGroupTerm> {
+        set.iter().map(|x| self.elements[x]).collect()
+    }
+
+    /// Returns the index of the product of the elements with indices `x` and
+    /// `y`, or `None` if it is not in the ball.
+    pub fn product(&self, x: usize, y: usize) -> Option<usize> {
+        let row = self.row(x);
+        row.binary_search_by_key(&(y as u32), |(y, _)| *y).ok().map(|k| row[k].1 as usize)
+    }
+
+    /// Returns the pairs of the indices of `y` and `xy`, for the `y` such that
+    /// `xy` is in the ball, by increasing `y`.
+    pub fn right_products(&self, x: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.row(x).iter().map(|(y, product)| (*y as usize, *product as usize))
+    }
+
+    /// Returns the pairs of the indices of `y` and `yx`, for the `y` such that
+    /// `yx` is in the ball.
+    pub fn left_products(&self, x: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        // `yx` is the inverse of `x⁻¹y⁻¹`
+        self.right_products(self.inverse(x)).map(move |(y, product)| (self.inverse(y), self.inverse(product)))
+    }
+
+    /// Returns the pairs of the indices of `g` and `g⁻¹xg`, for the `g` which
+    /// are generators or their inverses such that `g⁻¹xg` is in the ball.
+    pub fn conjugates(&self, x: usize) -> &[(usize, usize)] {
+        self.conjugates[x].get_or_init(|| {
+            let x = &self.words[x];
+            // `g⁻¹xg`, which is short if something cancels
+            self.inverse_letter.iter().enumerate().filter_map(|(g, g_inverse)| {
+                let conjugate = match (x.first() == Some(&g), x.last() == Some(g_inverse)) {
+                    _ if x.is_empty() => Vec::new(),
+                    (true, true) => x[1 .. x.len() - 1].to_vec(),
+                    (true, _) => [&x[1 ..], &[g]].concat(),
+                    (_, true) => [&[*g_inverse], &x[.. x.len() - 1]].concat(),
+                    _ if x.len() == 1 => vec![*g_inverse, x[0], g],
+                    _ => return None
+                };
+                Some((self.index_of(&[g]), self.index_of(&conjugate)))
+            }).collect()
+        })
+    }
+
+    fn row(&self, x: usize) -> &[(u32, u32)] {
+        self.products[x].get_or_init(|| {
+            let x = &self.words[x];
+            // the `y` which cancel exactly the last `c` letters of `x`,
+            // i.e., `x = ps` and `y = s⁻¹w` with `xy = pw`
+            let mut row = Vec::new();
+            for c in 0 ..= x.len() {
+                let (p, s) = x.split_at(x.len() - c);
+                let longest = 3 - std::cmp::max(p.len(), c);
+                for w in self.by_length[..= longest].iter().flatten().map(|w| &self.words[*w]) {
+                    let cancels_more = matches!((p.last(), w.first()), (Some(a), Some(b)) if *b == self.inverse_letter[*a]);
+                    if cancels_more || (!w.is_empty() && w.first() == s.first()) {
+                        continue;
+                    }
+                    let y = [self.inverse_word(s).as_slice(), w].concat();
+                    let product = [p, w].concat();
+                    row.push((self.index_of(&y) as u32, self.index_of(&product) as u32));
+                }
+            }
+            row.sort_unstable();
+            row
+        })
+    }
+
+    fn index_of(&self, w: &[usize]) -> usize {
+        let m = self.inverse_letter.len();
+        self.index_of_code[w.iter().fold(0, |code, a| code * (m + 1) + a + 1)] as usize
+    }
+
+    fn inverse_word(&self, w: &[usize]) -> Vec<usize> {
+        w.iter().rev().map(|a| self.inverse_letter[*a]).collect()
     }
 }
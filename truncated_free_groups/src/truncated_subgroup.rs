@@ -1,10 +1,15 @@
-use std::collections::{BTreeSet, BTreeMap};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 use l_group_formulas::short_free_group_term::*;
 use l_group_formulas::literal::Literal;
-use l_group_formulas::Term;
+use super::bit_set::BitSet;
+use super::truncated_group::TruncatedGroup;
 
-/// Represents the closed ball of radius 3 around e in the Cayley
-/// graph of a free group with respect to the standard free generating set.
+/// Represents a subset of the closed ball of radius 3 around e in the Cayley
+/// graph of a free group with respect to the standard free generating set,
+/// which is closed under products within the ball.
+///
+/// The elements are the `BitSet` of their indices in `ball`.
 /// 
 /// # Examples
 /// Basic usage:
@@ -22,313 +27,305 @@ use l_group_formulas::Term;
 /// gens.insert(Literal::from('x'));
 /// gens.insert(Literal::from('y'));
 /// gens.insert(Literal::from('z'));
-/// let truncated = TruncatedSubgroup::new(Box::new(set), gens, false, false);
-/// let mut expected = Box::new(BTreeSet::new());
+/// let truncated = TruncatedSubgroup::new(set, gens, false, false, false);
+/// let mut expected = BTreeSet::new();
 /// expected.insert(s);
 /// expected.insert(t);
 /// expected.insert(ShortFreeGroupTerm::from("xz"));
-/// assert_eq!(expected, truncated.elements.clone());
+/// assert_eq!(expected, truncated.terms());
 /// ```
-#[derive(Debug)]
+/// If `normal` is set, the elements are also closed under conjugation by
+/// generators, as far as the conjugates are short.
+/// ```
+/// # use truncated_free_groups::truncated_subgroup::TruncatedSubgroup;
+/// # use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
+/// # use l_group_formulas::literal::Literal;
+/// # use std::collections::BTreeSet;
+/// let mut set = BTreeSet::new();
+/// set.insert(ShortFreeGroupTerm::from("x"));
+/// let mut gens = BTreeSet::new();
+/// gens.insert(Literal::from('x'));
+/// gens.insert(Literal::from('y'));
+/// let normal = TruncatedSubgroup::new(set, gens, false, false, true);
+/// assert!(normal.contains(&ShortFreeGroupTerm::from("Yxy")));
+/// assert!(normal.contains(&ShortFreeGroupTerm::from("yxY")));
+/// ```
+/// Inserting elements can be undone by rolling back to a checkpoint, which
+/// forgets everything added since.
+/// ```
+/// # use truncated_free_groups::truncated_subgroup::{TruncatedSubgroup, Insert};
+/// # use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
+/// # use l_group_formulas::literal::Literal;
+/// # use std::collections::BTreeSet;
+/// let mut set = BTreeSet::new();
+/// set.insert(ShortFreeGroupTerm::from("x"));
+/// let gens: BTreeSet<Literal> = "xy".chars().map(Literal::from).collect();
+/// let mut subgroup = TruncatedSubgroup::new(set, gens, false, false, false);
+/// let before = subgroup.terms();
+/// let checkpoint = subgroup.checkpoint();
+/// let y = subgroup.ball.index(&ShortFreeGroupTerm::from("y")).unwrap();
+/// assert!(subgroup.insert(y).len() > 1);
+/// assert!(subgroup.contains(&ShortFreeGroupTerm::from("xy")));
+/// subgroup.rollback(checkpoint);
+/// assert_eq!(before, subgroup.terms());
+/// ```
+/// A subgroup built by `within_recording` remembers why each element is in
+/// it, and so how it came to contain `e`.
+/// ```
+/// # use truncated_free_groups::truncated_subgroup::{TruncatedSubgroup, Justification};
+/// # use truncated_free_groups::truncated_group::TruncatedGroup;
+/// # use l_group_formulas::short_free_group_term::ShortFreeGroupTerm;
+/// # use l_group_formulas::literal::Literal;
+/// # use std::sync::Arc;
+/// let ball = Arc::new(TruncatedGroup::new("xyz".chars().map(Literal::from).collect()));
+/// let elements = ball.set(&["xy", "Yz", "ZX"].iter().map(|s| ShortFreeGroupTerm::from(*s)).collect::<Vec<_>>());
+/// let subgroup = TruncatedSubgroup::within_recording(ball.clone(), elements, true, false);
+/// let e = ball.identity();
+/// assert!(matches!(subgroup.justification(e), Some(Justification::Product(_, _))));
+/// let derivation = subgroup.derivation(e).unwrap();
+/// assert_eq!(Some(&(e, subgroup.justification(e).unwrap())), derivation.last());
+/// assert_eq!("xy·Yz gives xz, and xz·ZX gives e", subgroup.argument(e).unwrap());
+/// ```
+#[derive(Debug, Clone)]
 pub struct TruncatedSubgroup {
-    pub elements:            Box<BTreeSet<ShortFreeGroupTerm>>,
-    // pub gens_of_ambient_group:   BTreeSet<Literal>,
-    starts_with_single:      Box<BTreeMap<Literal, BTreeSet<ShortFreeGroupTerm>>>,
-    previously_new:          BTreeSet<ShortFreeGroupTerm>,
-    ends_with_single:        Box<BTreeMap<Literal, BTreeSet<ShortFreeGroupTerm>>>,
-    starts_with_pair:        Box<BTreeMap<(Literal, Literal), BTreeSet<ShortFreeGroupTerm>>>,
-    ends_with_pair:          Box<BTreeMap<(Literal, Literal), BTreeSet<ShortFreeGroupTerm>>>,
-    length_one:              Box<BTreeSet<ShortFreeGroupTerm>>,
-    length_two:              Box<BTreeSet<ShortFreeGroupTerm>>,
-    length_three:            Box<BTreeSet<ShortFreeGroupTerm>>,
-    break_at_identity:       bool
+    pub ball:                Arc<TruncatedGroup>,
+    pub elements:            BitSet,
+    previously_new:          BitSet,
+    /// The elements in the order they were added, to roll back.
+    trail:                   Vec<usize>,
+    /// Why the elements of `trail` were added, if this is recorded.
+    reasons:                 Option<Vec<Justification>>,
+    break_at_identity:       bool,
+    normal:                  bool
 }
 
 impl TruncatedSubgroup {
     pub fn new(
-        elements:          Box<BTreeSet<ShortFreeGroupTerm>>, 
+        elements:          BTreeSet<ShortFreeGroupTerm>,
         gens:              BTreeSet<Literal>,
         closed:            bool,
         break_at_identity: bool,
+        normal:            bool
     ) -> TruncatedSubgroup {
-        // close gens under inversion
-        let mut gens_of_ambient_group = BTreeSet::new();
-        for g in gens {
-            gens_of_ambient_group.insert(g);
-            gens_of_ambient_group.insert(g.inverse());
-        }
+        let ball = Arc::new(TruncatedGroup::new(gens));
+        let elements = ball.set(&elements);
+        TruncatedSubgroup::within(ball, elements, closed, break_at_identity, normal)
+    }
+
+    /// Returns a subgroup as `new` does, but in a given `ball`, which can be
+    /// shared with other subgroups, with `elements` given by their indices.
+    pub fn within(
+        ball:              Arc<TruncatedGroup>,
+        elements:          BitSet,
+        closed:            bool,
+        break_at_identity: bool,
+        normal:            bool
+    ) -> TruncatedSubgroup {
+        TruncatedSubgroup::build(ball, elements, closed, break_at_identity, normal, false)
+    }
 
-        // initialize categories
-        let mut length_one   = Box::new(BTreeSet::new());
-        let mut length_two   = Box::new(BTreeSet::new());
-        let mut length_three = Box::new(BTreeSet::new());
+    /// Returns the closure of `elements` as `within` does, which records why
+    /// it adds each element, see `derivation`.
+    pub fn within_recording(
+        ball:              Arc<TruncatedGroup>,
+        elements:          BitSet,
+        break_at_identity: bool,
+        normal:            bool
+    ) -> TruncatedSubgroup {
+        TruncatedSubgroup::build(ball, elements, false, break_at_identity, normal, true)
+    }
 
-        let mut starts_with_single = Box::new(BTreeMap::new());
-        let mut starts_with_pair   = Box::new(BTreeMap::new());
-        let mut ends_with_single   = Box::new(BTreeMap::new());
-        let mut ends_with_pair     = Box::new(BTreeMap::new());
+    fn build(
+        ball:              Arc<TruncatedGroup>,
+        elements:          BitSet,
+        closed:            bool,
+        break_at_identity: bool,
+        normal:            bool,
+        record:            bool
+    ) -> TruncatedSubgroup {
+        let previously_new = if !closed {
+            elements.clone()
+        } else {
+            BitSet::new(ball.len())
+        };
 
-        for g in &gens_of_ambient_group {
-            starts_with_single.insert(*g, BTreeSet::new());
-            ends_with_single.insert(*g, BTreeSet::new());
-            for h in &gens_of_ambient_group {
-                starts_with_pair.insert((*g, *h), BTreeSet::new());
-                ends_with_pair.insert((*g, *h), BTreeSet::new());
-            }
+        // the elements to close count as added, so that they are on the trail
+        let (elements, previously_new) = match record && !closed {
+            true  => (BitSet::new(ball.len()), elements),
+            false => (elements, previously_new)
+        };
+        let mut sub = TruncatedSubgroup {
+            ball,
+            elements,
+            previously_new,
+            trail:             Vec::new(),
+            reasons:           if record { Some(Vec::new()) } else { None },
+            break_at_identity,
+            normal
+        };
+        if !closed { sub.close(); }
+        sub
+    }
+
+    pub fn contains(&self, x: &ShortFreeGroupTerm) -> bool {
+        self.ball.index(x).is_some_and(|x| self.elements.contains(x))
+    }
+
+    /// Returns the elements, rather than their indices.
+    pub fn terms(&self) -> BTreeSet<ShortFreeGroupTerm> {
+        self.ball.terms(&self.elements)
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.trail.len())
+    }
+
+    /// Removes the elements added since `checkpoint`.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        for x in self.trail.drain(checkpoint.0 ..) {
+            self.elements.remove(x);
         }
+        if let Some(reasons) = &mut self.reasons {
+            reasons.truncate(checkpoint.0);
+        }
+    }
 
-        for y in &*elements {
-            match (y.left, y.mid, y.right) {
-                (Some(a), None, None) => {
-                    length_one.insert(*y);
-                    starts_with_single.get_mut(&a).unwrap().insert(*y);
-                    ends_with_single.get_mut(&a).unwrap().insert(*y);
-                },
-                (Some(a), Some(b), None) => {
-                    length_two.insert(*y);
-                    starts_with_single.get_mut(&a).unwrap().insert(*y);
-                    ends_with_single.get_mut(&b).unwrap().insert(*y);
-                    starts_with_pair.get_mut(&(a, b)).unwrap().insert(*y);
-                    ends_with_pair.get_mut(&(a, b)).unwrap().insert(*y);
-                },
-                (Some(a), Some(b), Some(c)) => {
-                    length_three.insert(*y);
-                    starts_with_single.get_mut(&a).unwrap().insert(*y);
-                    ends_with_single.get_mut(&c).unwrap().insert(*y);
-                    starts_with_pair.get_mut(&(a, b)).unwrap().insert(*y);
-                    ends_with_pair.get_mut(&(b, c)).unwrap().insert(*y);
-                }
-                _ => {} // is identity
-            };
+    /// Returns why the element with index `x` is in the subgroup, or `None`
+    /// if it is not, or if the subgroup does not record this.
+    pub fn justification(&self, x: usize) -> Option<Justification> {
+        let reasons = self.reasons.as_ref()?;
+        match self.trail.iter().position(|y| *y == x) {
+            Some(i) => Some(reasons[i]),
+            None if self.elements.contains(x) => Some(Justification::Given),
+            None => None
         }
+    }
 
-        let previously_new: BTreeSet<ShortFreeGroupTerm>;
-        if !closed {
-            previously_new = *elements.clone()
-        } else {
-            previously_new = BTreeSet::new();
+    /// Returns the elements the element with index `x` was derived from, with
+    /// their justifications, each after the ones it was derived from, and
+    /// ending with `x`. Returns `None` if `x` is not in the subgroup, or if
+    /// the subgroup does not record why its elements are in it.
+    pub fn derivation(&self, x: usize) -> Option<Vec<(usize, Justification)>> {
+        let reasons = self.reasons.as_ref()?;
+        if !self.elements.contains(x) {
+            return None;
         }
+        let positions: BTreeMap<usize, usize> = self.trail.iter().enumerate().map(|(i, y)| (*y, i)).collect();
+        // elements not on the trail were there before, so they are given
+        let mut given = BTreeSet::new();
+        let mut needed = BTreeSet::new();
+        let mut todo = vec![x];
+        while let Some(y) = todo.pop() {
+            match positions.get(&y) {
+                None => { given.insert(y); },
+                Some(i) => if needed.insert(*i) {
+                    match reasons[*i] {
+                        Justification::Given => {},
+                        Justification::Product(a, b) => todo.extend(&[a, b]),
+                        Justification::Conjugate(a, _) => todo.push(a)
+                    }
+                }
+            };
+        }
+        let given = given.into_iter().map(|y| (y, Justification::Given));
+        Some(given.chain(needed.into_iter().map(|i| (self.trail[i], reasons[i]))).collect())
+    }
 
-        let mut sub = TruncatedSubgroup {
-            elements:              elements.clone(),
-            // gens_of_ambient_group: gens_of_ambient_group,
-            previously_new:        previously_new,
-            starts_with_single:    starts_with_single,
-            starts_with_pair:      starts_with_pair,
-            ends_with_single:      ends_with_single,
-            ends_with_pair:        ends_with_pair,
-            length_one:            length_one,
-            length_two:            length_two,
-            length_three:          length_three,
-            break_at_identity:     break_at_identity,
-        };
-        if !closed { sub.close(); }
-        return sub;
+    /// Returns the `derivation` of the element with index `x` as a sentence,
+    /// leaving out the given elements.
+    pub fn argument(&self, x: usize) -> Option<String> {
+        let term = |y: usize| self.ball.elements[y].to_string();
+        let steps: Vec<String> = self.derivation(x)?.into_iter()
+            .filter_map(|(y, justification)| match justification {
+                Justification::Given => None,
+                Justification::Product(a, b) => Some(format!("{}·{} gives {}", term(a), term(b), term(y))),
+                Justification::Conjugate(a, g) => Some(format!("conjugating {} by {} gives {}", term(a), term(g), term(y)))
+            })
+            .collect();
+        Some(match steps.split_last() {
+            None => format!("{} is given", term(x)),
+            Some((last, [])) => last.clone(),
+            Some((last, steps)) => format!("{}, and {}", steps.join(", "), last)
+        })
     }
 }
 
+/// Why an element is in a `TruncatedSubgroup`, by the indices of elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Justification {
+    /// It was one of the elements to close, or inserted.
+    Given,
+    /// It is the product of the two elements.
+    Product(usize, usize),
+    /// It is the conjugate `g⁻¹xg` of the first element `x` by the second `g`,
+    /// which is a generator or the inverse of one.
+    Conjugate(usize, usize)
+}
+
+/// The state of a `TruncatedSubgroup` to roll back to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
 pub trait Insert {
-    /// Inserts `element` and returns what was newly added.
-    fn insert(&mut self, element: ShortFreeGroupTerm) -> BTreeSet<ShortFreeGroupTerm>;
+    /// Inserts the element with index `element` and returns the indices of
+    /// what was newly added, in the order it was added.
+    fn insert(&mut self, element: usize) -> &[usize];
 }
 
 impl Insert for TruncatedSubgroup {
-    fn insert(&mut self, element: ShortFreeGroupTerm) -> BTreeSet<ShortFreeGroupTerm> {
-        self.elements.insert(element);
+    fn insert(&mut self, element: usize) -> &[usize] {
         self.previously_new.insert(element);
-        return self.close();
+        self.close()
     }
 }
 
 trait Closable {
-    fn close(&mut self) -> BTreeSet<ShortFreeGroupTerm>;
+    fn close(&mut self) -> &[usize];
 }
 
 impl Closable for TruncatedSubgroup {
-    fn close(&mut self) -> BTreeSet<ShortFreeGroupTerm> {
-        let mut output = BTreeSet::new();
-        let mut found_new_element = true;
-        let mut new_elements_buffer: BTreeSet<ShortFreeGroupTerm> = self.previously_new.clone();
-        while found_new_element {
-            if self.break_at_identity {
-                if self.elements.contains(&ShortFreeGroupTerm::new(None, None, None)) {
-                    return output;
-                }
+    fn close(&mut self) -> &[usize] {
+        let ball = self.ball.clone();
+        let start = self.trail.len();
+        let mut new_elements = std::mem::replace(&mut self.previously_new, BitSet::new(ball.len()));
+        // why the elements found were new, if this is recorded
+        let mut found_by = BTreeMap::new();
+        while !new_elements.is_empty() {
+            if self.break_at_identity && self.elements.contains(ball.identity()) {
+                return &self.trail[start ..];
             }
 
-            found_new_element = false;
-            for y in &new_elements_buffer {
-                self.elements.insert(y.clone());
-                output.insert(y.clone());
-                match (y.left, y.mid, y.right) {
-                    (Some(a), None, None) => {
-                        self.length_one.insert(*y);
-                        self.starts_with_single.get_mut(&a).unwrap().insert(*y);
-                        self.ends_with_single.get_mut(&a).unwrap().insert(*y);
-                    },
-                    (Some(a), Some(b), None) => {
-                        self.length_two.insert(*y);
-                        self.starts_with_single.get_mut(&a).unwrap().insert(*y);
-                        self.ends_with_single.get_mut(&b).unwrap().insert(*y);
-                        self.starts_with_pair.get_mut(&(a, b)).unwrap().insert(*y);
-                        self.ends_with_pair.get_mut(&(a, b)).unwrap().insert(*y);
-                    },
-                    (Some(a), Some(b), Some(c)) => {
-                        self.length_three.insert(*y);
-                        self.starts_with_single.get_mut(&a).unwrap().insert(*y);
-                        self.ends_with_single.get_mut(&c).unwrap().insert(*y);
-                        self.starts_with_pair.get_mut(&(a, b)).unwrap().insert(*y);
-                        self.ends_with_pair.get_mut(&(b, c)).unwrap().insert(*y);
+            for x in new_elements.iter() {
+                if self.elements.insert(x) {
+                    self.trail.push(x);
+                    if let Some(reasons) = &mut self.reasons {
+                        reasons.push(*found_by.get(&x).unwrap_or(&Justification::Given));
                     }
-                    _ => {} // is identity
-                };
+                }
             }
-            self.previously_new = new_elements_buffer.clone();
-            new_elements_buffer = BTreeSet::new();
-            for x in &self.previously_new {
-                match x.len() {
-                    0 => {},
-                    1 => {
-                        // x * y is shorter than 3 if either y is shorter 
-                        // than 2 or there is cancellation, i.e., y begins
-                        // with x^-1. Same for y * x, except then y ends
-                        // with x^-1.
-                        for y in &*self.length_one {
-                            let maybe_new = *x * *y;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                            let maybe_new = *y * *x;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                        }
-                        for y in &*self.length_two {
-                            let maybe_new = *x * *y;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                            let maybe_new = *y * *x;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                        }
-                        let lit = x.left.unwrap();
-                        let cancelling_candidates_start = self.starts_with_single.get(&lit.inverse()).unwrap();
-                        for y in cancelling_candidates_start {
-                            let maybe_new = *x * *y;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                        }
-                        let cancelling_candidates_end = self.ends_with_single.get(&lit.inverse()).unwrap();
-                        for y in cancelling_candidates_end {
-                            let maybe_new = *y * *x;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                        }
-                    },
-                    2 => {
-                        // if y has length 1, then x * y and y * x are of length <= 3
-                        // if y has length 2 or 3, then 
-                        //      x * y has length <= 3 if y starts with x.mid.inverse()
-                        //      y * x has length <= 3 if y ends with x.left.inverse()
-                        for y in &*self.length_one {
-                            let maybe_new = *x * *y;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                            let maybe_new = *y * *x;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                        }
-                        let cancelling_candidates_start = self.starts_with_single.get(&x.mid.unwrap().inverse()).unwrap();
-                        for y in cancelling_candidates_start {
-                            let maybe_new = *x * *y;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                        }
-                        let cancelling_candidates_end = self.ends_with_single.get(&x.left.unwrap().inverse()).unwrap();
-                        for y in cancelling_candidates_end {
-                            let maybe_new = *y * *x;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                        }
-                    },
-                    3 => {
-                        // if y has length 1 or 2, then x * y is of length <= 3 if y.left == x.right.inverse(),
-                        // and                     y * x is of length <= 3 if y.(left/mid) == x.left.inverse().
-                        // if y has length 3, then x * y is of length <= 3 if y.left == x.right.inverse() && y.mid == x.mid.inverse(),
-                        //                                  i.e., y starts with (x.right.inverse(), x.mid.inverse())
-                        // and                     y * x is of length <= 3 if y.right == x.left.inverse() && y.mid == x.mid.inverse(),
-                        //                                  i.e., y ends with (x.mid.inverse(), x.left.inverse())
-                        for y in &*self.length_one {
-                            if y.left.unwrap() == x.right.unwrap().inverse() {
-                                let maybe_new = *x * *y;
-                                if !self.elements.contains(&maybe_new) {
-                                    found_new_element = true;
-                                    new_elements_buffer.insert(maybe_new);
-                                }
-                            }
-                            if y.left.unwrap() == x.left.unwrap().inverse() {
-                                let maybe_new = *y * *x;
-                                if !self.elements.contains(&maybe_new) {
-                                    found_new_element = true;
-                                    new_elements_buffer.insert(maybe_new);
-                                }
-                            }
-                        }
-                        for y in &*self.length_two {
-                            if y.left.unwrap() == x.right.unwrap().inverse() {
-                                let maybe_new = *x * *y;
-                                if !self.elements.contains(&maybe_new) {
-                                    found_new_element = true;
-                                    new_elements_buffer.insert(maybe_new);
-                                }
-                            }
-                            if y.mid.unwrap() == x.left.unwrap().inverse() {
-                                let maybe_new = *y * *x;
-                                if !self.elements.contains(&maybe_new) {
-                                    found_new_element = true;
-                                    new_elements_buffer.insert(maybe_new);
-                                }
-                            }
-                        }
-                        let cancelling_candidates_start = self.starts_with_pair.get(&(x.right.unwrap().inverse(), x.mid.unwrap().inverse())).unwrap();
-                        for y in cancelling_candidates_start {
-                            let maybe_new = *x * *y;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
-                        }
-                        let cancelling_candidates_end = self.ends_with_pair.get(&(x.mid.unwrap().inverse(), x.left.unwrap().inverse())).unwrap();
-                        for y in cancelling_candidates_end {
-                            let maybe_new = *y * *x;
-                            if !self.elements.contains(&maybe_new) {
-                                found_new_element = true;
-                                new_elements_buffer.insert(maybe_new);
-                            }
+            let mut found = BitSet::new(ball.len());
+            found_by.clear();
+            let recording = self.reasons.is_some();
+            // semi-naively, only the products involving a new element can be new
+            for x in new_elements.iter() {
+                let products = ball.right_products(x).map(|(y, product)| (y, Justification::Product(x, y), product))
+                    .chain(ball.left_products(x).map(|(y, product)| (y, Justification::Product(y, x), product)));
+                for (y, justification, product) in products {
+                    if self.elements.contains(y) && found.insert(product) && recording {
+                        found_by.insert(product, justification);
+                    }
+                }
+                if self.normal {
+                    for (g, conjugate) in ball.conjugates(x) {
+                        if found.insert(*conjugate) && recording {
+                            found_by.insert(*conjugate, Justification::Conjugate(x, *g));
                         }
-                    },
-                    _ => { panic!("Invalid short free group term!") }
-                };
+                    }
+                }
             }
+            found.difference_with(&self.elements);
+            new_elements = found;
         }
-        return output;
+        &self.trail[start ..]
     }
 }